@@ -1,16 +1,230 @@
-use crate::primitives::{ChainAddress, ChainName, IdentityContext, JudgementStateBlanked};
+use crate::database::{StatsBucket, StatsInterval, StatsQuery};
+use crate::primitives::{
+    ChainAddress, ChainName, IdentityContext, JudgementState, JudgementStateBlanked, Timestamp,
+};
 use crate::Database;
 use std::str::FromStr;
 
 pub type Result<T> = std::result::Result<T, Response>;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum Command {
-    Status(ChainAddress),
-    Verify(ChainAddress, Vec<RawFieldName>),
+    Status(TargetAddress),
+    Verify(TargetAddress, Vec<RawFieldName>),
+    Unverify(TargetAddress, Vec<RawFieldName>),
+    Reset(TargetAddress),
+    Pending(Option<ChainName>),
+    Search(String),
+    Remove(TargetAddress),
+    Resend(TargetAddress),
+    Audit(Option<TargetAddress>),
+    Note(TargetAddress, String),
+    Notes(TargetAddress),
+    Approve(TargetAddress),
+    Approvals,
+    Cancel(TargetAddress),
+    Stats(StatsQuery),
     Help,
 }
 
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Status(_) => "status",
+            Command::Verify(..) => "verify",
+            Command::Unverify(..) => "unverify",
+            Command::Reset(_) => "reset",
+            Command::Pending(_) => "pending",
+            Command::Search(_) => "search",
+            Command::Remove(_) => "remove",
+            Command::Resend(_) => "resend",
+            Command::Audit(_) => "audit",
+            Command::Note(..) => "note",
+            Command::Notes(_) => "notes",
+            Command::Approve(_) => "approve",
+            Command::Approvals => "approvals",
+            Command::Cancel(_) => "cancel",
+            Command::Stats(_) => "stats",
+            Command::Help => "help",
+        }
+    }
+    /// The identity the command refers to, if any.
+    pub fn target(&self, chains: &[ChainName]) -> Option<IdentityContext> {
+        match self {
+            Command::Status(addr)
+            | Command::Verify(addr, _)
+            | Command::Unverify(addr, _)
+            | Command::Reset(addr)
+            | Command::Remove(addr)
+            | Command::Resend(addr)
+            | Command::Audit(Some(addr))
+            | Command::Note(addr, _)
+            | Command::Notes(addr)
+            | Command::Approve(addr)
+            | Command::Cancel(addr) => addr.resolve(chains).ok(),
+            Command::Pending(_)
+            | Command::Search(_)
+            | Command::Audit(None)
+            | Command::Approvals
+            | Command::Stats(_)
+            | Command::Help => None,
+        }
+    }
+}
+
+/// Address argument of admin commands. The chain is either specified
+/// explicitly (`<CHAIN>:<ADDR>`) or derived from the SS58 prefix.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", from = "TargetAddressEntry")]
+pub struct TargetAddress {
+    pub address: ChainAddress,
+    pub chain: Option<ChainName>,
+}
+
+// Audit log entries written before the chain could be specified only contain
+// the address.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TargetAddressEntry {
+    Address(ChainAddress),
+    WithChain {
+        address: ChainAddress,
+        chain: Option<ChainName>,
+    },
+}
+
+impl From<TargetAddressEntry> for TargetAddress {
+    fn from(entry: TargetAddressEntry) -> Self {
+        match entry {
+            TargetAddressEntry::Address(address) => TargetAddress::from(address),
+            TargetAddressEntry::WithChain { address, chain } => TargetAddress { address, chain },
+        }
+    }
+}
+
+impl TargetAddress {
+    /// Determines the identity context on one of the given (configured)
+    /// chains. Fails if the address is not valid on the specified chain, or if
+    /// no chain was specified and the address is valid on multiple chains.
+    /// Addresses with the generic Substrate prefix are re-encoded with the
+    /// prefix of the chain, since identities are stored that way.
+    pub fn resolve(&self, chains: &[ChainName]) -> Result<IdentityContext> {
+        let valid: Vec<ChainName> = self
+            .address
+            .valid_chains()
+            .into_iter()
+            .filter(|chain| chains.contains(chain))
+            .collect();
+
+        let chain = match self.chain {
+            Some(chain) if valid.contains(&chain) => chain,
+            Some(_) => return Err(Response::InvalidAddress(self.clone())),
+            None => match valid.as_slice() {
+                [chain] => *chain,
+                [] => return Err(Response::InvalidAddress(self.clone())),
+                _ => return Err(Response::AmbiguousChain(self.address.clone(), valid)),
+            },
+        };
+
+        let address = match self.address.public_key() {
+            Some(key) if self.address.is_generic() => {
+                ChainAddress::from_public_key(chain.ss58_prefix(), &key)
+            }
+            _ => self.address.clone(),
+        };
+
+        Ok(IdentityContext::new(address, chain))
+    }
+}
+
+impl From<ChainAddress> for TargetAddress {
+    fn from(address: ChainAddress) -> Self {
+        TargetAddress {
+            address,
+            chain: None,
+        }
+    }
+}
+
+impl From<IdentityContext> for TargetAddress {
+    fn from(context: IdentityContext) -> Self {
+        TargetAddress {
+            address: context.address,
+            chain: Some(context.chain),
+        }
+    }
+}
+
+impl FromStr for TargetAddress {
+    type Err = Response;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some((chain, address)) => Ok(TargetAddress {
+                address: ChainAddress::from(address.to_string()),
+                chain: Some(parse_chain(chain)?),
+            }),
+            None => Ok(TargetAddress::from(ChainAddress::from(s.to_string()))),
+        }
+    }
+}
+
+impl std::fmt::Display for TargetAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.chain {
+            Some(chain) => write!(f, "{}:{}", chain.as_str(), self.address.as_str()),
+            None => write!(f, "{}", self.address.as_str()),
+        }
+    }
+}
+
+/// Roles of admins, from the least to the most privileged.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    // Read-only access.
+    Viewer,
+    // Can verify individual fields, but not the full identity.
+    Verifier,
+    Superadmin,
+}
+
+impl Role {
+    pub fn allows(&self, command: &Command) -> bool {
+        match command {
+            Command::Status(_)
+            | Command::Pending(_)
+            | Command::Search(_)
+            | Command::Notes(_)
+            | Command::Approvals
+            | Command::Stats(_)
+            | Command::Help => true,
+            // Verifying `all` forces a judgement. Verifying the remaining
+            // fields of an identity is rejected when executed.
+            Command::Verify(_, fields) if fields.contains(&RawFieldName::All) => {
+                *self == Role::Superadmin
+            }
+            Command::Verify(..)
+            | Command::Unverify(..)
+            | Command::Resend(_)
+            | Command::Note(..) => *self >= Role::Verifier,
+            Command::Reset(_)
+            | Command::Remove(_)
+            | Command::Audit(_)
+            | Command::Approve(_)
+            | Command::Cancel(_) => *self == Role::Superadmin,
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Verifier => "verifier",
+            Role::Superadmin => "superadmin",
+        }
+    }
+}
+
 impl FromStr for Command {
     type Err = Response;
 
@@ -24,7 +238,7 @@ impl FromStr for Command {
                 return Err(Response::UnknownCommand);
             }
 
-            Ok(Command::Status(ChainAddress::from(parts[0].to_string())))
+            Ok(Command::Status(TargetAddress::from_str(parts[0])?))
         } else if s.starts_with("verify") {
             let parts: Vec<&str> = s.split(' ').skip(1).collect();
             if parts.len() < 2 {
@@ -32,12 +246,93 @@ impl FromStr for Command {
             }
 
             Ok(Command::Verify(
-                ChainAddress::from(parts[0].to_string()),
+                TargetAddress::from_str(parts[0])?,
                 parts[1..]
                     .iter()
                     .map(|s| RawFieldName::from_str(s))
                     .collect::<Result<Vec<RawFieldName>>>()?,
             ))
+        } else if s.starts_with("unverify") {
+            let parts: Vec<&str> = s.split(' ').skip(1).collect();
+            if parts.len() < 2 {
+                return Err(Response::UnknownCommand);
+            }
+
+            let fields = parts[1..]
+                .iter()
+                .map(|s| RawFieldName::from_str(s))
+                .collect::<Result<Vec<RawFieldName>>>()?;
+
+            // Use `reset` for the full identity.
+            if fields.contains(&RawFieldName::All) {
+                return Err(Response::InvalidSyntax(Some("all".to_string())));
+            }
+
+            Ok(Command::Unverify(
+                TargetAddress::from_str(parts[0])?,
+                fields,
+            ))
+        } else if s.starts_with("reset") {
+            Ok(Command::Reset(single_address(&s)?))
+        } else if s.starts_with("pending") {
+            let parts: Vec<&str> = s.split(' ').skip(1).collect();
+            match parts.as_slice() {
+                [] => Ok(Command::Pending(None)),
+                [chain] => Ok(Command::Pending(Some(parse_chain(chain)?))),
+                _ => Err(Response::UnknownCommand),
+            }
+        } else if s.starts_with("search") {
+            let parts: Vec<&str> = s.split(' ').skip(1).collect();
+            if parts.len() != 1 {
+                return Err(Response::UnknownCommand);
+            }
+
+            Ok(Command::Search(parts[0].to_string()))
+        } else if s.starts_with("remove") {
+            Ok(Command::Remove(single_address(&s)?))
+        } else if s.starts_with("resend") {
+            Ok(Command::Resend(single_address(&s)?))
+        } else if s.starts_with("audit") {
+            let parts: Vec<&str> = s.split(' ').skip(1).collect();
+            match parts.as_slice() {
+                [] => Ok(Command::Audit(None)),
+                [addr] => Ok(Command::Audit(Some(TargetAddress::from_str(addr)?))),
+                _ => Err(Response::UnknownCommand),
+            }
+        } else if s.starts_with("notes") {
+            Ok(Command::Notes(single_address(&s)?))
+        } else if s.starts_with("note") {
+            let mut parts = s.splitn(3, ' ').skip(1);
+            match (parts.next(), parts.next()) {
+                (Some(addr), Some(text)) if !text.trim().is_empty() => Ok(Command::Note(
+                    TargetAddress::from_str(addr)?,
+                    text.trim().to_string(),
+                )),
+                _ => Err(Response::UnknownCommand),
+            }
+        } else if s.starts_with("approvals") {
+            if s.split(' ').count() > 1 {
+                return Err(Response::UnknownCommand);
+            }
+
+            Ok(Command::Approvals)
+        } else if s.starts_with("approve") {
+            Ok(Command::Approve(single_address(&s)?))
+        } else if s.starts_with("cancel") {
+            Ok(Command::Cancel(single_address(&s)?))
+        } else if s.starts_with("stats") {
+            let mut query = StatsQuery::default();
+            for part in s.split(' ').skip(1) {
+                if let Ok(interval) = StatsInterval::from_str(part) {
+                    if query.interval.replace(interval).is_some() {
+                        return Err(Response::UnknownCommand);
+                    }
+                } else if query.chain.replace(parse_chain(part)?).is_some() {
+                    return Err(Response::UnknownCommand);
+                }
+            }
+
+            Ok(Command::Stats(query))
         } else if s.starts_with("help") {
             let count = s.split(' ').count();
 
@@ -52,24 +347,203 @@ impl FromStr for Command {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Parses the address of commands which expect exactly one argument.
+fn single_address(s: &str) -> Result<TargetAddress> {
+    let parts: Vec<&str> = s.split(' ').skip(1).collect();
+    if parts.len() != 1 {
+        return Err(Response::UnknownCommand);
+    }
+
+    TargetAddress::from_str(parts[0])
+}
+
+fn parse_chain(s: &str) -> Result<ChainName> {
+    ChainName::from_str(s).map_err(|_| Response::InvalidSyntax(Some(s.to_string())))
+}
+
+/// Summary of an open judgement request.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingRequest {
+    pub context: IdentityContext,
+    // In seconds.
+    pub age: u64,
+    pub verified: usize,
+    pub total: usize,
+}
+
+impl From<JudgementState> for PendingRequest {
+    fn from(state: JudgementState) -> Self {
+        PendingRequest {
+            age: Timestamp::now()
+                .raw()
+                .saturating_sub(state.inserted_timestamp.raw()),
+            verified: state
+                .fields
+                .iter()
+                .filter(|f| f.challenge.is_verified())
+                .count(),
+            total: state.fields.len(),
+            context: state.context,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum Response {
-    Status(JudgementStateBlanked),
-    Verified(ChainAddress, Vec<RawFieldName>),
+    Status(Box<IdentityStatus>),
+    Verified(IdentityContext, Vec<RawFieldName>),
+    Unverified(IdentityContext, Vec<RawFieldName>),
+    Reset(IdentityContext),
+    Pending(Vec<PendingRequest>),
+    Search(Vec<IdentityContext>),
+    Removed(IdentityContext),
+    SecondChallengeResent(IdentityContext),
+    NoPendingSecondChallenge,
+    AuditLog(Vec<AuditEntry>),
+    PermissionDenied(Role),
     UnknownCommand,
-    IdentityNotFound,
+    IdentityNotFound(IdentityContext),
     InvalidSyntax(Option<String>),
-    FullyVerified(ChainAddress),
+    InvalidAddress(TargetAddress),
+    AmbiguousChain(ChainAddress, Vec<ChainName>),
+    FullyVerified(IdentityContext),
+    NoteAdded(IdentityContext),
+    Notes(IdentityContext, Vec<ModeratorNote>),
+    ApprovalRequested(PendingApproval),
+    ApprovalAlreadyPending(PendingApproval),
+    Approvals(Vec<PendingApproval>),
+    ApprovalCancelled(IdentityContext),
+    NoPendingApproval(IdentityContext),
+    SelfApproval(IdentityContext),
+    UnknownApprovalAdmin(String),
+    Stats(Vec<StatsBucket>),
     InternalError,
     Help,
 }
 
+impl Response {
+    /// Whether the command was executed successfully.
+    pub fn is_success(&self) -> bool {
+        !matches!(
+            self,
+            Response::UnknownCommand
+                | Response::IdentityNotFound(_)
+                | Response::InvalidSyntax(_)
+                | Response::InvalidAddress(_)
+                | Response::AmbiguousChain(..)
+                | Response::NoPendingSecondChallenge
+                | Response::PermissionDenied(_)
+                | Response::ApprovalAlreadyPending(_)
+                | Response::NoPendingApproval(_)
+                | Response::SelfApproval(_)
+                | Response::UnknownApprovalAdmin(_)
+                | Response::InternalError
+        )
+    }
+}
+
+/// Verification state of an identity as shown to admins.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct IdentityStatus {
+    pub state: JudgementStateBlanked,
+    // Never exposed via the public API.
+    pub notes: Vec<ModeratorNote>,
+}
+
+/// Evidence or remarks recorded by a moderator, e.g. how a field was verified
+/// manually.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ModeratorNote {
+    pub context: IdentityContext,
+    pub timestamp: Timestamp,
+    pub author: String,
+    pub text: String,
+}
+
+impl std::fmt::Display for ModeratorNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.timestamp.raw(),
+            self.author,
+            self.text
+        )
+    }
+}
+
+/// Full manual verification which awaits the approval of a second admin.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingApproval {
+    pub context: IdentityContext,
+    pub requested_by: String,
+    pub requested_at: Timestamp,
+    pub expires_at: Timestamp,
+}
+
+impl std::fmt::Display for PendingApproval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requested by {}, expires in {}",
+            describe(&self.context),
+            self.requested_by,
+            format_age(self.expires_at.raw().saturating_sub(Timestamp::now().raw()))
+        )
+    }
+}
+
+/// Record of an executed admin command.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AuditEntry {
+    pub timestamp: Timestamp,
+    // Matrix handle, name of the API token or subject of the client
+    // certificate.
+    pub actor: String,
+    pub command: Command,
+    // The identity the command refers to, if any.
+    pub context: Option<IdentityContext>,
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    // The role of the actor does not permit the command.
+    Forbidden,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+            AuditOutcome::Forbidden => "forbidden",
+        }
+    }
+}
+
 impl std::fmt::Display for Response {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
-            Response::Status(state) => serde_json::to_string_pretty(state).unwrap(),
-            Response::Verified(_, fields) => {
-                format!("Verified the following fields: {}", {
+            Response::Status(status) => {
+                let state = serde_json::to_string_pretty(&status.state).unwrap();
+                if status.notes.is_empty() {
+                    state
+                } else {
+                    format!("{}\n\nNotes:\n{}", state, format_notes(&status.notes))
+                }
+            }
+            Response::Verified(context, fields) => {
+                format!("Verified the following fields of {}: {}", describe(context), {
                     let mut all = String::new();
                     for field in fields {
                         all.push_str(&format!("{}, ", field));
@@ -82,9 +556,97 @@ impl std::fmt::Display for Response {
                     all
                 })
             }
+            Response::Unverified(context, fields) => {
+                format!(
+                    "Unverified the following fields of {}: {}",
+                    describe(context),
+                    fields
+                        .iter()
+                        .map(|f| f.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            }
+            Response::Reset(context) => {
+                format!(
+                    "Reset the identity {}, all challenges have been replaced",
+                    describe(context)
+                )
+            }
+            Response::Pending(pending) => {
+                if pending.is_empty() {
+                    "There are no pending requests".to_string()
+                } else {
+                    pending
+                        .iter()
+                        .map(|p| {
+                            format!(
+                                "{}: {}/{} fields verified, open for {}",
+                                describe(&p.context),
+                                p.verified,
+                                p.total,
+                                format_age(p.age)
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                }
+            }
+            Response::Search(found) => {
+                if found.is_empty() {
+                    "No identity specifies that value".to_string()
+                } else {
+                    found
+                        .iter()
+                        .map(describe)
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                }
+            }
+            Response::Removed(context) => format!(
+                "The judgement request of {} has been removed",
+                describe(context)
+            ),
+            Response::SecondChallengeResent(context) => format!(
+                "The second challenge of {} will be sent again in a couple of seconds",
+                describe(context)
+            ),
+            Response::NoPendingSecondChallenge => {
+                "The identity has no pending second challenge".to_string()
+            }
+            Response::AuditLog(entries) => {
+                if entries.is_empty() {
+                    "The audit log is empty".to_string()
+                } else {
+                    entries
+                        .iter()
+                        .map(|entry| {
+                            format!(
+                                "[{}] {}: {} {} ({})",
+                                entry.timestamp.raw(),
+                                entry.actor,
+                                entry.command.name(),
+                                entry
+                                    .context
+                                    .as_ref()
+                                    .map(describe)
+                                    .unwrap_or_else(|| "-".to_string()),
+                                entry.outcome.as_str()
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                }
+            }
+            Response::PermissionDenied(role) => {
+                format!("The '{}' role does not permit this command", role.as_str())
+            }
             Response::UnknownCommand => "The provided command is unknown".to_string(),
-            Response::IdentityNotFound => {
-                "Identity was not found or invalid query executed".to_string()
+            Response::IdentityNotFound(context) => {
+                format!(
+                    "Identity {} was not found or invalid query executed",
+                    describe(context)
+                )
             }
             Response::InvalidSyntax(input) => {
                 format!(
@@ -95,16 +657,108 @@ impl std::fmt::Display for Response {
                     }
                 )
             }
+            Response::NoteAdded(context) => {
+                format!("Added a note to {}", describe(context))
+            }
+            Response::Notes(context, notes) => {
+                if notes.is_empty() {
+                    format!("There are no notes for {}", describe(context))
+                } else {
+                    format_notes(notes)
+                }
+            }
+            Response::ApprovalRequested(approval) => format!(
+                "The full verification of {} must be approved by a second admin with 'approve {}'",
+                describe(&approval.context),
+                TargetAddress::from(approval.context.clone())
+            ),
+            Response::ApprovalAlreadyPending(approval) => {
+                format!("There is already a pending approval: {}", approval)
+            }
+            Response::Approvals(approvals) => {
+                if approvals.is_empty() {
+                    "There are no pending approvals".to_string()
+                } else {
+                    approvals
+                        .iter()
+                        .map(|approval| approval.to_string())
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                }
+            }
+            Response::ApprovalCancelled(context) => {
+                format!(
+                    "The pending approval of {} has been cancelled",
+                    describe(context)
+                )
+            }
+            Response::NoPendingApproval(context) => {
+                format!("There is no pending approval for {}", describe(context))
+            }
+            Response::SelfApproval(context) => format!(
+                "The full verification of {} must be approved by a different admin",
+                describe(context)
+            ),
+            Response::UnknownApprovalAdmin(actor) => format!(
+                "{} is not listed as an admin in the approval config and cannot request or approve full verifications",
+                actor
+            ),
+            Response::InvalidAddress(target) => match target.chain {
+                Some(chain) => format!(
+                    "'{}' is not a valid {} address",
+                    target.address.as_str(),
+                    chain.as_str()
+                ),
+                None => format!(
+                    "'{}' is not a valid address of a supported chain",
+                    target.address.as_str()
+                ),
+            },
+            Response::AmbiguousChain(address, chains) => format!(
+                "'{}' is valid on multiple chains ({}), specify one as '<CHAIN>:<ADDR>'",
+                address.as_str(),
+                chains
+                    .iter()
+                    .map(|chain| chain.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+            Response::Stats(stats) => {
+                if stats.is_empty() {
+                    "There are no statistics for this period".to_string()
+                } else {
+                    stats
+                        .iter()
+                        .map(format_stats)
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                }
+            }
             Response::InternalError => {
                 "An internal error occured. Please contact the architects.".to_string()
             }
             Response::Help => "\
                 status <ADDR>\t\t\tShow the current verification status of the specified address.\n\
                 verify <ADDR> <FIELD>...\tVerify one or multiple fields of the specified address.\n\
+                unverify <ADDR> <FIELD>...\tRevert the verification of one or multiple fields and replace their challenges.\n\
+                reset <ADDR>\t\t\tReset all fields of the specified address and replace their challenges.\n\
+                pending [CHAIN]\t\t\tList the open requests with their age and progress.\n\
+                search <VALUE>\t\t\tFind identities by a field value, such as an email address or handle.\n\
+                remove <ADDR>\t\t\tRemove the judgement request of the specified address.\n\
+                resend <ADDR>\t\t\tSend the second email challenge of the specified address again.\n\
+                audit [ADDR]\t\t\tShow the latest admin actions, optionally for the specified address only.\n\
+                note <ADDR> <TEXT>\t\tRecord a note for the specified address, e.g. the evidence of a manual verification.\n\
+                notes <ADDR>\t\t\tShow the notes of the specified address.\n\
+                approve <ADDR>\t\t\tApprove the full verification of the specified address, requested by a different admin.\n\
+                approvals\t\t\tList the full verifications which await approval.\n\
+                cancel <ADDR>\t\t\tCancel the pending approval of the specified address.\n\
+                stats [CHAIN] [INTERVAL]\tShow the registrar statistics per day, week or month (default).\n\
+                \n\
+                The chain of an address is derived from its SS58 prefix. Use <CHAIN>:<ADDR> to specify it explicitly, e.g. kusama:<ADDR>.\n\
                 "
             .to_string(),
-            Response::FullyVerified(_) => {
-                "Identity has been fully verified. The extrinsic will be submitted in a couple of minutes".to_string()
+            Response::FullyVerified(context) => {
+                format!("Identity {} has been fully verified. The extrinsic will be submitted in a couple of minutes", describe(context))
             },
         };
 
@@ -120,6 +774,9 @@ pub enum RawFieldName {
     Web,
     Twitter,
     Matrix,
+    Telegram,
+    Github,
+    PGPFingerprint,
     // Represents the full identity
     All,
 }
@@ -134,6 +791,9 @@ impl std::fmt::Display for RawFieldName {
                 RawFieldName::Web => "web",
                 RawFieldName::Twitter => "twitter",
                 RawFieldName::Matrix => "matrix",
+                RawFieldName::Telegram => "telegram",
+                RawFieldName::Github => "github",
+                RawFieldName::PGPFingerprint => "pgp_fingerprint",
                 RawFieldName::All => "all",
             }
         })
@@ -145,7 +805,7 @@ impl FromStr for RawFieldName {
 
     fn from_str(s: &str) -> Result<Self> {
         // Convenience handler.
-        let s = s.trim().replace('-', "").replace('_', "").to_lowercase();
+        let s = s.trim().replace(['-', '_'], "").to_lowercase();
 
         let f = match s.as_str() {
             "legalname" => RawFieldName::LegalName,
@@ -154,6 +814,9 @@ impl FromStr for RawFieldName {
             "web" => RawFieldName::Web,
             "twitter" => RawFieldName::Twitter,
             "matrix" => RawFieldName::Matrix,
+            "telegram" => RawFieldName::Telegram,
+            "github" => RawFieldName::Github,
+            "pgpfingerprint" | "pgp" => RawFieldName::PGPFingerprint,
             "all" => RawFieldName::All,
             _ => return Err(Response::InvalidSyntax(Some(s.to_string()))),
         };
@@ -162,30 +825,75 @@ impl FromStr for RawFieldName {
     }
 }
 
-#[allow(clippy::needless_lifetimes)]
-pub async fn process_admin<'a>(db: &'a Database, command: Command) -> Response {
+/// Executes the command without checking permissions or recording it in the
+/// audit log.
+#[cfg(test)]
+pub async fn process_admin(db: &Database, command: Command) -> Response {
+    execute(db, "unknown", Role::Superadmin, command).await
+}
+
+async fn execute<'a>(db: &'a Database, actor: &'a str, role: Role, command: Command) -> Response {
     let local = |db: &'a Database, command: Command| async move {
         match command {
             Command::Status(addr) => {
-                let context = create_context(addr);
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
                 let state = db.fetch_judgement_state(&context).await?;
 
                 // Determine response based on database lookup.
                 match state {
-                    Some(state) => Ok(Response::Status(state.into())),
-                    None => Ok(Response::IdentityNotFound),
+                    Some(state) => Ok(Response::Status(Box::new(IdentityStatus {
+                        state: state.into(),
+                        notes: db.fetch_notes(&context).await?,
+                    }))),
+                    None => Ok(Response::IdentityNotFound(context)),
                 }
             }
             Command::Verify(addr, fields) => {
-                let context = create_context(addr.clone());
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                let state = match db.fetch_judgement_state(&context).await? {
+                    Some(state) => state,
+                    None => return Ok(Response::IdentityNotFound(context)),
+                };
+
+                // Check if _all_ should be verified (respectively the full
+                // identity). This includes verifying the remaining fields,
+                // which forces a judgement just the same.
+                if fields.contains(&RawFieldName::All) || state.is_completed_by(&fields) {
+                    if role != Role::Superadmin {
+                        return Ok(Response::PermissionDenied(role));
+                    }
+
+                    // Requires the approval of a second admin, if configured.
+                    if let Some(window) = db.approval_window() {
+                        let admin = match db.approval_admin(actor) {
+                            Some(admin) => admin,
+                            None => return Ok(Response::UnknownApprovalAdmin(actor.to_string())),
+                        };
+
+                        let approval = PendingApproval {
+                            context,
+                            requested_by: admin.to_string(),
+                            requested_at: Timestamp::now(),
+                            expires_at: Timestamp::with_offset(window),
+                        };
+
+                        return match db.insert_approval(&approval).await? {
+                            Some(existing) => Ok(Response::ApprovalAlreadyPending(existing)),
+                            None => Ok(Response::ApprovalRequested(approval)),
+                        };
+                    }
 
-                // Check if _all_ should be verified (respectively the full identity)
-                #[allow(clippy::collapsible_if)]
-                if fields.iter().any(|f| matches!(f, RawFieldName::All)) {
                     if db.full_manual_verification(&context).await? {
-                        return Ok(Response::FullyVerified(addr));
+                        return Ok(Response::FullyVerified(context));
                     } else {
-                        return Ok(Response::IdentityNotFound);
+                        return Ok(Response::IdentityNotFound(context));
                     }
                 }
 
@@ -196,11 +904,151 @@ pub async fn process_admin<'a>(db: &'a Database, command: Command) -> Response {
                         .await?
                         .is_none()
                     {
-                        return Ok(Response::IdentityNotFound);
+                        return Ok(Response::IdentityNotFound(context));
                     }
                 }
 
-                Ok(Response::Verified(addr, fields))
+                Ok(Response::Verified(context, fields))
+            }
+            Command::Unverify(addr, fields) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.unverify_manually(&context, &fields).await?.is_none() {
+                    return Ok(Response::IdentityNotFound(context));
+                }
+
+                Ok(Response::Unverified(context, fields))
+            }
+            Command::Reset(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.reset_judgement(&context).await? {
+                    Ok(Response::Reset(context))
+                } else {
+                    Ok(Response::IdentityNotFound(context))
+                }
+            }
+            Command::Pending(chain) => {
+                let pending = db.fetch_pending_judgements(chain).await?;
+                Ok(Response::Pending(
+                    pending.into_iter().map(PendingRequest::from).collect(),
+                ))
+            }
+            Command::Search(value) => {
+                let found = db.search_judgements(&value).await?;
+                Ok(Response::Search(
+                    found.into_iter().map(|state| state.context).collect(),
+                ))
+            }
+            Command::Remove(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.delete_judgement(&context).await? {
+                    Ok(Response::Removed(context))
+                } else {
+                    Ok(Response::IdentityNotFound(context))
+                }
+            }
+            Command::Resend(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.fetch_judgement_state(&context).await?.is_none() {
+                    return Ok(Response::IdentityNotFound(context));
+                }
+
+                if db.resend_second_challenge(&context).await? {
+                    Ok(Response::SecondChallengeResent(context))
+                } else {
+                    Ok(Response::NoPendingSecondChallenge)
+                }
+            }
+            Command::Audit(addr) => {
+                let context = match addr.map(|addr| addr.resolve(db.chains())).transpose() {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+                let entries = db.fetch_audit_entries(context.as_ref()).await?;
+                Ok(Response::AuditLog(entries))
+            }
+            Command::Note(addr, text) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.fetch_judgement_state(&context).await?.is_none() {
+                    return Ok(Response::IdentityNotFound(context));
+                }
+
+                db.insert_note(&ModeratorNote {
+                    context: context.clone(),
+                    timestamp: Timestamp::now(),
+                    author: actor.to_string(),
+                    text,
+                })
+                .await?;
+
+                Ok(Response::NoteAdded(context))
+            }
+            Command::Notes(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                let notes = db.fetch_notes(&context).await?;
+                Ok(Response::Notes(context, notes))
+            }
+            Command::Approve(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                let admin = match db.approval_admin(actor) {
+                    Some(admin) => admin,
+                    None => return Ok(Response::UnknownApprovalAdmin(actor.to_string())),
+                };
+
+                if db.take_approval(&context, admin).await?.is_none() {
+                    // Distinguish between self-approvals and missing approvals.
+                    return match db.fetch_approval(&context).await? {
+                        Some(_) => Ok(Response::SelfApproval(context)),
+                        None => Ok(Response::NoPendingApproval(context)),
+                    };
+                }
+
+                if db.full_manual_verification(&context).await? {
+                    Ok(Response::FullyVerified(context))
+                } else {
+                    Ok(Response::IdentityNotFound(context))
+                }
+            }
+            Command::Approvals => Ok(Response::Approvals(db.fetch_approvals().await?)),
+            Command::Stats(query) => Ok(Response::Stats(db.fetch_stats(&query).await?)),
+            Command::Cancel(addr) => {
+                let context = match addr.resolve(db.chains()) {
+                    Ok(context) => context,
+                    Err(resp) => return Ok(resp),
+                };
+
+                if db.delete_approval(&context).await? {
+                    Ok(Response::ApprovalCancelled(context))
+                } else {
+                    Ok(Response::NoPendingApproval(context))
+                }
             }
             Command::Help => Ok(Response::Help),
         }
@@ -217,16 +1065,102 @@ pub async fn process_admin<'a>(db: &'a Database, command: Command) -> Response {
     }
 }
 
-/// Convenience function for creating a full identity context when only the
-/// address itself is present. Only supports Kusama and Polkadot for now.
-pub fn create_context(address: ChainAddress) -> IdentityContext {
-    let chain = if address.as_str().starts_with('1') {
-        ChainName::Polkadot
+/// Executes the command on behalf of the actor, if permitted by its role, and
+/// records it in the audit log.
+pub async fn process_admin_as(
+    db: &Database,
+    actor: &str,
+    role: Role,
+    command: Command,
+) -> Response {
+    let resp = if role.allows(&command) {
+        execute(db, actor, role, command.clone()).await
     } else {
-        ChainName::Kusama
+        Response::PermissionDenied(role)
     };
 
-    IdentityContext { address, chain }
+    // Not an action.
+    if command == Command::Help {
+        return resp;
+    }
+
+    let outcome = match resp {
+        Response::PermissionDenied(_) => AuditOutcome::Forbidden,
+        _ if resp.is_success() => AuditOutcome::Success,
+        _ => AuditOutcome::Failure,
+    };
+
+    let _ = db
+        .insert_audit_entry(&AuditEntry {
+            timestamp: Timestamp::now(),
+            actor: actor.to_string(),
+            context: command.target(db.chains()),
+            command,
+            outcome,
+        })
+        .await
+        .map_err(|err| error!("Failed to insert audit log entry: {:?}", err));
+
+    resp
+}
+
+fn format_notes(notes: &[ModeratorNote]) -> String {
+    notes
+        .iter()
+        .map(|note| note.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Formats a duration in seconds as days, hours and minutes.
+fn format_age(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else {
+        format!("{}m", mins)
+    }
+}
+
+fn format_stats(bucket: &StatsBucket) -> String {
+    let percent = |share: Option<f64>| {
+        share
+            .map(|share| format!("{:.1}%", share * 100.0))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    format!(
+        "{} ({}): {} requests, {} judgements, median time to verify {}, {} manually verified identities ({}), {} display name rejections{}",
+        bucket.period,
+        bucket.chain.as_str(),
+        bucket.requests_received,
+        bucket.judgements_issued,
+        bucket
+            .median_time_to_verify
+            .map(format_age)
+            .unwrap_or_else(|| "-".to_string()),
+        bucket.manual_verifications,
+        percent(bucket.manual_share),
+        bucket.display_name_rejections,
+        bucket
+            .fields
+            .iter()
+            .map(|(field, stats)| format!(
+                "\n  {}: {} verified, {} failed ({})",
+                field,
+                stats.verified,
+                stats.failed,
+                percent(stats.success_rate)
+            ))
+            .collect::<String>()
+    )
+}
+
+fn describe(context: &IdentityContext) -> String {
+    format!("{} ({})", context.address.as_str(), context.chain.as_str())
 }
 
 #[cfg(test)]
@@ -239,13 +1173,13 @@ mod tests {
         let resp = Command::from_str("status Alice").unwrap();
         assert_eq!(
             resp,
-            Command::Status(ChainAddress::from("Alice".to_string()))
+            Command::Status(ChainAddress::from("Alice".to_string()).into())
         );
 
         let resp = Command::from_str("status  Alice").unwrap();
         assert_eq!(
             resp,
-            Command::Status(ChainAddress::from("Alice".to_string()))
+            Command::Status(ChainAddress::from("Alice".to_string()).into())
         );
 
         let resp = Command::from_str("status");
@@ -258,7 +1192,7 @@ mod tests {
         assert_eq!(
             resp,
             Command::Verify(
-                ChainAddress::from("Alice".to_string()),
+                ChainAddress::from("Alice".to_string()).into(),
                 vec![RawFieldName::Email]
             )
         );
@@ -267,7 +1201,7 @@ mod tests {
         assert_eq!(
             resp,
             Command::Verify(
-                ChainAddress::from("Alice".to_string()),
+                ChainAddress::from("Alice".to_string()).into(),
                 vec![RawFieldName::Email, RawFieldName::DisplayName]
             )
         );
@@ -276,7 +1210,7 @@ mod tests {
         assert_eq!(
             resp,
             Command::Verify(
-                ChainAddress::from("Alice".to_string()),
+                ChainAddress::from("Alice".to_string()).into(),
                 vec![RawFieldName::Email, RawFieldName::DisplayName]
             )
         );
@@ -285,7 +1219,7 @@ mod tests {
         assert_eq!(
             resp,
             Command::Verify(
-                ChainAddress::from("Alice".to_string()),
+                ChainAddress::from("Alice".to_string()).into(),
                 vec![RawFieldName::All]
             )
         );
@@ -294,6 +1228,343 @@ mod tests {
         assert!(resp.is_err());
     }
 
+    #[test]
+    fn command_unverify() {
+        let resp = Command::from_str("unverify Alice email").unwrap();
+        assert_eq!(
+            resp,
+            Command::Unverify(
+                ChainAddress::from("Alice".to_string()).into(),
+                vec![RawFieldName::Email]
+            )
+        );
+
+        let resp = Command::from_str("unverify Alice email display_name").unwrap();
+        assert_eq!(
+            resp,
+            Command::Unverify(
+                ChainAddress::from("Alice".to_string()).into(),
+                vec![RawFieldName::Email, RawFieldName::DisplayName]
+            )
+        );
+
+        let resp = Command::from_str("unverify Alice all");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("unverify Alice");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_reset() {
+        let resp = Command::from_str("reset Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Reset(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("reset");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("reset Alice Bob");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_pending() {
+        let resp = Command::from_str("pending").unwrap();
+        assert_eq!(resp, Command::Pending(None));
+
+        let resp = Command::from_str("pending Kusama").unwrap();
+        assert_eq!(resp, Command::Pending(Some(ChainName::Kusama)));
+
+        let resp = Command::from_str("pending polkadot").unwrap();
+        assert_eq!(resp, Command::Pending(Some(ChainName::Polkadot)));
+
+        let resp = Command::from_str("pending westend");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("pending kusama polkadot");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_search() {
+        let resp = Command::from_str("search alice@email.com").unwrap();
+        assert_eq!(resp, Command::Search("alice@email.com".to_string()));
+
+        let resp = Command::from_str("search  @alice").unwrap();
+        assert_eq!(resp, Command::Search("@alice".to_string()));
+
+        let resp = Command::from_str("search");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_remove() {
+        let resp = Command::from_str("remove Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Remove(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("remove");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_resend() {
+        let resp = Command::from_str("resend Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Resend(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("resend");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_explicit_chain() {
+        let resp = Command::from_str("status kusama:Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Status(TargetAddress {
+                address: ChainAddress::from("Alice".to_string()),
+                chain: Some(ChainName::Kusama),
+            })
+        );
+
+        let resp = Command::from_str("verify polkadot:Alice email").unwrap();
+        assert_eq!(
+            resp,
+            Command::Verify(
+                TargetAddress {
+                    address: ChainAddress::from("Alice".to_string()),
+                    chain: Some(ChainName::Polkadot),
+                },
+                vec![RawFieldName::Email]
+            )
+        );
+
+        let resp = Command::from_str("status unknown:Alice");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn resolve_target_address() {
+        let polkadot = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+        let generic = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+        let context = TargetAddress::from_str(polkadot)
+            .unwrap()
+            .resolve(ChainName::all())
+            .unwrap();
+        assert_eq!(context.chain, ChainName::Polkadot);
+
+        // Does not match the SS58 prefix.
+        let target = TargetAddress::from_str(&format!("kusama:{}", polkadot)).unwrap();
+        assert_eq!(
+            target.resolve(ChainName::all()),
+            Err(Response::InvalidAddress(target))
+        );
+
+        // Valid on all chains.
+        let target = TargetAddress::from_str(generic).unwrap();
+        assert_eq!(
+            target.resolve(ChainName::all()),
+            Err(Response::AmbiguousChain(
+                target.address.clone(),
+                vec![ChainName::Polkadot, ChainName::Kusama]
+            ))
+        );
+
+        // Re-encoded with the prefix of the chain.
+        let context = TargetAddress::from_str(&format!("kusama:{}", generic))
+            .unwrap()
+            .resolve(ChainName::all())
+            .unwrap();
+        assert_eq!(context.chain, ChainName::Kusama);
+        assert_eq!(
+            context.address.as_str(),
+            "HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F"
+        );
+
+        // Only configured chains are considered.
+        let context = TargetAddress::from_str(generic)
+            .unwrap()
+            .resolve(&[ChainName::Polkadot])
+            .unwrap();
+        assert_eq!(context.chain, ChainName::Polkadot);
+        assert_eq!(context.address.as_str(), polkadot);
+
+        let target = TargetAddress::from_str(polkadot).unwrap();
+        assert_eq!(
+            target.resolve(&[ChainName::Kusama]),
+            Err(Response::InvalidAddress(target))
+        );
+
+        let target = TargetAddress::from_str("Alice").unwrap();
+        assert_eq!(
+            target.resolve(ChainName::all()),
+            Err(Response::InvalidAddress(target))
+        );
+    }
+
+    #[test]
+    fn deserialize_legacy_command() {
+        let command: Command = serde_json::from_value(serde_json::json!({
+            "type": "status",
+            "value": "Alice",
+        }))
+        .unwrap();
+        assert_eq!(
+            command,
+            Command::Status(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let command: Command = serde_json::from_value(
+            serde_json::to_value(Command::Status(TargetAddress {
+                address: ChainAddress::from("Alice".to_string()),
+                chain: Some(ChainName::Kusama),
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            command,
+            Command::Status(TargetAddress {
+                address: ChainAddress::from("Alice".to_string()),
+                chain: Some(ChainName::Kusama),
+            })
+        );
+    }
+
+    #[test]
+    fn command_note() {
+        let resp = Command::from_str("note Alice LinkedIn chat confirmed legal name").unwrap();
+        assert_eq!(
+            resp,
+            Command::Note(
+                ChainAddress::from("Alice".to_string()).into(),
+                "LinkedIn chat confirmed legal name".to_string()
+            )
+        );
+
+        let resp = Command::from_str("notes Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Notes(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("note Alice");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("notes Alice Bob");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_approval() {
+        let resp = Command::from_str("approve Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Approve(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("approvals").unwrap();
+        assert_eq!(resp, Command::Approvals);
+
+        let resp = Command::from_str("cancel Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Cancel(ChainAddress::from("Alice".to_string()).into())
+        );
+
+        let resp = Command::from_str("approve");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("approvals Alice");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_stats() {
+        let resp = Command::from_str("stats").unwrap();
+        assert_eq!(resp, Command::Stats(StatsQuery::default()));
+
+        let resp = Command::from_str("stats kusama week").unwrap();
+        assert_eq!(
+            resp,
+            Command::Stats(StatsQuery {
+                chain: Some(ChainName::Kusama),
+                interval: Some(StatsInterval::Week),
+                ..Default::default()
+            })
+        );
+
+        let resp = Command::from_str("stats day").unwrap();
+        assert_eq!(
+            resp,
+            Command::Stats(StatsQuery {
+                interval: Some(StatsInterval::Day),
+                ..Default::default()
+            })
+        );
+
+        let resp = Command::from_str("stats westend");
+        assert!(resp.is_err());
+
+        let resp = Command::from_str("stats day month");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn command_audit() {
+        let resp = Command::from_str("audit").unwrap();
+        assert_eq!(resp, Command::Audit(None));
+
+        let resp = Command::from_str("audit Alice").unwrap();
+        assert_eq!(
+            resp,
+            Command::Audit(Some(ChainAddress::from("Alice".to_string()).into()))
+        );
+
+        let resp = Command::from_str("audit Alice Bob");
+        assert!(resp.is_err());
+    }
+
+    #[test]
+    fn role_permissions() {
+        let alice = || TargetAddress::from(ChainAddress::from("Alice".to_string()));
+
+        let status = Command::Status(alice());
+        let verify = Command::Verify(alice(), vec![RawFieldName::Email]);
+        let verify_all = Command::Verify(alice(), vec![RawFieldName::All]);
+        let reset = Command::Reset(alice());
+        let audit = Command::Audit(None);
+
+        assert!(Role::Viewer.allows(&status));
+        assert!(!Role::Viewer.allows(&verify));
+        assert!(!Role::Viewer.allows(&audit));
+
+        assert!(Role::Verifier.allows(&status));
+        assert!(Role::Verifier.allows(&verify));
+        assert!(!Role::Verifier.allows(&verify_all));
+        assert!(!Role::Verifier.allows(&reset));
+
+        assert!(Role::Superadmin.allows(&verify_all));
+        assert!(Role::Superadmin.allows(&reset));
+        assert!(Role::Superadmin.allows(&audit));
+    }
+
+    #[test]
+    fn age_format() {
+        assert_eq!(format_age(59), "0m");
+        assert_eq!(format_age(3_660), "1h 1m");
+        assert_eq!(format_age(90_000), "1d 1h");
+    }
+
     #[test]
     fn command_help() {
         let resp = Command::from_str("help").unwrap();
@@ -309,7 +1580,10 @@ mod tests {
     #[test]
     #[ignore]
     fn response_status_debug() {
-        let resp = Response::Status(JudgementState::alice().into());
+        let resp = Response::Status(Box::new(IdentityStatus {
+            state: JudgementState::alice().into(),
+            notes: vec![],
+        }));
         println!("{}", resp);
     }
 
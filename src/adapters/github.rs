@@ -0,0 +1,376 @@
+use crate::adapters::admin::RawFieldName;
+use crate::adapters::{read_capped, Adapter};
+use crate::primitives::{ExternalMessage, ExternalMessageType, IdentityFieldValue, Timestamp};
+use crate::{Database, Result};
+use reqwest::header::{self, HeaderValue};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use std::collections::HashMap;
+
+const DEFAULT_API_BASE: &str = "https://api.github.com";
+// The repository whose README may contain the challenge.
+const PROOF_REPOSITORY: &str = "polkadot-identity";
+// Only the most recent gists are checked.
+const MAX_GISTS: usize = 10;
+// Larger files are truncated.
+const MAX_FILE_SIZE: usize = 64 * 1024;
+// Larger responses of the gist listing are rejected.
+const MAX_GIST_LIST_SIZE: usize = 1024 * 1024;
+
+pub struct GithubBuilder {
+    db: Option<Database>,
+    api_base: Option<String>,
+    token: Option<String>,
+}
+
+impl GithubBuilder {
+    pub fn new() -> Self {
+        GithubBuilder {
+            db: None,
+            api_base: None,
+            token: None,
+        }
+    }
+    pub fn database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+    pub fn api_base(mut self, api_base: String) -> Self {
+        self.api_base = Some(api_base);
+        self
+    }
+    /// Optional access token, which raises the API rate limit.
+    pub fn token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+    pub fn build(self) -> Result<GithubVerifier> {
+        Ok(GithubVerifier {
+            db: self.db.ok_or_else(|| anyhow!("database not specified"))?,
+            client: GithubClient {
+                client: Client::new(),
+                api_base: self
+                    .api_base
+                    .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+                    .trim_end_matches('/')
+                    .to_string(),
+                token: self.token,
+            },
+        })
+    }
+}
+
+/// Verifies GitHub accounts by looking for the challenge in the public gists
+/// of the handle or in the README of its `polkadot-identity` repository.
+/// Pending GitHub fields are re-checked on every request interval.
+pub struct GithubVerifier {
+    db: Database,
+    client: GithubClient,
+}
+
+impl GithubVerifier {
+    async fn request_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        let pending = self
+            .db
+            .fetch_unverified_challenges(&RawFieldName::Github)
+            .await?;
+
+        if pending.is_empty() {
+            debug!("No pending GitHub fields found");
+            return Ok(vec![]);
+        }
+
+        // Multiple identities might specify the same handle.
+        let mut proofs: HashMap<String, Vec<String>> = HashMap::new();
+
+        let mut messages = vec![];
+        for (_, field, expected) in pending {
+            let github = match field {
+                IdentityFieldValue::Github(github) => github,
+                _ => continue,
+            };
+
+            let handle = match handle_of(&github) {
+                Some(handle) => handle,
+                None => {
+                    debug!("Skipping invalid GitHub handle: {}", github);
+                    continue;
+                }
+            };
+
+            if !proofs.contains_key(&handle) {
+                let found = self.client.fetch_proofs(&handle).await;
+                proofs.insert(handle.clone(), found);
+            }
+
+            let files = &proofs[&handle];
+            if files.iter().any(|file| file.contains(&expected.value)) {
+                debug!("Found proof for {}", handle);
+
+                messages.push(ExternalMessage {
+                    origin: ExternalMessageType::Github(github),
+                    // Proofs are looked up, there is no message Id.
+                    id: 0u32.into(),
+                    timestamp: Timestamp::now(),
+                    values: files.iter().cloned().map(|f| f.into()).collect(),
+                });
+            }
+        }
+
+        Ok(messages)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiGist {
+    description: Option<String>,
+    files: HashMap<String, ApiGistFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiGistFile {
+    raw_url: String,
+}
+
+struct GithubClient {
+    client: Client,
+    api_base: String,
+    token: Option<String>,
+}
+
+impl GithubClient {
+    /// Collects the proofs of both sources. A missing or unreachable source
+    /// is not an error.
+    async fn fetch_proofs(&self, handle: &str) -> Vec<String> {
+        let mut proofs = vec![];
+
+        match self.fetch_gists(handle).await {
+            Ok(mut files) => proofs.append(&mut files),
+            Err(err) => debug!("Failed to fetch gists of {}: {:?}", handle, err),
+        }
+
+        match self.fetch_readme(handle).await {
+            Ok(Some(readme)) => proofs.push(readme),
+            Ok(None) => {}
+            Err(err) => debug!("Failed to fetch README of {}: {:?}", handle, err),
+        }
+
+        proofs
+    }
+    async fn fetch_gists(&self, handle: &str) -> Result<Vec<String>> {
+        // Only gists owned by the handle are listed.
+        let resp = self
+            .api_request(&format!("/users/{}/gists", handle))
+            .query(&[("per_page", MAX_GISTS)])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // A truncated response fails to parse.
+        let gists: Vec<ApiGist> =
+            serde_json::from_str(&read_capped(resp, MAX_GIST_LIST_SIZE).await?)?;
+
+        let mut files = vec![];
+        for gist in gists {
+            if let Some(description) = gist.description {
+                files.push(description);
+            }
+
+            // A single unavailable file does not discard the other gists.
+            for (name, file) in &gist.files {
+                match self.fetch_gist_file(&file.raw_url).await {
+                    Ok(content) => files.push(content),
+                    Err(err) => debug!(
+                        "Failed to fetch gist file {} of {}: {:?}",
+                        name, handle, err
+                    ),
+                }
+            }
+        }
+
+        Ok(files)
+    }
+    async fn fetch_gist_file(&self, raw_url: &str) -> Result<String> {
+        let resp = self.client.get(raw_url).send().await?.error_for_status()?;
+
+        read_capped(resp, MAX_FILE_SIZE).await
+    }
+    async fn fetch_readme(&self, handle: &str) -> Result<Option<String>> {
+        let resp = self
+            .api_request(&format!("/repos/{}/{}/readme", handle, PROOF_REPOSITORY))
+            .header(
+                header::ACCEPT,
+                HeaderValue::from_static("application/vnd.github.raw"),
+            )
+            .send()
+            .await?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            read_capped(resp.error_for_status()?, MAX_FILE_SIZE).await?,
+        ))
+    }
+    fn api_request(&self, path: &str) -> RequestBuilder {
+        let mut req = self
+            .client
+            .get(format!("{}{}", self.api_base, path))
+            .header(
+                header::ACCEPT,
+                HeaderValue::from_static("application/vnd.github+json"),
+            )
+            // Required by the GitHub API.
+            .header(header::USER_AGENT, HeaderValue::from_static("registrar"));
+
+        if let Some(token) = &self.token {
+            req = req.bearer_auth(token);
+        }
+
+        req
+    }
+}
+
+/// Extracts the handle from the GitHub field, which might be prefixed with
+/// `@` or specified as a profile URL. Returns `None` for invalid handles.
+fn handle_of(github: &str) -> Option<String> {
+    let github = github.trim().trim_end_matches('/');
+    let handle = github
+        .rsplit_once("github.com/")
+        .map(|(_, handle)| handle)
+        .unwrap_or(github)
+        .trim_start_matches('@');
+
+    let is_valid = !handle.is_empty()
+        && handle.len() <= 39
+        && !handle.starts_with('-')
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+    if is_valid {
+        Some(handle.to_lowercase())
+    } else {
+        None
+    }
+}
+
+#[async_trait]
+impl Adapter for GithubVerifier {
+    type MessageType = ();
+
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+    async fn fetch_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        self.request_messages().await
+    }
+    #[allow(clippy::diverging_sub_expression)]
+    async fn send_message(&mut self, _to: &str, _content: Self::MessageType) -> Result<()> {
+        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_test::{start, TestServer};
+    use actix_web::{web, App, HttpRequest, HttpResponse};
+
+    fn fake_server() -> TestServer {
+        start(|| {
+            App::new()
+                .route(
+                    "/users/{handle}/gists",
+                    web::get().to(|req: HttpRequest| async move {
+                        assert!(req.headers().contains_key(header::USER_AGENT));
+
+                        let gists = if req.match_info().query("handle") == "alice" {
+                            let host = req.connection_info().host().to_string();
+                            let raw_url = format!("http://{}/raw/alice/proof.txt", host);
+                            let broken_url = format!("http://{}/raw/alice/broken.txt", host);
+
+                            serde_json::json!([{
+                                "description": "Polkadot identity",
+                                "files": {
+                                    "proof.txt": { "raw_url": raw_url },
+                                    "broken.txt": { "raw_url": broken_url },
+                                },
+                            }])
+                        } else {
+                            serde_json::json!([])
+                        };
+
+                        HttpResponse::Ok().json(gists)
+                    }),
+                )
+                .route(
+                    "/raw/alice/proof.txt",
+                    web::get().to(|| async { HttpResponse::Ok().body("CHALLENGE\n") }),
+                )
+                .route(
+                    "/raw/alice/broken.txt",
+                    web::get().to(|| async { HttpResponse::InternalServerError().finish() }),
+                )
+                .route(
+                    "/repos/{handle}/polkadot-identity/readme",
+                    web::get().to(|req: HttpRequest| async move {
+                        match req.match_info().query("handle") {
+                            "bob" => HttpResponse::Ok().body("# Proof\n\nCHALLENGE"),
+                            "carol" => HttpResponse::Ok()
+                                .body(format!("CHALLENGE\n{}", "x".repeat(MAX_FILE_SIZE * 4))),
+                            _ => HttpResponse::NotFound().finish(),
+                        }
+                    }),
+                )
+        })
+    }
+
+    fn github_client(server: &TestServer) -> GithubClient {
+        GithubClient {
+            client: Client::new(),
+            api_base: server.url("").trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    #[test]
+    fn parse_handle() {
+        assert_eq!(handle_of("alice"), Some("alice".to_string()));
+        assert_eq!(handle_of("@Alice"), Some("alice".to_string()));
+        assert_eq!(
+            handle_of("https://github.com/alice-bob/"),
+            Some("alice-bob".to_string())
+        );
+        assert_eq!(handle_of("alice/../repos"), None);
+        assert_eq!(handle_of("-alice"), None);
+        assert_eq!(handle_of(""), None);
+    }
+
+    #[actix::test]
+    async fn fetch_proofs() {
+        let server = fake_server();
+        let client = github_client(&server);
+
+        // Gist, the unavailable file is skipped.
+        let proofs = client.fetch_proofs("alice").await;
+        assert_eq!(
+            proofs,
+            vec!["Polkadot identity".to_string(), "CHALLENGE\n".to_string()]
+        );
+
+        // Repository README.
+        let proofs = client.fetch_proofs("bob").await;
+        assert_eq!(proofs, vec!["# Proof\n\nCHALLENGE".to_string()]);
+
+        // Large files are truncated.
+        let proofs = client.fetch_proofs("carol").await;
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].len(), MAX_FILE_SIZE);
+        assert!(proofs[0].starts_with("CHALLENGE\n"));
+
+        // No proofs.
+        let proofs = client.fetch_proofs("eve").await;
+        assert!(proofs.is_empty());
+    }
+}
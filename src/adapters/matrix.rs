@@ -1,4 +1,4 @@
-use crate::adapters::admin::{process_admin, Command, Response};
+use crate::adapters::admin::{process_admin_as, Command, Response, Role};
 use crate::adapters::Adapter;
 use crate::primitives::{ExternalMessage, ExternalMessageType, Timestamp};
 use crate::{Database, Result};
@@ -19,8 +19,10 @@ const REJOIN_MAX_ATTEMPTS: usize = 5;
 
 #[derive(Clone)]
 pub struct MatrixClient {
+    client: Client,
     // TODO: This should just be a channel.
     messages: Arc<Mutex<Vec<ExternalMessage>>>,
+    admins: Vec<MatrixAdmin>,
 }
 
 impl MatrixClient {
@@ -30,7 +32,7 @@ impl MatrixClient {
         password: &str,
         db_path: &str,
         db: Database,
-        admins: Vec<MatrixHandle>,
+        admins: Vec<MatrixAdmin>,
     ) -> Result<MatrixClient> {
         info!("Setting up Matrix client");
         // Setup client
@@ -56,7 +58,7 @@ impl MatrixClient {
                 client.clone(),
                 Arc::clone(&messages),
                 db,
-                admins,
+                admins.clone(),
             )))
             .await;
 
@@ -69,22 +71,55 @@ impl MatrixClient {
                 .ok_or_else(|| anyhow!("Failed to acquire sync token"))?,
         );
 
+        let sync_client = client.clone();
         actix::spawn(async move {
-            client.clone().sync(settings).await;
+            sync_client.sync(settings).await;
         });
 
-        Ok(MatrixClient { messages })
+        Ok(MatrixClient {
+            client,
+            messages,
+            admins,
+        })
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct MatrixHandle(String);
 
+/// An admin and its role. Admins specified as a plain handle are
+/// superadmins, as they were before roles existed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "MatrixAdminEntry")]
+pub struct MatrixAdmin {
+    pub handle: MatrixHandle,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MatrixAdminEntry {
+    Handle(MatrixHandle),
+    WithRole { handle: MatrixHandle, role: Role },
+}
+
+impl From<MatrixAdminEntry> for MatrixAdmin {
+    fn from(entry: MatrixAdminEntry) -> Self {
+        match entry {
+            MatrixAdminEntry::Handle(handle) => MatrixAdmin {
+                handle,
+                role: Role::Superadmin,
+            },
+            MatrixAdminEntry::WithRole { handle, role } => MatrixAdmin { handle, role },
+        }
+    }
+}
+
 struct Listener {
     client: Client,
     messages: Arc<Mutex<Vec<ExternalMessage>>>,
     db: Database,
-    admins: Vec<MatrixHandle>,
+    admins: Vec<MatrixAdmin>,
 }
 
 impl Listener {
@@ -92,7 +127,7 @@ impl Listener {
         client: Client,
         messages: Arc<Mutex<Vec<ExternalMessage>>>,
         db: Database,
-        admins: Vec<MatrixHandle>,
+        admins: Vec<MatrixAdmin>,
     ) -> Self {
         Self {
             client,
@@ -154,10 +189,10 @@ impl EventHandler for Listener {
 
             // Check for admin message
             let sender = event.sender.to_string();
-            if self.admins.contains(&MatrixHandle(sender)) {
+            if let Some(admin) = self.admins.iter().find(|admin| admin.handle.0 == sender) {
                 let resp = match Command::from_str(msg_body) {
                     // If a valid admin command was found, execute it.
-                    Ok(cmd) => Some(process_admin(&self.db, cmd).await),
+                    Ok(cmd) => Some(process_admin_as(&self.db, &sender, admin.role, cmd).await),
                     Err(err @ Response::InvalidSyntax(_)) => Some(err),
                     // Ignore, allow noise (catches `UnknownCommand`).
                     Err(_) => None,
@@ -211,7 +246,58 @@ impl Adapter for MatrixClient {
         // Return messages and wipe inner field.
         Ok(std::mem::take(&mut *lock))
     }
+    #[allow(clippy::diverging_sub_expression)]
     async fn send_message(&mut self, _to: &str, _content: Self::MessageType) -> Result<()> {
         unimplemented!()
     }
+    async fn notify_admins(&mut self, message: &str) -> Result<()> {
+        // Alerts are sent to every room an admin has joined.
+        for room in self.client.joined_rooms() {
+            let has_admin = room.joined_members_no_sync().await?.iter().any(|member| {
+                let member = member.user_id().to_string();
+                self.admins.iter().any(|admin| admin.handle.0 == member)
+            });
+
+            if has_admin {
+                room.send(
+                    AnyMessageEventContent::RoomMessage(MessageEventContent::text_plain(message)),
+                    None,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_admins() {
+        let admins: Vec<MatrixAdmin> = serde_yaml::from_str(
+            "
+            - '@alice:matrix.org'
+            - handle: '@bob:matrix.org'
+              role: viewer
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            admins,
+            vec![
+                MatrixAdmin {
+                    handle: MatrixHandle("@alice:matrix.org".to_string()),
+                    role: Role::Superadmin,
+                },
+                MatrixAdmin {
+                    handle: MatrixHandle("@bob:matrix.org".to_string()),
+                    role: Role::Viewer,
+                },
+            ]
+        );
+    }
 }
@@ -1,4 +1,6 @@
 use crate::database::{Database, EventCursor};
+use crate::health::health;
+use crate::metrics::metrics;
 use crate::primitives::{
     ExpectedMessage, ExternalMessage, IdentityFieldValue, NotificationMessage,
 };
@@ -8,8 +10,11 @@ use tracing::Instrument;
 
 pub mod admin;
 pub mod email;
+pub mod github;
 pub mod matrix;
+pub mod telegram;
 pub mod twitter;
+pub mod web;
 
 pub async fn run_adapters(config: AdapterConfig, db: Database) -> Result<()> {
     let listener = AdapterListener::new(db.clone()).await;
@@ -22,7 +27,11 @@ pub async fn run_adapters(config: AdapterConfig, db: Database) -> Result<()> {
         matrix: matrix_config,
         twitter: twitter_config,
         email: email_config,
+        telegram: telegram_config,
+        web: web_config,
+        github: github_config,
         display_name: _,
+        monitoring_address: _,
     } = config;
 
     // Matrix client configuration and execution.
@@ -42,7 +51,7 @@ pub async fn run_adapters(config: AdapterConfig, db: Database) -> Result<()> {
                 &config.username,
                 &config.password,
                 &config.db_path,
-                db,
+                db.clone(),
                 config.admins.unwrap_or_default(),
             )
             .await?;
@@ -121,6 +130,98 @@ pub async fn run_adapters(config: AdapterConfig, db: Database) -> Result<()> {
         started = true;
     }
 
+    // Telegram client configuration and execution.
+    if let Some(config) = telegram_config.filter(|config| config.enabled) {
+        let span = info_span!("telegram_adapter");
+        info!(api_base = config.api_base.as_deref().unwrap_or("default"));
+
+        async {
+            info!("Configuring client");
+            let mut builder = telegram::TelegramBuilder::new()
+                .token(config.token)
+                .database(db.clone());
+            if let Some(api_base) = config.api_base {
+                builder = builder.api_base(api_base);
+            }
+
+            let telegram_client = builder.build()?;
+
+            info!("Starting message adapter");
+            listener
+                .start_message_adapter(telegram_client, config.request_interval)
+                .await;
+
+            Result::Ok(())
+        }
+        .instrument(span)
+        .await?;
+
+        started = true;
+    }
+
+    // Web verifier configuration and execution.
+    if let Some(config) = web_config.filter(|config| config.enabled) {
+        let span = info_span!("web_verifier");
+        info!(
+            dns_resolver = config.dns_resolver.as_deref().unwrap_or("default"),
+            well_known_url = config.well_known_url.as_deref().unwrap_or("default"),
+        );
+
+        async {
+            info!("Configuring verifier");
+            let mut builder = web::WebVerifierBuilder::new().database(db.clone());
+            if let Some(resolver) = config.dns_resolver {
+                builder = builder.dns_resolver(resolver);
+            }
+            if let Some(url) = config.well_known_url {
+                builder = builder.well_known_url(url);
+            }
+
+            let web_verifier = builder.build()?;
+
+            info!("Starting message adapter");
+            listener
+                .start_message_adapter(web_verifier, config.request_interval)
+                .await;
+
+            Result::Ok(())
+        }
+        .instrument(span)
+        .await?;
+
+        started = true;
+    }
+
+    // GitHub verifier configuration and execution.
+    if let Some(config) = github_config.filter(|config| config.enabled) {
+        let span = info_span!("github_verifier");
+        info!(api_base = config.api_base.as_deref().unwrap_or("default"));
+
+        async {
+            info!("Configuring verifier");
+            let mut builder = github::GithubBuilder::new().database(db.clone());
+            if let Some(api_base) = config.api_base {
+                builder = builder.api_base(api_base);
+            }
+            if let Some(token) = config.token {
+                builder = builder.token(token);
+            }
+
+            let github_verifier = builder.build()?;
+
+            info!("Starting message adapter");
+            listener
+                .start_message_adapter(github_verifier, config.request_interval)
+                .await;
+
+            Result::Ok(())
+        }
+        .instrument(span)
+        .await?;
+
+        started = true;
+    }
+
     if !started {
         warn!("No adapters are enabled");
     }
@@ -128,6 +229,42 @@ pub async fn run_adapters(config: AdapterConfig, db: Database) -> Result<()> {
     Ok(())
 }
 
+/// Truncates a fetched proof to at most `max` bytes, without splitting a
+/// character.
+fn truncate(mut content: String, max: usize) -> String {
+    if content.len() > max {
+        let mut end = max;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        content.truncate(end);
+    }
+
+    content
+}
+
+/// Reads the body of a fetched proof, but at most `max` bytes. The rest of a
+/// larger body is never read, so the size of the response does not matter.
+async fn read_capped(mut resp: reqwest::Response, max: usize) -> Result<String> {
+    let capacity = resp
+        .content_length()
+        .map(|length| (length as usize).min(max))
+        .unwrap_or_default();
+
+    let mut body = Vec::with_capacity(capacity);
+    while let Some(chunk) = resp.chunk().await? {
+        let remaining = max - body.len();
+        body.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+
+        if body.len() >= max {
+            break;
+        }
+    }
+
+    Ok(truncate(String::from_utf8_lossy(&body).into_owned(), max))
+}
+
 #[async_trait]
 pub trait Adapter {
     type MessageType;
@@ -135,6 +272,20 @@ pub trait Adapter {
     fn name(&self) -> &'static str;
     async fn fetch_messages(&mut self) -> Result<Vec<ExternalMessage>>;
     async fn send_message(&mut self, to: &str, content: Self::MessageType) -> Result<()>;
+    /// Informs the owner of the field about the outcome of a verification
+    /// attempt. Only relevant for adapters that can reply to their users.
+    async fn notify_verification_result(
+        &mut self,
+        _field: &IdentityFieldValue,
+        _is_verified: bool,
+    ) -> Result<()> {
+        Ok(())
+    }
+    /// Sends an alert to the admins. Only relevant for adapters that know
+    /// about admins.
+    async fn notify_admins(&mut self, _message: &str) -> Result<()> {
+        Ok(())
+    }
 }
 
 // Filler for adapters that do not send messages.
@@ -156,16 +307,30 @@ impl AdapterListener {
         <T as Adapter>::MessageType: From<ExpectedMessage>,
     {
         let mut interval = interval(Duration::from_secs(timeout));
+        let label = adapter.name().to_lowercase();
+        health().adapter_started(&label, timeout);
 
         let mut db = self.db.clone();
         let mut cursor = EventCursor::new();
+        let notify_admins = db
+            .failed_attempts()
+            .map(|config| config.notify_admins)
+            .unwrap_or(false);
         actix::spawn(async move {
             loop {
                 // Timeout (skipped the first time);
                 interval.tick().await;
 
                 // Fetch message and send it to the listener, if any.
-                match adapter.fetch_messages().await {
+                let timer = metrics()
+                    .adapter_poll_duration
+                    .with_label_values(&[&label])
+                    .start_timer();
+                let fetched = adapter.fetch_messages().await;
+                timer.observe_duration();
+                health().adapter_fetched(&label, &fetched);
+
+                match fetched {
                     Ok(messages) => {
                         for message in messages {
                             info!("Processing message from: {:?}", message.origin);
@@ -181,30 +346,79 @@ impl AdapterListener {
                             adapter.name(),
                             err
                         );
+                        metrics()
+                            .adapter_poll_errors
+                            .with_label_values(&[&label])
+                            .inc();
                     }
                 }
 
-                // Check if a second challenge must be sent to the user directly.
+                // Check if a second challenge or a verification result must be
+                // sent to the user directly.
                 match db.fetch_events(&mut cursor).await {
                     Ok(events) => {
-                        for event in &events {
-                            if let NotificationMessage::AwaitingSecondChallenge { context, field } =
-                                event
-                            {
-                                if let IdentityFieldValue::Email(to) = field {
-                                    if adapter.name() == "email" {
-                                        info!("Sending second challenge to {}", to);
-                                        if let Ok(challenge) = db
-                                            .fetch_second_challenge(context, field)
-                                            .await
-                                            .map_err(|err| error!("Failed to fetch second challenge from database: {:?}", err)) {
-                                                let _ = adapter
-                                                    .send_message(to.as_str(), challenge.into())
-                                                    .await
-                                                    .map_err(|err| error!("Failed to send second challenge to {} ({} adapter): {:?}", to, adapter.name(), err));
+                        for (_, event) in &events {
+                            match event {
+                                NotificationMessage::AwaitingSecondChallenge { context, field } => {
+                                    if let IdentityFieldValue::Email(to) = field {
+                                        if adapter.name() == "email" {
+                                            info!("Sending second challenge to {}", to);
+                                            if let Ok(challenge) = db
+                                                .fetch_second_challenge(context, field)
+                                                .await
+                                                .map_err(|err| error!("Failed to fetch second challenge from database: {:?}", err)) {
+                                                    match adapter.send_message(to.as_str(), challenge.into()).await {
+                                                        Ok(()) => {
+                                                            let _ = db
+                                                                .set_second_challenge_issued(context, field)
+                                                                .await
+                                                                .map_err(|err| error!("Failed to update second challenge: {:?}", err));
+                                                        }
+                                                        Err(err) => error!("Failed to send second challenge to {} ({} adapter): {:?}", to, adapter.name(), err),
+                                                    }
                                                 }
+                                        }
                                     }
                                 }
+                                NotificationMessage::FieldVerified { context: _, field } => {
+                                    let _ = adapter
+                                        .notify_verification_result(field, true)
+                                        .await
+                                        .map_err(|err| error!("Failed to send verification result ({} adapter): {:?}", adapter.name(), err));
+                                }
+                                NotificationMessage::FieldVerificationFailed {
+                                    context: _,
+                                    field,
+                                } => {
+                                    let _ = adapter
+                                        .notify_verification_result(field, false)
+                                        .await
+                                        .map_err(|err| error!("Failed to send verification result ({} adapter): {:?}", adapter.name(), err));
+                                }
+                                NotificationMessage::FieldLocked {
+                                    context,
+                                    field,
+                                    locked_until,
+                                } if notify_admins => {
+                                    let (ty, value) = field.as_account_type();
+                                    let alert = format!(
+                                        "Too many failed attempts: locked {:?} field '{}' of {} ({}) until UNIX time {}",
+                                        ty,
+                                        value,
+                                        context.address.as_str(),
+                                        context.chain.as_str(),
+                                        locked_until.raw()
+                                    );
+
+                                    let _ = adapter.notify_admins(&alert).await.map_err(|err| {
+                                        error!(
+                                            "Failed to alert admins ({} adapter): {:?}",
+                                            adapter.name(),
+                                            err
+                                        )
+                                    });
+                                }
+                                _ => {}
                             }
                         }
                     }
@@ -255,6 +469,7 @@ pub mod tests {
             let mut lock = self.messages.lock().await;
             Ok(std::mem::take(&mut *lock))
         }
+        #[allow(clippy::diverging_sub_expression)]
         async fn send_message(&mut self, _to: &str, _content: Self::MessageType) -> Result<()> {
             unimplemented!()
         }
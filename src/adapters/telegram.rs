@@ -0,0 +1,449 @@
+use crate::adapters::Adapter;
+use crate::primitives::{
+    normalize_telegram_handle, ExpectedMessage, ExternalMessage, ExternalMessageType,
+    IdentityFieldValue, Timestamp,
+};
+use crate::{Database, Result};
+use reqwest::header::{self, HeaderValue};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const DEFAULT_API_BASE: &str = "https://api.telegram.org";
+
+// The amount of seconds the Telegram API holds a `getUpdates` request open
+// until new messages arrive (long-polling).
+#[cfg(not(test))]
+const LONG_POLL_TIMEOUT: u64 = 10;
+#[cfg(test)]
+const LONG_POLL_TIMEOUT: u64 = 0;
+
+pub struct TelegramBuilder {
+    token: Option<String>,
+    api_base: Option<String>,
+    db: Option<Database>,
+}
+
+impl TelegramBuilder {
+    pub fn new() -> Self {
+        TelegramBuilder {
+            token: None,
+            api_base: None,
+            db: None,
+        }
+    }
+    pub fn token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+    pub fn api_base(mut self, api_base: String) -> Self {
+        self.api_base = Some(api_base);
+        self
+    }
+    /// Persists the chat Ids, so replies can still be sent after a restart.
+    pub fn database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+    pub fn build(self) -> Result<TelegramClient> {
+        Ok(TelegramClient {
+            client: Client::new(),
+            token: self
+                .token
+                .ok_or_else(|| anyhow!("bot token not specified"))?,
+            api_base: self
+                .api_base
+                .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            offset: 0,
+            chat_ids: HashMap::new(),
+            db: self.db,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct TelegramClient {
+    client: Client,
+    token: String,
+    api_base: String,
+    // The Id of the next update to fetch. Requesting updates with this offset
+    // acknowledges all previous updates.
+    offset: i64,
+    // Maps Telegram handles to the Id of the private chat with the bot, which
+    // is required for sending replies. Cached in front of the database.
+    chat_ids: HashMap<String, i64>,
+    db: Option<Database>,
+}
+
+impl TelegramClient {
+    async fn request_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        debug!("Requesting Telegram messages");
+        let updates = self
+            .post_request::<Vec<ApiUpdate>, _>(
+                "getUpdates",
+                &ApiGetUpdates {
+                    offset: self.offset,
+                    timeout: LONG_POLL_TIMEOUT,
+                    allowed_updates: vec!["message"],
+                },
+            )
+            .await?;
+
+        if updates.is_empty() {
+            debug!("No new Telegram messages found");
+            return Ok(vec![]);
+        } else {
+            debug!("Fetched {} update(-s)", updates.len());
+        }
+
+        let mut parsed_messages = vec![];
+        for update in updates {
+            self.offset = self.offset.max(update.update_id + 1);
+
+            let message = match update.message {
+                Some(message) => message,
+                None => continue,
+            };
+
+            // Only direct messages to the bot are accepted.
+            if message.chat.chat_type != "private" {
+                debug!("Skipping Telegram message from non-private chat");
+                continue;
+            }
+
+            let (from, text) = match (message.from, message.text) {
+                (Some(from), Some(text)) => (from, text),
+                _ => {
+                    debug!("Skipping Telegram message without sender or text");
+                    continue;
+                }
+            };
+
+            // The challenge can only be matched against a handle.
+            let sender = match from.username {
+                Some(username) => normalize_telegram_handle(&username),
+                None => {
+                    let _ = self
+                        .send_text(
+                            message.chat.id,
+                            "Please set a Telegram username that matches your on-chain identity and try again.",
+                        )
+                        .await
+                        .map_err(|err| error!("Failed to reply to Telegram user: {:?}", err));
+
+                    continue;
+                }
+            };
+
+            debug!("Received message from {}", sender);
+            if self.chat_ids.insert(sender.clone(), message.chat.id) != Some(message.chat.id) {
+                if let Some(db) = &self.db {
+                    let _ = db
+                        .set_telegram_chat_id(&sender, message.chat.id)
+                        .await
+                        .map_err(|err| error!("Failed to store Telegram chat Id: {:?}", err));
+                }
+            }
+
+            parsed_messages.push(ExternalMessage {
+                origin: ExternalMessageType::Telegram(sender),
+                id: message.message_id.into(),
+                timestamp: Timestamp::now(),
+                values: vec![text.into()],
+            });
+        }
+
+        Ok(parsed_messages)
+    }
+    async fn send_text(&self, chat_id: i64, text: &str) -> Result<()> {
+        let _ = self
+            .post_request::<serde_json::Value, _>("sendMessage", &ApiSendMessage { chat_id, text })
+            .await?;
+
+        Ok(())
+    }
+    async fn send_text_to(&mut self, to: &str, text: &str) -> Result<()> {
+        let to = normalize_telegram_handle(to);
+        let chat_id = match self.chat_ids.get(&to) {
+            Some(chat_id) => Some(*chat_id),
+            None => match &self.db {
+                Some(db) => db.fetch_telegram_chat_id(&to).await?,
+                None => None,
+            },
+        };
+
+        // The bot can only contact users who messaged it before.
+        let chat_id = match chat_id {
+            Some(chat_id) => chat_id,
+            None => {
+                debug!("No private chat with {} is known, skipping message", to);
+                return Ok(());
+            }
+        };
+
+        self.chat_ids.insert(to, chat_id);
+        self.send_text(chat_id, text).await
+    }
+    async fn post_request<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: &str,
+        body: &B,
+    ) -> Result<T> {
+        let url = format!("{}/bot{}/{}", self.api_base, self.token, method);
+
+        let resp = self
+            .client
+            .post(&url)
+            .header(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )
+            .body(serde_json::to_string(body)?)
+            .send()
+            .await?;
+
+        let txt = resp.text().await?;
+
+        debug!("Telegram response: {:?}", txt);
+
+        let resp = serde_json::from_str::<ApiResponse<T>>(&txt)?;
+        if resp.ok {
+            resp.result
+                .ok_or_else(|| anyhow!("no result in Telegram response to '{}'", method))
+        } else {
+            Err(anyhow!(
+                "Telegram API error on '{}': {}",
+                method,
+                resp.description.unwrap_or_default()
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiGetUpdates {
+    offset: i64,
+    timeout: u64,
+    allowed_updates: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiSendMessage<'a> {
+    chat_id: i64,
+    text: &'a str,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ApiUpdate {
+    update_id: i64,
+    message: Option<ApiMessage>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ApiMessage {
+    message_id: u64,
+    from: Option<ApiUser>,
+    chat: ApiChat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ApiUser {
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ApiChat {
+    id: i64,
+    #[serde(rename = "type")]
+    chat_type: String,
+}
+
+#[async_trait]
+impl Adapter for TelegramClient {
+    type MessageType = ExpectedMessage;
+
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+    async fn fetch_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        self.request_messages().await
+    }
+    async fn send_message(&mut self, to: &str, content: Self::MessageType) -> Result<()> {
+        self.send_text_to(
+            to,
+            &format!(
+                "Insert the following challenge into the web interface: {}",
+                content.value
+            ),
+        )
+        .await
+    }
+    async fn notify_verification_result(
+        &mut self,
+        field: &IdentityFieldValue,
+        is_verified: bool,
+    ) -> Result<()> {
+        let to = match field {
+            IdentityFieldValue::Telegram(to) => to,
+            _ => return Ok(()),
+        };
+
+        let text = if is_verified {
+            "Your Telegram account has been verified."
+        } else {
+            "The provided challenge is invalid. Please check the web interface and try again."
+        };
+
+        self.send_text_to(to, text).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_test::{start, TestServer};
+    use actix_web::{web, App, HttpResponse};
+    use std::sync::{Arc, Mutex};
+
+    const TOKEN: &str = "123:TEST";
+
+    #[derive(Clone, Default)]
+    struct FakeTelegram {
+        updates: Arc<Mutex<Vec<serde_json::Value>>>,
+        offsets: Arc<Mutex<Vec<i64>>>,
+        sent: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    impl FakeTelegram {
+        fn start(&self) -> TestServer {
+            let fake = self.clone();
+
+            start(move || {
+                let (get_fake, send_fake) = (fake.clone(), fake.clone());
+
+                App::new()
+                    .route(
+                        &format!("/bot{}/getUpdates", TOKEN),
+                        web::post().to(move |body: web::Json<serde_json::Value>| {
+                            let fake = get_fake.clone();
+                            async move {
+                                let offset = body["offset"].as_i64().unwrap();
+                                fake.offsets.lock().unwrap().push(offset);
+
+                                let updates: Vec<serde_json::Value> =
+                                    std::mem::take(&mut *fake.updates.lock().unwrap());
+
+                                HttpResponse::Ok()
+                                    .json(serde_json::json!({ "ok": true, "result": updates }))
+                            }
+                        }),
+                    )
+                    .route(
+                        &format!("/bot{}/sendMessage", TOKEN),
+                        web::post().to(move |body: web::Json<serde_json::Value>| {
+                            let fake = send_fake.clone();
+                            async move {
+                                fake.sent.lock().unwrap().push(body.into_inner());
+
+                                HttpResponse::Ok()
+                                    .json(serde_json::json!({ "ok": true, "result": {} }))
+                            }
+                        }),
+                    )
+            })
+        }
+        fn push_update(&self, update_id: i64, chat_type: &str, username: Option<&str>, text: &str) {
+            self.updates.lock().unwrap().push(serde_json::json!({
+                "update_id": update_id,
+                "message": {
+                    "message_id": update_id as u64 + 100,
+                    "from": { "id": 42, "username": username },
+                    "chat": { "id": 4242, "type": chat_type },
+                    "text": text,
+                }
+            }));
+        }
+    }
+
+    fn client(server: &TestServer) -> TelegramClient {
+        TelegramBuilder::new()
+            .token(TOKEN.to_string())
+            .api_base(server.url(""))
+            .build()
+            .unwrap()
+    }
+
+    #[actix::test]
+    async fn fetch_direct_messages() {
+        let fake = FakeTelegram::default();
+        let server = fake.start();
+        let mut client = client(&server);
+
+        fake.push_update(1, "private", Some("Alice"), "some challenge");
+        fake.push_update(2, "group", Some("Bob"), "group noise");
+
+        let messages = client.fetch_messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(
+            messages[0].origin,
+            ExternalMessageType::Telegram("@alice".to_string())
+        );
+        assert_eq!(
+            messages[0].values,
+            vec!["some challenge".to_string().into()]
+        );
+
+        // All updates are acknowledged on the next request.
+        let messages = client.fetch_messages().await.unwrap();
+        assert!(messages.is_empty());
+        assert_eq!(*fake.offsets.lock().unwrap(), vec![0, 3]);
+    }
+
+    #[actix::test]
+    async fn reply_verification_result() {
+        let fake = FakeTelegram::default();
+        let server = fake.start();
+        let mut client = client(&server);
+
+        fake.push_update(1, "private", Some("alice"), "some challenge");
+        fake.push_update(2, "private", None, "no username");
+        let messages = client.fetch_messages().await.unwrap();
+        assert_eq!(messages.len(), 1);
+
+        client
+            .notify_verification_result(&IdentityFieldValue::Telegram("@alice".to_string()), true)
+            .await
+            .unwrap();
+
+        // Fields of other adapters are ignored.
+        client
+            .notify_verification_result(&IdentityFieldValue::Matrix("@alice".to_string()), true)
+            .await
+            .unwrap();
+
+        // Unknown handles cannot be contacted and are skipped.
+        client
+            .notify_verification_result(&IdentityFieldValue::Telegram("@bob".to_string()), false)
+            .await
+            .unwrap();
+
+        let sent = fake.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        // Request to set a username.
+        assert_eq!(sent[0]["chat_id"], 4242);
+        // Verification result.
+        assert_eq!(sent[1]["chat_id"], 4242);
+        assert_eq!(sent[1]["text"], "Your Telegram account has been verified.");
+    }
+}
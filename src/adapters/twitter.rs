@@ -227,7 +227,7 @@ impl TwitterClient {
             fields.append(&mut params.to_vec());
         }
 
-        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        fields.sort_by_key(|(a, _)| *a);
 
         let mut params = String::new();
         for (name, val) in &fields {
@@ -254,7 +254,7 @@ impl TwitterClient {
 
         // Insert the signature;
         fields.push(("oauth_signature", &sig));
-        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        fields.sort_by_key(|(a, _)| *a);
 
         // Merge all fields into the OAuth header.
         let mut oauth_header = String::new();
@@ -426,6 +426,7 @@ impl Adapter for TwitterClient {
     async fn fetch_messages(&mut self) -> Result<Vec<ExternalMessage>> {
         self.request_messages().await
     }
+    #[allow(clippy::diverging_sub_expression)]
     async fn send_message(&mut self, _to: &str, _content: Self::MessageType) -> Result<()> {
         unimplemented!()
     }
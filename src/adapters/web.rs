@@ -0,0 +1,399 @@
+use crate::adapters::admin::RawFieldName;
+use crate::adapters::{read_capped, Adapter};
+use crate::primitives::{ExternalMessage, ExternalMessageType, IdentityFieldValue, Timestamp};
+use crate::{Database, Result};
+use reqwest::header::{self, HeaderValue};
+use reqwest::{redirect, Client};
+use std::collections::HashMap;
+use url::Url;
+
+// DNS-over-HTTPS resolver supporting the JSON API.
+const DEFAULT_DNS_RESOLVER: &str = "https://dns.google/resolve";
+const DEFAULT_WELL_KNOWN_URL: &str = "https://{domain}/.well-known/polkadot-identity";
+// The DNS record type of TXT records.
+const TXT_RECORD_TYPE: u16 = 16;
+// Larger `.well-known` files are truncated.
+const MAX_WELL_KNOWN_SIZE: usize = 64 * 1024;
+// Larger DNS-over-HTTPS responses are rejected.
+const MAX_DNS_RESPONSE_SIZE: usize = 64 * 1024;
+// Maximum number of redirects followed when fetching a proof.
+const MAX_REDIRECTS: usize = 5;
+
+pub struct WebVerifierBuilder {
+    db: Option<Database>,
+    dns_resolver: Option<String>,
+    well_known_url: Option<String>,
+}
+
+impl WebVerifierBuilder {
+    pub fn new() -> Self {
+        WebVerifierBuilder {
+            db: None,
+            dns_resolver: None,
+            well_known_url: None,
+        }
+    }
+    pub fn database(mut self, db: Database) -> Self {
+        self.db = Some(db);
+        self
+    }
+    pub fn dns_resolver(mut self, resolver: String) -> Self {
+        self.dns_resolver = Some(resolver);
+        self
+    }
+    /// The URL of the proof file, where `{domain}` gets replaced with the
+    /// domain of the identity.
+    pub fn well_known_url(mut self, url: String) -> Self {
+        self.well_known_url = Some(url);
+        self
+    }
+    pub fn build(self) -> Result<WebVerifier> {
+        let well_known_url = self
+            .well_known_url
+            .unwrap_or_else(|| DEFAULT_WELL_KNOWN_URL.to_string());
+
+        if !well_known_url.contains("{domain}") {
+            return Err(anyhow!("well-known URL does not contain '{{domain}}'"));
+        }
+
+        Ok(WebVerifier {
+            db: self.db.ok_or_else(|| anyhow!("database not specified"))?,
+            fetcher: ProofFetcher {
+                client: proof_client()?,
+                dns_resolver: self
+                    .dns_resolver
+                    .unwrap_or_else(|| DEFAULT_DNS_RESOLVER.to_string()),
+                well_known_url,
+            },
+        })
+    }
+}
+
+/// Verifies web domains by looking up proofs, either as a DNS TXT record or as
+/// a `.well-known/polkadot-identity` file. A proof must contain the challenge
+/// or the address of the identity. Pending web fields are re-checked on every
+/// request interval.
+pub struct WebVerifier {
+    db: Database,
+    fetcher: ProofFetcher,
+}
+
+impl WebVerifier {
+    async fn request_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        let pending = self
+            .db
+            .fetch_unverified_challenges(&RawFieldName::Web)
+            .await?;
+
+        if pending.is_empty() {
+            debug!("No pending web fields found");
+            return Ok(vec![]);
+        }
+
+        // Multiple identities might specify the same domain.
+        let mut proofs: HashMap<String, Vec<String>> = HashMap::new();
+
+        let mut messages = vec![];
+        for (context, field, expected) in pending {
+            let web = match field {
+                IdentityFieldValue::Web(web) => web,
+                _ => continue,
+            };
+
+            let domain = match domain_of(&web) {
+                Some(domain) => domain,
+                None => {
+                    debug!("Skipping invalid web domain: {}", web);
+                    continue;
+                }
+            };
+
+            if !proofs.contains_key(&domain) {
+                let found = self.fetcher.fetch_proofs(&domain).await;
+                proofs.insert(domain.clone(), found);
+            }
+
+            let records = &proofs[&domain];
+            if records.iter().any(|record| {
+                record.contains(&expected.value) || record.contains(context.address.as_str())
+            }) {
+                debug!("Found proof for {}", domain);
+
+                messages.push(ExternalMessage {
+                    origin: ExternalMessageType::Web(web),
+                    // Proofs are looked up, there is no message Id.
+                    id: 0u32.into(),
+                    timestamp: Timestamp::now(),
+                    values: records.iter().cloned().map(|r| r.into()).collect(),
+                });
+            }
+        }
+
+        Ok(messages)
+    }
+}
+
+struct ProofFetcher {
+    client: Client,
+    dns_resolver: String,
+    well_known_url: String,
+}
+
+impl ProofFetcher {
+    /// Collects the proofs of both sources. A missing or unreachable source
+    /// is not an error.
+    async fn fetch_proofs(&self, domain: &str) -> Vec<String> {
+        let mut proofs = vec![];
+
+        match self.lookup_txt_records(domain).await {
+            Ok(mut records) => proofs.append(&mut records),
+            Err(err) => debug!("Failed to look up TXT records of {}: {:?}", domain, err),
+        }
+
+        match self.fetch_well_known(domain).await {
+            Ok(Some(file)) => proofs.push(file),
+            Ok(None) => {}
+            Err(err) => debug!("Failed to fetch well-known file of {}: {:?}", domain, err),
+        }
+
+        proofs
+    }
+    async fn lookup_txt_records(&self, domain: &str) -> Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct DnsResponse {
+            #[serde(rename = "Answer", default)]
+            answer: Vec<DnsAnswer>,
+        }
+
+        #[derive(Deserialize)]
+        struct DnsAnswer {
+            #[serde(rename = "type")]
+            record_type: u16,
+            data: String,
+        }
+
+        let resp = self
+            .client
+            .get(&self.dns_resolver)
+            .query(&[("name", domain), ("type", "TXT")])
+            .header(
+                header::ACCEPT,
+                HeaderValue::from_static("application/dns-json"),
+            )
+            .send()
+            .await?
+            .error_for_status()?;
+
+        if resp
+            .content_length()
+            .map(|length| length > MAX_DNS_RESPONSE_SIZE as u64)
+            .unwrap_or(false)
+        {
+            return Err(anyhow!("DNS response of {} is too large", domain));
+        }
+
+        // A truncated response fails to parse.
+        let resp = read_capped(resp, MAX_DNS_RESPONSE_SIZE).await?;
+        let parsed: DnsResponse = serde_json::from_str(&resp)?;
+
+        Ok(parsed
+            .answer
+            .into_iter()
+            .filter(|answer| answer.record_type == TXT_RECORD_TYPE)
+            // Long TXT records are split into multiple quoted strings.
+            .map(|answer| {
+                answer
+                    .data
+                    .replace("\" \"", "")
+                    .trim_matches('"')
+                    .to_string()
+            })
+            .collect())
+    }
+    async fn fetch_well_known(&self, domain: &str) -> Result<Option<String>> {
+        let url = self.well_known_url.replace("{domain}", domain);
+
+        let resp = self.client.get(&url).send().await?;
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+
+        Ok(Some(read_capped(resp, MAX_WELL_KNOWN_SIZE).await?))
+    }
+}
+
+/// Only follows redirects within the same host, so a proof cannot be served
+/// by a different domain.
+fn proof_client() -> Result<Client> {
+    let policy = redirect::Policy::custom(|attempt| {
+        let is_same_host = attempt
+            .previous()
+            .first()
+            .map(|first| first.host_str() == attempt.url().host_str())
+            .unwrap_or(false);
+
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if is_same_host {
+            attempt.follow()
+        } else {
+            attempt.stop()
+        }
+    });
+
+    Ok(Client::builder().redirect(policy).build()?)
+}
+
+/// Extracts the domain from the web field, which might be specified as a full
+/// URL or as a plain domain.
+fn domain_of(web: &str) -> Option<String> {
+    let web = web.trim();
+    let url = if web.contains("://") {
+        Url::parse(web)
+    } else {
+        Url::parse(&format!("https://{}", web))
+    }
+    .ok()?;
+
+    url.domain().map(|domain| domain.to_lowercase())
+}
+
+#[async_trait]
+impl Adapter for WebVerifier {
+    type MessageType = ();
+
+    fn name(&self) -> &'static str {
+        "web"
+    }
+    async fn fetch_messages(&mut self) -> Result<Vec<ExternalMessage>> {
+        self.request_messages().await
+    }
+    #[allow(clippy::diverging_sub_expression)]
+    async fn send_message(&mut self, _to: &str, _content: Self::MessageType) -> Result<()> {
+        unimplemented!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_test::{start, TestServer};
+    use actix_web::{web, App, HttpRequest, HttpResponse};
+
+    fn fake_server() -> TestServer {
+        start(|| {
+            App::new()
+                .route(
+                    "/resolve",
+                    web::get().to(|req: HttpRequest| async move {
+                        let answer = if req.query_string().contains("name=alice.com") {
+                            serde_json::json!([
+                                { "name": "alice.com.", "type": 16, "data": "\"v=spf1 -all\"" },
+                                { "name": "alice.com.", "type": 16, "data": "\"polkadot=\" \"CHALLENGE\"" },
+                                { "name": "alice.com.", "type": 1, "data": "127.0.0.1" },
+                            ])
+                        } else {
+                            serde_json::json!([])
+                        };
+
+                        HttpResponse::Ok().json(serde_json::json!({ "Status": 0, "Answer": answer }))
+                    }),
+                )
+                .route(
+                    "/bob.com/.well-known/polkadot-identity",
+                    web::get().to(|| async { HttpResponse::Ok().body("ADDRESS\n") }),
+                )
+                .route(
+                    "/carol.com/.well-known/polkadot-identity",
+                    web::get().to(|| async {
+                        HttpResponse::Found()
+                            .insert_header((header::LOCATION, "/bob.com/.well-known/polkadot-identity"))
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/erin.com/.well-known/polkadot-identity",
+                    web::get().to(|| async {
+                        HttpResponse::Ok().body(format!("ADDRESS\n{}", "x".repeat(MAX_WELL_KNOWN_SIZE * 4)))
+                    }),
+                )
+                .route(
+                    "/dave.com/.well-known/polkadot-identity",
+                    web::get().to(|req: HttpRequest| async move {
+                        // Same server, but a different host.
+                        let host = req.connection_info().host().to_string();
+                        let (name, port) = host.rsplit_once(':').unwrap();
+                        let other = if name == "localhost" {
+                            "127.0.0.1"
+                        } else {
+                            "localhost"
+                        };
+
+                        HttpResponse::Found()
+                            .insert_header((
+                                header::LOCATION,
+                                format!(
+                                    "http://{}:{}/bob.com/.well-known/polkadot-identity",
+                                    other, port
+                                ),
+                            ))
+                            .finish()
+                    }),
+                )
+        })
+    }
+
+    fn proof_fetcher(server: &TestServer) -> ProofFetcher {
+        ProofFetcher {
+            client: proof_client().unwrap(),
+            dns_resolver: server.url("/resolve"),
+            well_known_url: server.url("/{domain}/.well-known/polkadot-identity"),
+        }
+    }
+
+    #[test]
+    fn parse_domain() {
+        assert_eq!(domain_of("alice.com"), Some("alice.com".to_string()));
+        assert_eq!(domain_of(" Alice.com "), Some("alice.com".to_string()));
+        assert_eq!(
+            domain_of("https://www.alice.com/about"),
+            Some("www.alice.com".to_string())
+        );
+        assert_eq!(domain_of("http://127.0.0.1"), None);
+        assert_eq!(domain_of(""), None);
+    }
+
+    #[actix::test]
+    async fn fetch_proofs() {
+        let server = fake_server();
+        let fetcher = proof_fetcher(&server);
+
+        // DNS TXT records.
+        let proofs = fetcher.fetch_proofs("alice.com").await;
+        assert_eq!(
+            proofs,
+            vec!["v=spf1 -all".to_string(), "polkadot=CHALLENGE".to_string()]
+        );
+
+        // Well-known file.
+        let proofs = fetcher.fetch_proofs("bob.com").await;
+        assert_eq!(proofs, vec!["ADDRESS\n".to_string()]);
+
+        // Redirects are only followed within the same host.
+        let proofs = fetcher.fetch_proofs("carol.com").await;
+        assert_eq!(proofs, vec!["ADDRESS\n".to_string()]);
+
+        let proofs = fetcher.fetch_proofs("dave.com").await;
+        assert!(proofs.is_empty());
+
+        // Large files are truncated.
+        let proofs = fetcher.fetch_proofs("erin.com").await;
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].len(), MAX_WELL_KNOWN_SIZE);
+        assert!(proofs[0].starts_with("ADDRESS\n"));
+
+        // No proofs.
+        let proofs = fetcher.fetch_proofs("eve.com").await;
+        assert!(proofs.is_empty());
+    }
+}
@@ -0,0 +1,273 @@
+use super::judgement_state::NO_JUDGEMENT_REQUEST;
+use super::JsonResult;
+use crate::database::Database;
+use crate::primitives::{AccountKeyType, IdentityContext};
+use actix::prelude::*;
+use actix_web::{web, HttpResponse};
+use ed25519_dalek::Verifier;
+
+// Signing context of sr25519 signatures created by Substrate wallets.
+const SR25519_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+pub struct AccountKeyVerifier {
+    db: Database,
+}
+
+impl Default for AccountKeyVerifier {
+    fn default() -> Self {
+        panic!("AccountKeyVerifier is not initialized");
+    }
+}
+
+impl AccountKeyVerifier {
+    pub fn new(db: Database) -> Self {
+        AccountKeyVerifier { db }
+    }
+}
+
+impl SystemService for AccountKeyVerifier {}
+impl Supervised for AccountKeyVerifier {}
+
+impl Actor for AccountKeyVerifier {
+    type Context = Context<Self>;
+}
+
+/// Requests the message which must be signed with the account key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
+#[rtype(result = "JsonResult<AccountKeyChallenge>")]
+pub struct RequestAccountKeyChallenge {
+    pub context: IdentityContext,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AccountKeyChallenge {
+    pub message: String,
+    pub is_verified: bool,
+}
+
+/// The hex encoded signature of the message, created with the account key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
+#[rtype(result = "JsonResult<bool>")]
+pub struct VerifyAccountKey {
+    pub context: IdentityContext,
+    pub signature: String,
+}
+
+impl Handler<RequestAccountKeyChallenge> for AccountKeyVerifier {
+    type Result = ResponseActFuture<Self, JsonResult<AccountKeyChallenge>>;
+
+    fn handle(
+        &mut self,
+        msg: RequestAccountKeyChallenge,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(
+            async move {
+                match db.request_account_key_proof(&msg.context).await {
+                    Ok(Some(proof)) => JsonResult::Ok(AccountKeyChallenge {
+                        message: proof.message(&msg.context),
+                        is_verified: proof.is_verified(),
+                    }),
+                    Ok(None) => JsonResult::Err(NO_JUDGEMENT_REQUEST.to_string()),
+                    Err(err) => {
+                        error!("Failed to request account key proof: {:?}", err);
+                        JsonResult::Err("Backend error, contact admin".to_string())
+                    }
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<VerifyAccountKey> for AccountKeyVerifier {
+    type Result = ResponseActFuture<Self, JsonResult<bool>>;
+
+    fn handle(&mut self, msg: VerifyAccountKey, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(
+            async move {
+                let state = match db.fetch_judgement_state(&msg.context).await {
+                    Ok(Some(state)) => state,
+                    Ok(None) => return JsonResult::Err(NO_JUDGEMENT_REQUEST.to_string()),
+                    Err(err) => {
+                        error!("Failed to fetch judgement state: {:?}", err);
+                        return JsonResult::Err("Backend error, contact admin".to_string());
+                    }
+                };
+
+                let proof = match state.account_key {
+                    Some(proof) if proof.is_verified() => return JsonResult::Ok(true),
+                    Some(proof) if !proof.nonce.is_expired(db.challenge_ttl()) => proof,
+                    _ => {
+                        return JsonResult::Err(
+                            "No pending account key challenge, request a new one".to_string(),
+                        )
+                    }
+                };
+
+                let key_type = match verify_account_signature(
+                    &msg.context,
+                    &proof.message(&msg.context),
+                    &msg.signature,
+                ) {
+                    Ok(key_type) => key_type,
+                    Err(err) => return JsonResult::Err(err.to_string()),
+                };
+
+                debug!(
+                    "Verified {:?} account key of {:?}",
+                    key_type, msg.context.address
+                );
+
+                match db
+                    .set_account_key_verified(&msg.context, &proof.nonce.value, key_type)
+                    .await
+                {
+                    Ok(true) => JsonResult::Ok(true),
+                    Ok(false) => JsonResult::Err(
+                        "The account key challenge was replaced, request a new one".to_string(),
+                    ),
+                    Err(err) => {
+                        error!("Failed to verify account key: {:?}", err);
+                        JsonResult::Err("Backend error, contact admin".to_string())
+                    }
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+pub async fn request_account_key_challenge(
+    req: web::Json<RequestAccountKeyChallenge>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(
+        AccountKeyVerifier::from_registry()
+            .send(req.into_inner())
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn verify_account_key(req: web::Json<VerifyAccountKey>) -> HttpResponse {
+    HttpResponse::Ok().json(
+        AccountKeyVerifier::from_registry()
+            .send(req.into_inner())
+            .await
+            .unwrap(),
+    )
+}
+
+/// Verifies the hex encoded signature of the message, created with the account
+/// key of the identity.
+pub fn verify_account_signature(
+    context: &IdentityContext,
+    message: &str,
+    signature: &str,
+) -> std::result::Result<AccountKeyType, &'static str> {
+    let public_key = context
+        .address
+        .public_key()
+        .ok_or("Unsupported account address")?;
+
+    let signature = hex::decode(signature.trim().trim_start_matches("0x"))
+        .map_err(|_| "Invalid signature encoding")?;
+
+    verify_signature(&public_key, message.as_bytes(), &signature).ok_or("Invalid signature")
+}
+
+/// Verifies the sr25519 or ed25519 signature of the message. Wallet extensions
+/// wrap raw messages in `<Bytes>` tags before signing, so both variants are
+/// accepted.
+fn verify_signature(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8],
+) -> Option<AccountKeyType> {
+    let wrapped = [b"<Bytes>", message, b"</Bytes>"].concat();
+    let messages = [message, wrapped.as_slice()];
+
+    if let (Ok(key), Ok(sig)) = (
+        schnorrkel::PublicKey::from_bytes(public_key),
+        schnorrkel::Signature::from_bytes(signature),
+    ) {
+        if messages.iter().any(|msg| {
+            key.verify_simple(SR25519_SIGNING_CONTEXT, msg, &sig)
+                .is_ok()
+        }) {
+            return Some(AccountKeyType::Sr25519);
+        }
+    }
+
+    if let (Ok(key), Ok(sig)) = (
+        ed25519_dalek::VerifyingKey::from_bytes(public_key),
+        ed25519_dalek::Signature::from_slice(signature),
+    ) {
+        if messages.iter().any(|msg| key.verify(msg, &sig).is_ok()) {
+            return Some(AccountKeyType::Ed25519);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+
+    const MESSAGE: &[u8] = b"polkadot-registrar:polkadot:Alice:e5ba85f2aec2ed39b16da1ba6e2a2e1f";
+
+    #[test]
+    fn sr25519_signature() {
+        let keypair = schnorrkel::MiniSecretKey::from_bytes(&[1; 32])
+            .unwrap()
+            .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519);
+        let public_key = keypair.public.to_bytes();
+
+        let signature = keypair
+            .sign_simple(SR25519_SIGNING_CONTEXT, MESSAGE)
+            .to_bytes();
+        assert_eq!(
+            verify_signature(&public_key, MESSAGE, &signature),
+            Some(AccountKeyType::Sr25519)
+        );
+
+        // Signed by a wallet extension.
+        let wrapped = [b"<Bytes>", MESSAGE, b"</Bytes>"].concat();
+        let signature = keypair
+            .sign_simple(SR25519_SIGNING_CONTEXT, &wrapped)
+            .to_bytes();
+        assert_eq!(
+            verify_signature(&public_key, MESSAGE, &signature),
+            Some(AccountKeyType::Sr25519)
+        );
+
+        // Different message.
+        assert_eq!(verify_signature(&public_key, b"other", &signature), None);
+    }
+
+    #[test]
+    fn ed25519_signature() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[2; 32]);
+        let public_key = key.verifying_key().to_bytes();
+
+        let signature = key.sign(MESSAGE).to_bytes();
+        assert_eq!(
+            verify_signature(&public_key, MESSAGE, &signature),
+            Some(AccountKeyType::Ed25519)
+        );
+
+        // Signed by a different key.
+        let other = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let signature = other.sign(MESSAGE).to_bytes();
+        assert_eq!(verify_signature(&public_key, MESSAGE, &signature), None);
+
+        // Invalid signature length.
+        assert_eq!(verify_signature(&public_key, MESSAGE, &[0; 12]), None);
+    }
+}
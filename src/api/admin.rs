@@ -0,0 +1,456 @@
+use super::JsonResult;
+use crate::adapters::admin::{
+    process_admin_as, Command, RawFieldName, Response, Role, TargetAddress,
+};
+use crate::database::{Database, StatsQuery};
+use crate::primitives::{ChainAddress, ChainName};
+use crate::{AdminApiConfig, AdminTlsConfig, Result};
+use actix::prelude::*;
+use actix_tls::accept::openssl::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::http::{header, StatusCode};
+use actix_web::rt::net::TcpStream;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use openssl::nid::Nid;
+use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+use std::any::Any;
+use std::str::FromStr;
+
+/// Subject (common name) of the verified client certificate, set by the mTLS
+/// listener.
+#[derive(Debug, Clone)]
+struct ClientCertificate(String);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Credentials {
+    Token(String),
+    Certificate(String),
+    None,
+}
+
+/// Executes admin commands received via the REST API. Every authenticated
+/// call is recorded in the audit log.
+pub struct AdminApi {
+    db: Database,
+    config: AdminApiConfig,
+}
+
+impl Default for AdminApi {
+    fn default() -> Self {
+        panic!("AdminApi is not initialized");
+    }
+}
+
+impl AdminApi {
+    pub fn new(db: Database, config: AdminApiConfig) -> Self {
+        AdminApi { db, config }
+    }
+    /// Returns the name of the actor, as recorded in the audit log, and its
+    /// role.
+    fn authenticate(&self, credentials: &Credentials) -> Option<(String, Role)> {
+        match credentials {
+            Credentials::Token(token) => self
+                .config
+                .tokens
+                .iter()
+                .find(|t| constant_time_eq(t.token.as_bytes(), token.as_bytes()))
+                .map(|t| (format!("token:{}", t.name), t.role)),
+            // The certificate was already verified by the TLS acceptor.
+            Credentials::Certificate(subject) => self
+                .config
+                .tls
+                .as_ref()
+                .map(|tls| (format!("cert:{}", subject), tls.client_role)),
+            Credentials::None => None,
+        }
+    }
+}
+
+impl SystemService for AdminApi {}
+impl Supervised for AdminApi {}
+
+impl Actor for AdminApi {
+    type Context = Context<Self>;
+}
+
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "Option<Response>")]
+pub struct AdminRequest {
+    pub credentials: Credentials,
+    pub command: Command,
+}
+
+impl Handler<AdminRequest> for AdminApi {
+    // `None` if the request is unauthorized.
+    type Result = ResponseActFuture<Self, Option<Response>>;
+
+    fn handle(&mut self, msg: AdminRequest, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        let actor = self.authenticate(&msg.credentials);
+
+        Box::pin(
+            async move {
+                if let Some((actor, role)) = actor {
+                    info!("Admin API: {} executes {:?}", actor, msg.command);
+                    return Some(process_admin_as(&db, &actor, role, msg.command).await);
+                }
+
+                // Not recorded in the audit log, so unauthenticated callers
+                // cannot flood it. Only the command name is logged, the
+                // arguments are attacker controlled.
+                warn!(
+                    "Admin API: unauthorized request for '{}'",
+                    msg.command.name()
+                );
+
+                None
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+// The chain is derived from the address if not specified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressRequest {
+    pub address: ChainAddress,
+    #[serde(default)]
+    pub chain: Option<ChainName>,
+}
+
+impl AddressRequest {
+    fn target(self) -> TargetAddress {
+        TargetAddress {
+            address: self.address,
+            chain: self.chain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldsRequest {
+    pub address: ChainAddress,
+    #[serde(default)]
+    pub chain: Option<ChainName>,
+    pub fields: Vec<String>,
+}
+
+impl FieldsRequest {
+    fn target(&self) -> TargetAddress {
+        TargetAddress {
+            address: self.address.clone(),
+            chain: self.chain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRequest {
+    pub address: ChainAddress,
+    #[serde(default)]
+    pub chain: Option<ChainName>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainQuery {
+    pub chain: Option<ChainName>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchQuery {
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditQuery {
+    pub address: Option<ChainAddress>,
+    pub chain: Option<ChainName>,
+}
+
+pub fn admin_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .route("/status/{address}", web::get().to(status))
+            .route("/verify", web::post().to(verify))
+            .route("/unverify", web::post().to(unverify))
+            .route("/reset", web::post().to(reset))
+            .route("/pending", web::get().to(pending))
+            .route("/search", web::get().to(search))
+            .route("/remove", web::post().to(remove))
+            .route("/resend", web::post().to(resend))
+            .route("/audit", web::get().to(audit))
+            .route("/note", web::post().to(note))
+            .route("/notes/{address}", web::get().to(notes))
+            .route("/approve", web::post().to(approve))
+            .route("/approvals", web::get().to(approvals))
+            .route("/cancel", web::post().to(cancel))
+            .route("/stats", web::get().to(stats)),
+    );
+}
+
+async fn status(
+    req: HttpRequest,
+    address: web::Path<String>,
+    query: web::Query<ChainQuery>,
+) -> HttpResponse {
+    let target = TargetAddress {
+        address: ChainAddress::from(address.into_inner()),
+        chain: query.into_inner().chain,
+    };
+
+    execute(&req, Command::Status(target)).await
+}
+
+async fn verify(req: HttpRequest, body: web::Json<FieldsRequest>) -> HttpResponse {
+    let body = body.into_inner();
+    match parse_fields(&body.fields) {
+        Ok(fields) => execute(&req, Command::Verify(body.target(), fields)).await,
+        Err(err) => error_response(&err),
+    }
+}
+
+async fn unverify(req: HttpRequest, body: web::Json<FieldsRequest>) -> HttpResponse {
+    let body = body.into_inner();
+    match parse_fields(&body.fields) {
+        // Use `reset` for the full identity.
+        Ok(fields) if fields.contains(&RawFieldName::All) => {
+            error_response(&Response::InvalidSyntax(Some("all".to_string())))
+        }
+        Ok(fields) => execute(&req, Command::Unverify(body.target(), fields)).await,
+        Err(err) => error_response(&err),
+    }
+}
+
+async fn reset(req: HttpRequest, body: web::Json<AddressRequest>) -> HttpResponse {
+    execute(&req, Command::Reset(body.into_inner().target())).await
+}
+
+async fn pending(req: HttpRequest, query: web::Query<ChainQuery>) -> HttpResponse {
+    execute(&req, Command::Pending(query.into_inner().chain)).await
+}
+
+async fn search(req: HttpRequest, query: web::Query<SearchQuery>) -> HttpResponse {
+    execute(&req, Command::Search(query.into_inner().value)).await
+}
+
+async fn remove(req: HttpRequest, body: web::Json<AddressRequest>) -> HttpResponse {
+    execute(&req, Command::Remove(body.into_inner().target())).await
+}
+
+async fn resend(req: HttpRequest, body: web::Json<AddressRequest>) -> HttpResponse {
+    execute(&req, Command::Resend(body.into_inner().target())).await
+}
+
+async fn audit(req: HttpRequest, query: web::Query<AuditQuery>) -> HttpResponse {
+    let AuditQuery { address, chain } = query.into_inner();
+    let target = address.map(|address| TargetAddress { address, chain });
+
+    execute(&req, Command::Audit(target)).await
+}
+
+async fn note(req: HttpRequest, body: web::Json<NoteRequest>) -> HttpResponse {
+    let body = body.into_inner();
+    if body.text.trim().is_empty() {
+        return error_response(&Response::InvalidSyntax(None));
+    }
+
+    let target = TargetAddress {
+        address: body.address,
+        chain: body.chain,
+    };
+
+    execute(&req, Command::Note(target, body.text.trim().to_string())).await
+}
+
+async fn notes(
+    req: HttpRequest,
+    address: web::Path<String>,
+    query: web::Query<ChainQuery>,
+) -> HttpResponse {
+    let target = TargetAddress {
+        address: ChainAddress::from(address.into_inner()),
+        chain: query.into_inner().chain,
+    };
+
+    execute(&req, Command::Notes(target)).await
+}
+
+async fn approve(req: HttpRequest, body: web::Json<AddressRequest>) -> HttpResponse {
+    execute(&req, Command::Approve(body.into_inner().target())).await
+}
+
+async fn approvals(req: HttpRequest) -> HttpResponse {
+    execute(&req, Command::Approvals).await
+}
+
+async fn cancel(req: HttpRequest, body: web::Json<AddressRequest>) -> HttpResponse {
+    execute(&req, Command::Cancel(body.into_inner().target())).await
+}
+
+async fn stats(req: HttpRequest, query: web::Query<StatsQuery>) -> HttpResponse {
+    let query = query.into_inner();
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(JsonResult::<()>::Err(err.to_string()));
+    }
+
+    execute(&req, Command::Stats(query)).await
+}
+
+async fn execute(req: &HttpRequest, command: Command) -> HttpResponse {
+    let resp = AdminApi::from_registry()
+        .send(AdminRequest {
+            credentials: credentials_of(req),
+            command,
+        })
+        .await
+        .unwrap();
+
+    match resp {
+        Some(resp) if resp.is_success() => HttpResponse::Ok().json(JsonResult::Ok(resp)),
+        Some(resp) => error_response(&resp),
+        None => HttpResponse::Unauthorized().json(JsonResult::<()>::Err(
+            "Missing or invalid credentials".to_string(),
+        )),
+    }
+}
+
+fn error_response(resp: &Response) -> HttpResponse {
+    let status = match resp {
+        Response::IdentityNotFound(_) | Response::NoPendingApproval(_) => StatusCode::NOT_FOUND,
+        Response::NoPendingSecondChallenge | Response::ApprovalAlreadyPending(_) => {
+            StatusCode::CONFLICT
+        }
+        Response::PermissionDenied(_) | Response::SelfApproval(_) => StatusCode::FORBIDDEN,
+        Response::UnknownCommand
+        | Response::InvalidSyntax(_)
+        | Response::InvalidAddress(_)
+        | Response::AmbiguousChain(..) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    HttpResponse::build(status).json(JsonResult::<()>::Err(resp.to_string()))
+}
+
+fn parse_fields(fields: &[String]) -> std::result::Result<Vec<RawFieldName>, Response> {
+    if fields.is_empty() {
+        return Err(Response::InvalidSyntax(None));
+    }
+
+    fields
+        .iter()
+        .map(|field| RawFieldName::from_str(field))
+        .collect()
+}
+
+fn credentials_of(req: &HttpRequest) -> Credentials {
+    if let Some(cert) = req.conn_data::<ClientCertificate>() {
+        return Credentials::Certificate(cert.0.clone());
+    }
+
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| Credentials::Token(token.trim().to_string()))
+        .unwrap_or(Credentials::None)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Runs the internal listener for the `/admin` endpoints, which
+/// authenticates clients by their bearer token.
+pub fn run_admin_server(address: &str) -> Result<()> {
+    let server = HttpServer::new(|| App::new().configure(admin_routes)).bind(address)?;
+
+    actix::spawn(async move {
+        if let Err(err) = server.run().await {
+            error!("Admin API server stopped: {:?}", err);
+        }
+    });
+
+    Ok(())
+}
+
+/// Runs a separate listener for the `/admin` endpoints, which only accepts
+/// clients with a certificate issued by the configured CA.
+pub fn run_admin_tls_server(config: &AdminTlsConfig) -> Result<()> {
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+    builder.set_private_key_file(&config.key, SslFiletype::PEM)?;
+    builder.set_certificate_chain_file(&config.cert)?;
+    builder.set_ca_file(&config.client_ca)?;
+    builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+
+    let server = HttpServer::new(|| App::new().configure(admin_routes))
+        .on_connect(extract_client_certificate)
+        .bind_openssl(config.address.as_str(), builder)?;
+
+    actix::spawn(async move {
+        if let Err(err) = server.run().await {
+            error!("Admin API server (mTLS) stopped: {:?}", err);
+        }
+    });
+
+    Ok(())
+}
+
+fn extract_client_certificate(conn: &dyn Any, ext: &mut Extensions) {
+    if let Some(stream) = conn.downcast_ref::<TlsStream<TcpStream>>() {
+        let subject = stream.ssl().peer_certificate().and_then(|cert| {
+            cert.subject_name()
+                .entries_by_nid(Nid::COMMONNAME)
+                .next()
+                .and_then(|entry| std::str::from_utf8(entry.data().as_slice()).ok())
+                .map(|name| name.to_string())
+        });
+
+        if let Some(subject) = subject {
+            ext.insert(ClientCertificate(subject));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn parse_credentials() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer secret"))
+            .to_http_request();
+        assert_eq!(
+            credentials_of(&req),
+            Credentials::Token("secret".to_string())
+        );
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic secret"))
+            .to_http_request();
+        assert_eq!(credentials_of(&req), Credentials::None);
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(credentials_of(&req), Credentials::None);
+    }
+
+    #[test]
+    fn compare_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn parse_field_names() {
+        assert_eq!(
+            parse_fields(&["email".to_string(), "display_name".to_string()]),
+            Ok(vec![RawFieldName::Email, RawFieldName::DisplayName])
+        );
+        assert!(parse_fields(&["unknown".to_string()]).is_err());
+        assert!(parse_fields(&[]).is_err());
+    }
+}
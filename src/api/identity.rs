@@ -0,0 +1,290 @@
+use super::judgement_state::{
+    FetchEventHistory, FetchIdentity, FetchStats, IdentityRecord, LookupServer,
+    SseAccountStatusSession, NO_JUDGEMENT_REQUEST,
+};
+use super::JsonResult;
+use crate::database::{EventHistoryQuery, StatsQuery};
+use crate::primitives::{ChainAddress, ChainName, IdentityContext};
+use actix::prelude::*;
+use actix_web::http::header::{
+    CacheControl, CacheDirective, EntityTag, HttpDate, IfModifiedSince, IfNoneMatch, LastModified,
+    ETAG,
+};
+use actix_web::{web, Error as ActixError, HttpMessage, HttpRequest, HttpResponse};
+use futures::channel::mpsc;
+use futures::StreamExt;
+use std::str::FromStr;
+use std::time::{Duration, UNIX_EPOCH};
+
+pub const LAST_EVENT_ID: &str = "last-event-id";
+
+pub fn identity_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1")
+            .route("/identity/{chain}/{address}", web::get().to(identity))
+            .route(
+                "/identity/{chain}/{address}/events",
+                web::get().to(identity_events),
+            )
+            .route(
+                "/identity/{chain}/{address}/history",
+                web::get().to(identity_history),
+            )
+            .route("/stats", web::get().to(stats)),
+    );
+}
+
+async fn identity(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let context = match parse_context(path.into_inner()) {
+        Ok(context) => context,
+        Err(resp) => return resp,
+    };
+
+    let record = match LookupServer::from_registry()
+        .send(FetchIdentity { context })
+        .await
+        .unwrap()
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .json(JsonResult::<()>::Err(NO_JUDGEMENT_REQUEST.to_string()))
+        }
+        Err(err) => {
+            error!("Failed to fetch judgement state: {:?}", err);
+            return HttpResponse::InternalServerError().json(JsonResult::<()>::Err(
+                "Backend error, contact admin".to_string(),
+            ));
+        }
+    };
+
+    let (etag, last_modified) = cache_validators(&record);
+
+    let not_modified = is_not_modified(&req, etag.as_ref(), last_modified);
+
+    let mut resp = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+
+    // Clients must always revalidate.
+    resp.insert_header(CacheControl(vec![CacheDirective::NoCache]));
+    if let Some(etag) = etag {
+        resp.insert_header((ETAG, etag));
+    }
+    if let Some(last_modified) = last_modified {
+        resp.insert_header(LastModified(last_modified));
+    }
+
+    if not_modified {
+        resp.finish()
+    } else {
+        resp.json(JsonResult::Ok(record.state))
+    }
+}
+
+/// Streams the state of the identity as server-sent events, with the same
+/// payloads as the `/api/account_status` websocket.
+async fn identity_events(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let context = match parse_context(path.into_inner()) {
+        Ok(context) => context,
+        Err(resp) => return resp,
+    };
+
+    // Set by clients when reconnecting.
+    let last_event_id = req
+        .headers()
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    let (sender, receiver) = mpsc::unbounded();
+    SseAccountStatusSession::new(context, last_event_id, sender).start();
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(receiver.map(Ok::<_, ActixError>))
+}
+
+/// Returns the past notifications of the identity, newest first.
+async fn identity_history(
+    path: web::Path<(String, String)>,
+    query: web::Query<EventHistoryQuery>,
+) -> HttpResponse {
+    let context = match parse_context(path.into_inner()) {
+        Ok(context) => context,
+        Err(resp) => return resp,
+    };
+
+    let query = query.into_inner();
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(JsonResult::<()>::Err(err.to_string()));
+    }
+
+    match LookupServer::from_registry()
+        .send(FetchEventHistory { context, query })
+        .await
+        .unwrap()
+    {
+        Ok(Some(history)) => HttpResponse::Ok().json(JsonResult::Ok(history)),
+        Ok(None) => {
+            HttpResponse::NotFound().json(JsonResult::<()>::Err(NO_JUDGEMENT_REQUEST.to_string()))
+        }
+        Err(err) => {
+            error!("Failed to fetch event history: {:?}", err);
+            HttpResponse::InternalServerError().json(JsonResult::<()>::Err(
+                "Backend error, contact admin".to_string(),
+            ))
+        }
+    }
+}
+
+/// Returns the aggregated registrar statistics per chain and period.
+async fn stats(query: web::Query<StatsQuery>) -> HttpResponse {
+    let query = query.into_inner();
+    if let Err(err) = query.validate() {
+        return HttpResponse::BadRequest().json(JsonResult::<()>::Err(err.to_string()));
+    }
+
+    match LookupServer::from_registry()
+        .send(FetchStats { query })
+        .await
+        .unwrap()
+    {
+        Ok(stats) => HttpResponse::Ok().json(JsonResult::Ok(stats)),
+        Err(err) => {
+            error!("Failed to fetch statistics: {:?}", err);
+            HttpResponse::InternalServerError().json(JsonResult::<()>::Err(
+                "Backend error, contact admin".to_string(),
+            ))
+        }
+    }
+}
+
+/// Parses the chain and address from the path. The address must be valid on
+/// the chain.
+fn parse_context((chain, address): (String, String)) -> Result<IdentityContext, HttpResponse> {
+    let chain = ChainName::from_str(&chain).map_err(|_| {
+        HttpResponse::BadRequest().json(JsonResult::<()>::Err(format!(
+            "Unsupported chain '{}'",
+            chain
+        )))
+    })?;
+
+    let address = ChainAddress::from(address);
+    if !address.valid_chains().contains(&chain) {
+        return Err(
+            HttpResponse::BadRequest().json(JsonResult::<()>::Err(format!(
+                "'{}' is not a valid {} address",
+                address.as_str(),
+                chain.as_str()
+            ))),
+        );
+    }
+
+    Ok(IdentityContext::new(address, chain))
+}
+
+/// The ETag and Last-Modified date are derived from the latest event of the
+/// identity.
+fn cache_validators(record: &IdentityRecord) -> (Option<EntityTag>, Option<HttpDate>) {
+    match &record.last_event {
+        Some((id, timestamp)) => (
+            Some(EntityTag::new_strong(id.clone())),
+            Some(HttpDate::from(
+                UNIX_EPOCH + Duration::from_secs(timestamp.raw()),
+            )),
+        ),
+        None => (None, None),
+    }
+}
+
+fn is_not_modified(
+    req: &HttpRequest,
+    etag: Option<&EntityTag>,
+    last_modified: Option<HttpDate>,
+) -> bool {
+    // `If-Modified-Since` is ignored if `If-None-Match` is present.
+    if let Some(if_none_match) = req.get_header::<IfNoneMatch>() {
+        return match (if_none_match, etag) {
+            (IfNoneMatch::Any, Some(_)) => true,
+            (IfNoneMatch::Items(tags), Some(etag)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+            _ => false,
+        };
+    }
+
+    match (req.get_header::<IfModifiedSince>(), last_modified) {
+        (Some(IfModifiedSince(since)), Some(last_modified)) => last_modified <= since,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{JudgementState, Timestamp};
+    use actix_web::http::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn parse_path() {
+        let alice = IdentityContext::alice();
+
+        let context =
+            parse_context(("Polkadot".to_string(), alice.address.as_str().to_string())).unwrap();
+        assert_eq!(context, alice);
+
+        let resp =
+            parse_context(("westend".to_string(), alice.address.as_str().to_string())).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // Not a Kusama address.
+        let resp =
+            parse_context(("kusama".to_string(), alice.address.as_str().to_string())).unwrap_err();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn conditional_requests() {
+        let record = IdentityRecord {
+            state: JudgementState::alice().into(),
+            last_event: Some(("6400e0e2a0c3d1b0c8a5f1e2".to_string(), Timestamp::now())),
+        };
+
+        let (etag, last_modified) = cache_validators(&record);
+        let etag = etag.unwrap();
+        let last_modified = last_modified.unwrap();
+
+        let req = TestRequest::default().to_http_request();
+        assert!(!is_not_modified(&req, Some(&etag), Some(last_modified)));
+
+        let req = TestRequest::default()
+            .insert_header((IF_NONE_MATCH, etag.to_string()))
+            .to_http_request();
+        assert!(is_not_modified(&req, Some(&etag), Some(last_modified)));
+
+        let req = TestRequest::default()
+            .insert_header((IF_NONE_MATCH, "\"outdated\""))
+            .to_http_request();
+        assert!(!is_not_modified(&req, Some(&etag), Some(last_modified)));
+
+        let req = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, last_modified.to_string()))
+            .to_http_request();
+        assert!(is_not_modified(&req, Some(&etag), Some(last_modified)));
+
+        let req = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, HttpDate::from(UNIX_EPOCH).to_string()))
+            .to_http_request();
+        assert!(!is_not_modified(&req, Some(&etag), Some(last_modified)));
+
+        // Identities without events are never cached.
+        let req = TestRequest::default()
+            .insert_header((IF_NONE_MATCH, "*"))
+            .to_http_request();
+        assert!(!is_not_modified(&req, None, None));
+    }
+}
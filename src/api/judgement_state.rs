@@ -1,26 +1,54 @@
+use super::account_key::verify_account_signature;
 use super::JsonResult;
-use crate::database::Database;
-use crate::primitives::{IdentityContext, JudgementStateBlanked, NotificationMessage};
+use crate::database::{Database, EventHistory, EventHistoryQuery, StatsBucket, StatsQuery};
+use crate::metrics::metrics;
+use crate::primitives::{
+    ChallengeType, IdentityContext, IdentityFieldValue, JudgementStateBlanked, NotificationMessage,
+    Timestamp,
+};
 use actix::prelude::*;
 use actix_broker::BrokerSubscribe;
+use actix_web::web::Bytes;
 use actix_web_actors::ws;
+use futures::channel::mpsc::UnboundedSender;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-type Subscriber = Recipient<JsonResult<ResponseAccountState>>;
+type Subscriber = Recipient<AccountStateUpdate>;
 
+pub const NO_JUDGEMENT_REQUEST: &str =
+    "There is no judgement request from that account for this registrar";
+
+// Interval of the comments which keep idle event streams alive.
+const SSE_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Subscribes to the state of the identity. Returns `false` if there is no
+/// judgement request for the identity.
 #[derive(Clone, Debug, Message)]
-#[rtype(result = "()")]
+#[rtype(result = "crate::Result<bool>")]
 pub struct SubscribeAccountState {
     pub subscriber: Subscriber,
     pub id_context: IdentityContext,
+    // Replays the events after the given event id, if any, instead of
+    // sending the current state.
+    pub last_event_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct UnsubscribeAccountState {
+    pub subscriber: Subscriber,
+    pub id_contexts: Vec<IdentityContext>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
 #[rtype(result = "()")]
 pub struct NotifyAccountState {
+    // Id of the event log entry which caused the notification.
+    pub event_id: Option<String>,
     pub state: JudgementStateBlanked,
     pub notifications: Vec<NotificationMessage>,
 }
@@ -46,6 +74,71 @@ impl ResponseAccountState {
     }
 }
 
+/// A message from the server to a subscriber, including the id of the event
+/// it originates from, if any.
+#[derive(Clone, Debug, Eq, PartialEq, Message)]
+#[rtype(result = "()")]
+pub struct AccountStateUpdate {
+    pub event_id: Option<String>,
+    pub response: JsonResult<ResponseAccountState>,
+}
+
+/// Replaces the pending challenge of a field. The new challenge is sent to
+/// the subscribers of the identity via a regular state notification.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
+#[rtype(result = "JsonResult<bool>")]
+pub struct RegenerateChallenge {
+    pub context: IdentityContext,
+    pub field: IdentityFieldValue,
+    // The hex encoded signature of `RegenerateChallenge::message`, created
+    // with the account key.
+    pub signature: String,
+}
+
+impl RegenerateChallenge {
+    /// The message that must be signed with the account key. Binds the
+    /// request to the current challenge of the field, so it cannot be
+    /// replayed.
+    pub fn message(context: &IdentityContext, challenge: &str) -> String {
+        format!(
+            "polkadot-registrar:regenerate:{}:{}:{}",
+            context.chain.as_str(),
+            context.address.as_str(),
+            challenge
+        )
+    }
+}
+
+/// Fetches the current state of an identity, without subscribing to it.
+#[derive(Clone, Debug, Eq, PartialEq, Message)]
+#[rtype(result = "crate::Result<Option<IdentityRecord>>")]
+pub struct FetchIdentity {
+    pub context: IdentityContext,
+}
+
+/// Fetches the past events of an identity. Returns `None` if there is no
+/// judgement request.
+#[derive(Clone, Debug, Eq, PartialEq, Message)]
+#[rtype(result = "crate::Result<Option<EventHistory>>")]
+pub struct FetchEventHistory {
+    pub context: IdentityContext,
+    pub query: EventHistoryQuery,
+}
+
+/// Aggregated registrar statistics, see `Database::fetch_stats`.
+#[derive(Clone, Debug, Eq, PartialEq, Message)]
+#[rtype(result = "crate::Result<Vec<StatsBucket>>")]
+pub struct FetchStats {
+    pub query: StatsQuery,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdentityRecord {
+    pub state: JudgementStateBlanked,
+    // Id and timestamp of the latest event of the identity, if any.
+    pub last_event: Option<(String, Timestamp)>,
+}
+
 impl From<NotifyAccountState> for ResponseAccountState {
     fn from(val: NotifyAccountState) -> Self {
         ResponseAccountState {
@@ -75,6 +168,15 @@ impl LookupServer {
     }
 }
 
+// Updates the metric of active subscriptions.
+fn record_subscribers(sessions: &HashMap<IdentityContext, Vec<Subscriber>>) {
+    let count = sessions
+        .values()
+        .map(|subscribers| subscribers.len())
+        .sum::<usize>();
+    metrics().account_state_subscribers.set(count as i64);
+}
+
 impl SystemService for LookupServer {}
 impl Supervised for LookupServer {}
 
@@ -87,7 +189,7 @@ impl Actor for LookupServer {
 }
 
 impl Handler<SubscribeAccountState> for LookupServer {
-    type Result = ResponseActFuture<Self, ()>;
+    type Result = ResponseActFuture<Self, crate::Result<bool>>;
 
     fn handle(&mut self, msg: SubscribeAccountState, _ctx: &mut Self::Context) -> Self::Result {
         let db = self.db.clone();
@@ -97,44 +199,219 @@ impl Handler<SubscribeAccountState> for LookupServer {
             async move {
                 let (id, subscriber) = (msg.id_context, msg.subscriber);
 
-                let state = if let Ok(state) = db
-                    .fetch_judgement_state(&id)
-                    .await
-                    .map_err(|err| error!("Failed to fetch judgement state: {:?}", err))
-                {
-                    state
-                } else {
-                    return;
+                let state = match db.fetch_judgement_state(&id).await? {
+                    Some(state) => state,
+                    None => return Ok(false),
                 };
 
-                if let Some(state) = state {
-                    if subscriber
-                        .try_send(JsonResult::Ok(ResponseAccountState::with_no_notifications(
+                let mut updates = vec![];
+
+                // Replay the missed events. Each one is sent alongside the
+                // current state, just like regular notifications.
+                if let Some(last_event_id) = msg.last_event_id {
+                    match db.fetch_events_after(&id, &last_event_id).await {
+                        Ok(events) => {
+                            for (event_id, event) in events {
+                                updates.push(AccountStateUpdate {
+                                    event_id: Some(event_id),
+                                    response: JsonResult::Ok(ResponseAccountState {
+                                        state: state.clone().into(),
+                                        notifications: vec![event],
+                                    }),
+                                });
+                            }
+                        }
+                        Err(err) => debug!("Cannot replay events: {:?}", err),
+                    }
+                }
+
+                if updates.is_empty() {
+                    let event_id = db
+                        .fetch_latest_event(&id)
+                        .await?
+                        .map(|(event_id, _)| event_id);
+
+                    updates.push(AccountStateUpdate {
+                        event_id,
+                        response: JsonResult::Ok(ResponseAccountState::with_no_notifications(
                             state,
-                        )))
-                        .is_ok()
-                    {
-                        sessions
-                            .write()
-                            .await
-                            .entry(id)
-                            .and_modify(|subscribers| {
-                                subscribers.push(subscriber.clone());
-                            })
-                            .or_insert_with(|| vec![subscriber]);
+                        )),
+                    });
+                }
+
+                if !updates
+                    .into_iter()
+                    .all(|update| subscriber.try_send(update).is_ok())
+                {
+                    return Err(anyhow!("Subscriber of {:?} is unavailable", id));
+                }
+
+                // Subscribing twice does not result in duplicate notifications.
+                let mut sessions = sessions.write().await;
+                let subscribers = sessions.entry(id).or_default();
+                if !subscribers.contains(&subscriber) {
+                    subscribers.push(subscriber);
+                }
+                record_subscribers(&sessions);
+
+                Ok(true)
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<UnsubscribeAccountState> for LookupServer {
+    type Result = ResponseActFuture<Self, ()>;
+
+    fn handle(&mut self, msg: UnsubscribeAccountState, _ctx: &mut Self::Context) -> Self::Result {
+        let sessions = Arc::clone(&self.sessions);
+
+        Box::pin(
+            async move {
+                let mut sessions = sessions.write().await;
+
+                for context in &msg.id_contexts {
+                    if let Some(subscribers) = sessions.get_mut(context) {
+                        subscribers.retain(|subscriber| subscriber != &msg.subscriber);
+
+                        if subscribers.is_empty() {
+                            sessions.remove(context);
+                        }
                     }
-                } else {
-                    subscriber.do_send(JsonResult::Err(
-                        "There is no judgement request from that account for this registrar"
-                            .to_string(),
-                    ));
                 }
+
+                record_subscribers(&sessions);
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<RegenerateChallenge> for LookupServer {
+    type Result = ResponseActFuture<Self, JsonResult<bool>>;
+
+    fn handle(&mut self, msg: RegenerateChallenge, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(
+            async move {
+                let state = match db.fetch_judgement_state(&msg.context).await {
+                    Ok(Some(state)) => state,
+                    Ok(None) => return JsonResult::Err(NO_JUDGEMENT_REQUEST.to_string()),
+                    Err(err) => {
+                        error!("Failed to fetch judgement state: {:?}", err);
+                        return JsonResult::Err("Backend error, contact admin".to_string());
+                    }
+                };
+
+                let challenge = state
+                    .fields
+                    .iter()
+                    .find(|field| field.value == msg.field)
+                    .and_then(|field| match &field.challenge {
+                        ChallengeType::ExpectedMessage { expected, .. } => {
+                            Some(expected.value.clone())
+                        }
+                        _ => None,
+                    });
+
+                let challenge = match challenge {
+                    Some(challenge) => challenge,
+                    None => {
+                        return JsonResult::Err(
+                            "The challenge of this field cannot be regenerated".to_string(),
+                        )
+                    }
+                };
+
+                // Only the owner of the account key can regenerate challenges.
+                if let Err(err) = verify_account_signature(
+                    &msg.context,
+                    &RegenerateChallenge::message(&msg.context, &challenge),
+                    &msg.signature,
+                ) {
+                    return JsonResult::Err(err.to_string());
+                }
+
+                match db
+                    .regenerate_challenge(&msg.context, &msg.field, &challenge)
+                    .await
+                {
+                    Ok(true) => JsonResult::Ok(true),
+                    Ok(false) => JsonResult::Err(
+                        "The challenge of this field cannot be regenerated (yet)".to_string(),
+                    ),
+                    Err(err) => {
+                        error!("Failed to regenerate challenge: {:?}", err);
+                        JsonResult::Err("Backend error, contact admin".to_string())
+                    }
+                }
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<FetchIdentity> for LookupServer {
+    type Result = ResponseActFuture<Self, crate::Result<Option<IdentityRecord>>>;
+
+    fn handle(&mut self, msg: FetchIdentity, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(
+            async move {
+                let state = match db.fetch_judgement_state(&msg.context).await? {
+                    Some(state) => state,
+                    None => return Ok(None),
+                };
+
+                let last_event = db
+                    .fetch_latest_event(&msg.context)
+                    .await?
+                    .map(|(id, event)| (id, event.timestamp));
+
+                Ok(Some(IdentityRecord {
+                    state: state.into(),
+                    last_event,
+                }))
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+impl Handler<FetchEventHistory> for LookupServer {
+    type Result = ResponseActFuture<Self, crate::Result<Option<EventHistory>>>;
+
+    fn handle(&mut self, msg: FetchEventHistory, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(
+            async move {
+                if db.fetch_judgement_state(&msg.context).await?.is_none() {
+                    return Ok(None);
+                }
+
+                db.fetch_event_history(&msg.context, &msg.query)
+                    .await
+                    .map(Some)
             }
             .into_actor(self),
         )
     }
 }
 
+impl Handler<FetchStats> for LookupServer {
+    type Result = ResponseActFuture<Self, crate::Result<Vec<StatsBucket>>>;
+
+    fn handle(&mut self, msg: FetchStats, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+
+        Box::pin(async move { db.fetch_stats(&msg.query).await }.into_actor(self))
+    }
+}
+
 impl Handler<NotifyAccountState> for LookupServer {
     type Result = ResponseActFuture<Self, ()>;
 
@@ -143,38 +420,278 @@ impl Handler<NotifyAccountState> for LookupServer {
 
         Box::pin(
             async move {
-                // Move all subscribers into a temporary storage. Subscribers who
-                // still have an active session open will be added back later.
-                let mut to_reinsert = vec![];
-
-                if let Some(subscribers) = sessions.read().await.get(&msg.state.context) {
-                    // Notify each subscriber.
-                    for subscriber in subscribers {
-                        if subscriber
-                            .try_send(JsonResult::Ok(msg.clone().into()))
+                let mut sessions = sessions.write().await;
+
+                if let Some(subscribers) = sessions.get_mut(&msg.state.context) {
+                    // Notify each subscriber. Sessions unsubscribe when
+                    // stopped, but unavailable subscribers are dropped
+                    // regardless.
+                    subscribers.retain(|subscriber| {
+                        subscriber
+                            .try_send(AccountStateUpdate {
+                                event_id: msg.event_id.clone(),
+                                response: JsonResult::Ok(msg.clone().into()),
+                            })
                             .is_ok()
-                        {
-                            to_reinsert.push(subscriber.clone());
-                        }
+                    });
+
+                    if subscribers.is_empty() {
+                        sessions.remove(&msg.state.context);
                     }
                 }
 
-                // Reinsert active subscribers back into storage.
-                sessions
-                    .write()
-                    .await
-                    .insert(msg.state.context, to_reinsert);
+                record_subscribers(&sessions);
             }
             .into_actor(self),
         )
     }
 }
 
+/// Version of the websocket message envelope.
+pub const WS_PROTOCOL_VERSION: u32 = 1;
+
+/// A request of the versioned websocket protocol. The id, if any, is echoed in
+/// the response.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WsRequest {
+    pub version: u32,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub op: WsOperation,
+}
+
+// Used to tell versioned requests apart from legacy messages, even if the
+// operation itself is invalid.
+#[derive(Deserialize)]
+struct WsRequestHeader {
+    version: u32,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "op")]
+pub enum WsOperation {
+    Subscribe {
+        context: IdentityContext,
+    },
+    Unsubscribe {
+        context: IdentityContext,
+    },
+    Ping,
+    List,
+    History {
+        context: IdentityContext,
+        #[serde(flatten)]
+        query: EventHistoryQuery,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WsReply {
+    Subscribed(IdentityContext),
+    Unsubscribed(IdentityContext),
+    Pong,
+    Subscriptions(Vec<IdentityContext>),
+    History(EventHistory),
+}
+
+/// A message of the versioned websocket protocol, sent by the server.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum WsEnvelope {
+    Response {
+        version: u32,
+        id: Option<String>,
+        result: JsonResult<WsReply>,
+    },
+    Update {
+        version: u32,
+        event_id: Option<String>,
+        message: ResponseAccountState,
+    },
+}
+
+// The message format in which the updates of an identity are sent.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum WsProtocol {
+    // The raw `IdentityContext` subscription.
+    Legacy,
+    Versioned,
+}
+
 #[derive(Default)]
-pub struct WsAccountStatusSession;
+pub struct WsAccountStatusSession {
+    // Challenges can only be regenerated for identities this session is
+    // subscribed to, in addition to the signature of the account key.
+    subscribed: Vec<(IdentityContext, WsProtocol)>,
+}
+
+impl WsAccountStatusSession {
+    fn protocol(&self, context: &IdentityContext) -> Option<WsProtocol> {
+        self.subscribed
+            .iter()
+            .find(|(subscribed, _)| subscribed == context)
+            .map(|(_, protocol)| *protocol)
+    }
+    fn send<T: Serialize>(&self, msg: &T, ctx: &mut <Self as Actor>::Context) {
+        match serde_json::to_string(msg) {
+            Ok(m) => ctx.text(m),
+            Err(err) => error!("Failed to serialize WS session message response: {:?}", err),
+        }
+    }
+    fn reply(
+        &self,
+        id: Option<String>,
+        result: JsonResult<WsReply>,
+        ctx: &mut <Self as Actor>::Context,
+    ) {
+        self.send(
+            &WsEnvelope::Response {
+                version: WS_PROTOCOL_VERSION,
+                id,
+                result,
+            },
+            ctx,
+        );
+    }
+    fn handle_request(&mut self, req: WsRequest, ctx: &mut <Self as Actor>::Context) {
+        match req.op {
+            WsOperation::Subscribe { context } => {
+                self.subscribe(context, WsProtocol::Versioned, req.id, ctx)
+            }
+            WsOperation::Unsubscribe { context } => {
+                let result = if self.protocol(&context).is_some() {
+                    self.subscribed
+                        .retain(|(subscribed, _)| subscribed != &context);
+
+                    LookupServer::from_registry().do_send(UnsubscribeAccountState {
+                        subscriber: ctx.address().recipient(),
+                        id_contexts: vec![context.clone()],
+                    });
+
+                    JsonResult::Ok(WsReply::Unsubscribed(context))
+                } else {
+                    JsonResult::Err("Not subscribed to this identity".to_string())
+                };
+
+                self.reply(req.id, result, ctx);
+            }
+            WsOperation::Ping => self.reply(req.id, JsonResult::Ok(WsReply::Pong), ctx),
+            WsOperation::List => {
+                let contexts = self
+                    .subscribed
+                    .iter()
+                    .map(|(context, _)| context.clone())
+                    .collect();
+
+                self.reply(
+                    req.id,
+                    JsonResult::Ok(WsReply::Subscriptions(contexts)),
+                    ctx,
+                );
+            }
+            WsOperation::History { context, query } => {
+                if let Err(err) = query.validate() {
+                    self.reply(req.id, JsonResult::Err(err.to_string()), ctx);
+                    return;
+                }
+
+                let id = req.id;
+                LookupServer::from_registry()
+                    .send(FetchEventHistory { context, query })
+                    .into_actor(self)
+                    .then(move |res, act, ctx| {
+                        let result = match res {
+                            Ok(Ok(Some(history))) => JsonResult::Ok(WsReply::History(history)),
+                            Ok(Ok(None)) => JsonResult::Err(NO_JUDGEMENT_REQUEST.to_string()),
+                            Ok(Err(err)) => {
+                                error!("Failed to fetch event history: {:?}", err);
+                                JsonResult::Err("Backend error, contact admin".to_string())
+                            }
+                            Err(err) => {
+                                error!("Failed to fetch event history: {:?}", err);
+                                JsonResult::Err("Backend error, contact admin".to_string())
+                            }
+                        };
+
+                        act.reply(id, result, ctx);
+                        fut::ready(())
+                    })
+                    .wait(ctx);
+            }
+        }
+    }
+    fn subscribe(
+        &mut self,
+        context: IdentityContext,
+        protocol: WsProtocol,
+        id: Option<String>,
+        ctx: &mut <Self as Actor>::Context,
+    ) {
+        // The current state is sent as an update once the subscription is
+        // confirmed.
+        LookupServer::from_registry()
+            .send(SubscribeAccountState {
+                subscriber: ctx.address().recipient(),
+                id_context: context.clone(),
+                last_event_id: None,
+            })
+            .into_actor(self)
+            .then(move |res, act, ctx| {
+                let result = match res {
+                    Ok(Ok(true)) => {
+                        act.subscribed
+                            .retain(|(subscribed, _)| subscribed != &context);
+                        act.subscribed.push((context.clone(), protocol));
+
+                        JsonResult::Ok(WsReply::Subscribed(context))
+                    }
+                    Ok(Ok(false)) => JsonResult::Err(NO_JUDGEMENT_REQUEST.to_string()),
+                    Ok(Err(err)) => {
+                        error!("Failed to subscribe to judgement state: {:?}", err);
+                        JsonResult::Err("Backend error, contact admin".to_string())
+                    }
+                    Err(err) => {
+                        error!("Failed to subscribe to judgement state: {:?}", err);
+                        JsonResult::Err("Backend error, contact admin".to_string())
+                    }
+                };
+
+                match (protocol, result) {
+                    (WsProtocol::Versioned, result) => act.reply(id, result, ctx),
+                    // Only errors are reported.
+                    (WsProtocol::Legacy, JsonResult::Err(err)) => {
+                        act.send(&JsonResult::<()>::Err(err), ctx)
+                    }
+                    (WsProtocol::Legacy, JsonResult::Ok(_)) => {}
+                }
+
+                fut::ready(())
+            })
+            .wait(ctx);
+    }
+}
 
 impl Actor for WsAccountStatusSession {
     type Context = ws::WebsocketContext<Self>;
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        if self.subscribed.is_empty() {
+            return;
+        }
+
+        LookupServer::from_registry().do_send(UnsubscribeAccountState {
+            subscriber: ctx.address().recipient(),
+            id_contexts: self
+                .subscribed
+                .drain(..)
+                .map(|(context, _)| context)
+                .collect(),
+        });
+    }
 }
 
 // Handle messages from the subscriber.
@@ -194,26 +711,59 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsAccountStatusSe
                     return;
                 }
 
-                if let Ok(context) = serde_json::from_slice::<IdentityContext>(msg.as_bytes()) {
-                    // Subscribe the the specified identity context.
+                if let Ok(header) = serde_json::from_slice::<WsRequestHeader>(msg.as_bytes()) {
+                    if header.version != WS_PROTOCOL_VERSION {
+                        self.reply(
+                            header.id,
+                            JsonResult::Err(format!(
+                                "Unsupported protocol version, expected {}",
+                                WS_PROTOCOL_VERSION
+                            )),
+                            ctx,
+                        );
+                    } else if let Ok(req) = serde_json::from_slice::<WsRequest>(msg.as_bytes()) {
+                        self.handle_request(req, ctx);
+                    } else {
+                        self.reply(
+                            header.id,
+                            JsonResult::Err("Invalid request".to_string()),
+                            ctx,
+                        );
+                    }
+                } else if let Ok(req) =
+                    serde_json::from_slice::<RegenerateChallenge>(msg.as_bytes())
+                {
+                    if self.protocol(&req.context).is_none() {
+                        ctx.address().do_send(JsonResult::<()>::Err(
+                            "Not subscribed to this identity".to_string(),
+                        ));
+                        return;
+                    }
+
+                    // On success, the new challenge is sent as a state
+                    // notification.
                     LookupServer::from_registry()
-                        .send(SubscribeAccountState {
-                            subscriber: ctx.address().recipient(),
-                            id_context: context,
-                        })
+                        .send(req)
                         .into_actor(self)
-                        .then(|_, _, _| fut::ready(()))
+                        .then(|res, _, ctx| {
+                            if let Ok(JsonResult::Err(err)) = res {
+                                ctx.address().do_send(JsonResult::<()>::Err(err));
+                            }
+
+                            fut::ready(())
+                        })
                         .wait(ctx);
+                } else if let Ok(context) =
+                    serde_json::from_slice::<IdentityContext>(msg.as_bytes())
+                {
+                    // Subscribe the the specified identity context.
+                    self.subscribe(context, WsProtocol::Legacy, None, ctx);
                 } else {
                     // Invalid message type, inform caller.
-                    match serde_json::to_string(&JsonResult::<()>::Err(
-                        "Invalid message type".to_string(),
-                    )) {
-                        Ok(m) => ctx.text(m),
-                        Err(err) => {
-                            error!("Failed to serialize WS session message response: {:?}", err)
-                        }
-                    }
+                    self.send(
+                        &JsonResult::<()>::Err("Invalid message type".to_string()),
+                        ctx,
+                    );
                 }
             }
             ws::Message::Ping(b) => {
@@ -232,9 +782,229 @@ impl<T: Serialize> Handler<JsonResult<T>> for WsAccountStatusSession {
     type Result = ();
 
     fn handle(&mut self, msg: JsonResult<T>, ctx: &mut Self::Context) -> Self::Result {
-        match serde_json::to_string(&msg) {
-            Ok(m) => ctx.text(m),
-            Err(err) => error!("Failed to serialize WS session message response: {:?}", err),
+        self.send(&msg, ctx);
+    }
+}
+
+impl Handler<AccountStateUpdate> for WsAccountStatusSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: AccountStateUpdate, ctx: &mut Self::Context) -> Self::Result {
+        let state = match msg.response {
+            JsonResult::Ok(state) => state,
+            JsonResult::Err(err) => return self.send(&JsonResult::<()>::Err(err), ctx),
+        };
+
+        match self.protocol(&state.state.context) {
+            Some(WsProtocol::Legacy) => self.send(&JsonResult::Ok(state), ctx),
+            Some(WsProtocol::Versioned) => self.send(
+                &WsEnvelope::Update {
+                    version: WS_PROTOCOL_VERSION,
+                    event_id: msg.event_id,
+                    message: state,
+                },
+                ctx,
+            ),
+            // Unsubscribed in the meantime.
+            None => {}
         }
     }
 }
+
+/// Streams the state of a single identity as server-sent events, as an
+/// alternative to `WsAccountStatusSession`.
+pub struct SseAccountStatusSession {
+    context: IdentityContext,
+    last_event_id: Option<String>,
+    sender: UnboundedSender<Bytes>,
+}
+
+impl SseAccountStatusSession {
+    pub fn new(
+        context: IdentityContext,
+        last_event_id: Option<String>,
+        sender: UnboundedSender<Bytes>,
+    ) -> Self {
+        SseAccountStatusSession {
+            context,
+            last_event_id,
+            sender,
+        }
+    }
+    fn send(&self, frame: String, ctx: &mut Context<Self>) {
+        // The client disconnected.
+        if self.sender.unbounded_send(Bytes::from(frame)).is_err() {
+            ctx.stop();
+        }
+    }
+    fn send_json<T: Serialize>(&self, event_id: Option<&str>, msg: &T, ctx: &mut Context<Self>) {
+        match serde_json::to_string(msg) {
+            Ok(data) => self.send(sse_frame(event_id, &data), ctx),
+            Err(err) => error!("Failed to serialize SSE session message: {:?}", err),
+        }
+    }
+}
+
+impl Actor for SseAccountStatusSession {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        LookupServer::from_registry()
+            .send(SubscribeAccountState {
+                subscriber: ctx.address().recipient(),
+                id_context: self.context.clone(),
+                last_event_id: self.last_event_id.take(),
+            })
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                let err = match res {
+                    Ok(Ok(true)) => return fut::ready(()),
+                    Ok(Ok(false)) => NO_JUDGEMENT_REQUEST.to_string(),
+                    Ok(Err(err)) => {
+                        error!("Failed to subscribe to judgement state: {:?}", err);
+                        "Backend error, contact admin".to_string()
+                    }
+                    Err(err) => {
+                        error!("Failed to subscribe to judgement state: {:?}", err);
+                        "Backend error, contact admin".to_string()
+                    }
+                };
+
+                // Nothing to stream.
+                act.send_json(None, &JsonResult::<()>::Err(err), ctx);
+                ctx.stop();
+
+                fut::ready(())
+            })
+            .wait(ctx);
+
+        ctx.run_interval(SSE_KEEP_ALIVE, |act, ctx| {
+            act.send(": keep-alive\n\n".to_string(), ctx);
+        });
+    }
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        LookupServer::from_registry().do_send(UnsubscribeAccountState {
+            subscriber: ctx.address().recipient(),
+            id_contexts: vec![self.context.clone()],
+        });
+    }
+}
+
+impl Handler<AccountStateUpdate> for SseAccountStatusSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: AccountStateUpdate, ctx: &mut Self::Context) -> Self::Result {
+        self.send_json(msg.event_id.as_deref(), &msg.response, ctx);
+    }
+}
+
+fn sse_frame(event_id: Option<&str>, data: &str) -> String {
+    match event_id {
+        Some(event_id) => format!("id: {}\ndata: {}\n\n", event_id, data),
+        None => format!("data: {}\n\n", data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::ChainName;
+    use serde_json::json;
+
+    #[test]
+    fn ws_envelope() {
+        let alice = IdentityContext::alice();
+
+        let req: WsRequest = serde_json::from_value(json!({
+            "version": 1,
+            "id": "1",
+            "op": "subscribe",
+            "context": {
+                "address": alice.address.as_str(),
+                "chain": "polkadot",
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            req,
+            WsRequest {
+                version: 1,
+                id: Some("1".to_string()),
+                op: WsOperation::Subscribe {
+                    context: alice.clone()
+                },
+            }
+        );
+
+        let req: WsRequest = serde_json::from_value(json!({
+            "version": 1,
+            "op": "ping",
+        }))
+        .unwrap();
+        assert_eq!(req.id, None);
+        assert_eq!(req.op, WsOperation::Ping);
+
+        let req: WsRequest = serde_json::from_value(json!({
+            "version": 1,
+            "op": "history",
+            "context": {
+                "address": alice.address.as_str(),
+                "chain": "polkadot",
+            },
+            "from": 1600000000,
+            "limit": 10,
+        }))
+        .unwrap();
+        assert_eq!(
+            req.op,
+            WsOperation::History {
+                context: alice.clone(),
+                query: EventHistoryQuery {
+                    before: None,
+                    from: Some(1600000000),
+                    to: None,
+                    limit: Some(10),
+                },
+            }
+        );
+
+        // Legacy messages are not mistaken for requests.
+        assert!(serde_json::from_value::<WsRequestHeader>(json!({
+            "address": alice.address.as_str(),
+            "chain": ChainName::Polkadot,
+        }))
+        .is_err());
+
+        let resp = serde_json::to_value(&WsEnvelope::Response {
+            version: WS_PROTOCOL_VERSION,
+            id: Some("1".to_string()),
+            result: JsonResult::Ok(WsReply::Pong),
+        })
+        .unwrap();
+        assert_eq!(
+            resp,
+            json!({
+                "type": "response",
+                "version": 1,
+                "id": "1",
+                "result": {
+                    "type": "ok",
+                    "message": "pong",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn sse_frames() {
+        assert_eq!(
+            sse_frame(Some("6400e0e2a0c3d1b0c8a5f1e2"), r#"{"type":"ok"}"#),
+            "id: 6400e0e2a0c3d1b0c8a5f1e2\ndata: {\"type\":\"ok\"}\n\n"
+        );
+        assert_eq!(
+            sse_frame(None, r#"{"type":"err"}"#),
+            "data: {\"type\":\"err\"}\n\n"
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use self::account_key::{request_account_key_challenge, verify_account_key, AccountKeyVerifier};
+use self::admin::{run_admin_server, run_admin_tls_server, AdminApi};
+use self::identity::{identity_routes, LAST_EVENT_ID};
 use self::judgement_state::WsAccountStatusSession;
 use crate::database::Database;
+use crate::health::monitoring_routes;
 use crate::{NotifierConfig, Result};
 use actix::prelude::*;
 use actix::registry::SystemRegistry;
@@ -7,14 +11,26 @@ use actix_cors::Cors;
 use actix_web::{http, web, App, Error as ActixError, HttpRequest, HttpResponse, HttpServer};
 use actix_web_actors::ws;
 use display_name_check::{check_display_name, DisplayNameChecker};
+use pgp_signature::{verify_pgp_signature, Keyserver, PgpSignatureVerifier};
+use rate_limit::RateLimiter;
 use second_challenge::{verify_second_challenge, SecondChallengeVerifier};
 
+mod account_key;
+mod admin;
 mod display_name_check;
+mod identity;
 mod judgement_state;
+mod pgp_signature;
+mod rate_limit;
 mod second_challenge;
 
 // Reexport
-pub use self::judgement_state::{LookupServer, NotifyAccountState, ResponseAccountState};
+pub use self::judgement_state::{LookupServer, NotifyAccountState};
+#[cfg(test)]
+pub use self::judgement_state::{
+    RegenerateChallenge, ResponseAccountState, WsEnvelope, WsOperation, WsReply, WsRequest,
+    WS_PROTOCOL_VERSION,
+};
 pub use self::second_challenge::VerifyChallenge;
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Message)]
@@ -38,8 +54,41 @@ pub async fn run_rest_api_server(
     // Add configured actor to the registry.
     let actor = LookupServer::new(db.clone()).start();
     SystemRegistry::set(actor.clone());
-    SystemRegistry::set(SecondChallengeVerifier::new(db.clone()).start());
-    SystemRegistry::set(DisplayNameChecker::new(db, config.display_name.clone()).start());
+    SystemRegistry::set(
+        SecondChallengeVerifier::new(
+            db.clone()
+                .with_unscoped_second_challenge(config.allow_unscoped_second_challenge),
+        )
+        .start(),
+    );
+    SystemRegistry::set(
+        PgpSignatureVerifier::new(
+            db.clone(),
+            Keyserver::new(config.pgp.as_ref().and_then(|pgp| pgp.keyserver.clone())),
+        )
+        .start(),
+    );
+    SystemRegistry::set(AccountKeyVerifier::new(db.clone()).start());
+    SystemRegistry::set(
+        AdminApi::new(db.clone(), config.admin_api.clone().unwrap_or_default()).start(),
+    );
+    SystemRegistry::set(DisplayNameChecker::new(db.clone(), config.display_name.clone()).start());
+
+    // The admin API is never served by the public API server.
+    if let Some(admin) = &config.admin_api {
+        if let Some(address) = &admin.address {
+            info!("Starting admin API on {}", address);
+            run_admin_server(address)?;
+        }
+
+        if let Some(tls) = &admin.tls {
+            info!("Starting admin API (mTLS) on {}", tls.address);
+            run_admin_tls_server(tls)?;
+        }
+    }
+
+    // Shared by all workers.
+    let rate_limiter = RateLimiter::new(config.rate_limit.clone().unwrap_or_default());
 
     // Run the WS server.
     let server = HttpServer::new(move || {
@@ -47,9 +96,16 @@ pub async fn run_rest_api_server(
         let mut cors = Cors::default()
             .allowed_methods(vec!["GET", "POST"])
             .allowed_headers(vec![
-                http::header::AUTHORIZATION,
                 http::header::ACCEPT,
                 http::header::CONTENT_TYPE,
+                http::header::IF_NONE_MATCH,
+                http::header::IF_MODIFIED_SINCE,
+                http::header::HeaderName::from_static(LAST_EVENT_ID),
+            ])
+            .expose_headers(vec![
+                http::header::ETAG,
+                http::header::LAST_MODIFIED,
+                http::header::RETRY_AFTER,
             ])
             .max_age(3600);
 
@@ -59,8 +115,11 @@ pub async fn run_rest_api_server(
         }
 
         App::new()
+            .wrap(rate_limiter.clone())
             .wrap(cors)
+            .app_data(web::Data::new(db.clone()))
             .route("/healthcheck", web::get().to(healthcheck))
+            .configure(monitoring_routes)
             .service(web::resource("/api/account_status").to(account_status_server_route))
             .route(
                 "/api/verify_second_challenge",
@@ -70,11 +129,26 @@ pub async fn run_rest_api_server(
                 "/api/check_display_name",
                 web::post().to(check_display_name),
             )
+            .route(
+                "/api/verify_pgp_signature",
+                web::post().to(verify_pgp_signature),
+            )
+            .route(
+                "/api/account_key/challenge",
+                web::post().to(request_account_key_challenge),
+            )
+            .route(
+                "/api/account_key/verify",
+                web::post().to(verify_account_key),
+            )
+            .configure(identity_routes)
     })
     .bind(api_address.as_str())?;
 
     actix::spawn(async move {
-        let _ = server.run().await;
+        if let Err(err) = server.run().await {
+            error!("API server stopped: {:?}", err);
+        }
     });
 
     Ok(actor)
@@ -89,11 +163,16 @@ async fn account_status_server_route(
 
 #[cfg(test)]
 pub mod tests {
+    use super::admin::admin_routes;
     use super::*;
+    use crate::adapters::admin::Role;
     use crate::database::Database;
-    use crate::DisplayNameConfig;
+    use crate::{AdminApiConfig, AdminToken, DisplayNameConfig};
     use actix_test::{start, TestServer};
 
+    pub const ADMIN_TEST_TOKEN: &str = "admin-test-token";
+    pub const ADMIN_VIEWER_TEST_TOKEN: &str = "admin-viewer-test-token";
+
     impl Default for DisplayNameConfig {
         fn default() -> Self {
             DisplayNameConfig {
@@ -112,11 +191,39 @@ pub mod tests {
             // Add configured actor to the registry.
             SystemRegistry::set(t_actor.clone());
             SystemRegistry::set(SecondChallengeVerifier::new(db.clone()).start());
+            SystemRegistry::set(
+                PgpSignatureVerifier::new(db.clone(), Keyserver::new(None)).start(),
+            );
+            SystemRegistry::set(AccountKeyVerifier::new(db.clone()).start());
             SystemRegistry::set(
                 DisplayNameChecker::new(db.clone(), DisplayNameConfig::default()).start(),
             );
+            SystemRegistry::set(
+                AdminApi::new(
+                    db.clone(),
+                    AdminApiConfig {
+                        address: None,
+                        tokens: vec![
+                            AdminToken {
+                                name: "test".to_string(),
+                                token: ADMIN_TEST_TOKEN.to_string(),
+                                role: Role::Superadmin,
+                            },
+                            AdminToken {
+                                name: "viewer".to_string(),
+                                token: ADMIN_VIEWER_TEST_TOKEN.to_string(),
+                                role: Role::Viewer,
+                            },
+                        ],
+                        tls: None,
+                    },
+                )
+                .start(),
+            );
 
             App::new()
+                .app_data(web::Data::new(db.clone()))
+                .configure(monitoring_routes)
                 .service(web::resource("/api/account_status").to(account_status_server_route))
                 .route(
                     "/api/verify_second_challenge",
@@ -126,6 +233,21 @@ pub mod tests {
                     "/api/check_display_name",
                     web::post().to(check_display_name),
                 )
+                .route(
+                    "/api/verify_pgp_signature",
+                    web::post().to(verify_pgp_signature),
+                )
+                .route(
+                    "/api/account_key/challenge",
+                    web::post().to(request_account_key_challenge),
+                )
+                .route(
+                    "/api/account_key/verify",
+                    web::post().to(verify_account_key),
+                )
+                .configure(identity_routes)
+                // Served by separate listeners outside of tests.
+                .configure(admin_routes)
         });
 
         (server, actor)
@@ -0,0 +1,510 @@
+use super::JsonResult;
+use crate::database::Database;
+use crate::primitives::{
+    normalize_pgp_fingerprint, ExternalMessage, ExternalMessageType, Timestamp,
+};
+use crate::Result;
+use actix::prelude::*;
+use actix_web::{web, HttpResponse};
+use pgp::packet::{Signature, SignatureType};
+use pgp::types::KeyTrait;
+use pgp::{Deserializable, SignedPublicKey, SignedPublicSubKey, StandaloneSignature};
+use reqwest::Client;
+
+const DEFAULT_KEYSERVER: &str = "https://keys.openpgp.org";
+
+const BEGIN_SIGNED_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// Fetches public keys from a keyserver via HKP.
+#[derive(Debug, Clone)]
+pub struct Keyserver {
+    client: Client,
+    url: String,
+}
+
+impl Keyserver {
+    pub fn new(url: Option<String>) -> Self {
+        Keyserver {
+            client: Client::new(),
+            url: url
+                .unwrap_or_else(|| DEFAULT_KEYSERVER.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+    async fn fetch_key(&self, fingerprint: &str) -> Result<String> {
+        let key = self
+            .client
+            .get(format!("{}/pks/lookup", self.url))
+            .query(&[
+                ("op", "get"),
+                ("options", "mr"),
+                ("search", &format!("0x{}", fingerprint)),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(key)
+    }
+}
+
+pub struct PgpSignatureVerifier {
+    db: Database,
+    keyserver: Keyserver,
+}
+
+impl Default for PgpSignatureVerifier {
+    fn default() -> Self {
+        panic!("PgpSignatureVerifier is not initialized");
+    }
+}
+
+impl PgpSignatureVerifier {
+    pub fn new(db: Database, keyserver: Keyserver) -> Self {
+        PgpSignatureVerifier { db, keyserver }
+    }
+}
+
+impl SystemService for PgpSignatureVerifier {}
+impl Supervised for PgpSignatureVerifier {}
+
+impl Actor for PgpSignatureVerifier {
+    type Context = Context<Self>;
+}
+
+impl Handler<VerifyPgpSignature> for PgpSignatureVerifier {
+    type Result = ResponseActFuture<Self, JsonResult<bool>>;
+
+    fn handle(&mut self, msg: VerifyPgpSignature, _ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        let keyserver = self.keyserver.clone();
+
+        Box::pin(
+            async move {
+                debug!("Received PGP signature for {}", msg.fingerprint);
+                let fingerprint = normalize_pgp_fingerprint(&msg.fingerprint);
+
+                let armored_key = match msg.public_key {
+                    Some(key) => key,
+                    None => match keyserver.fetch_key(&fingerprint).await {
+                        Ok(key) => key,
+                        Err(err) => {
+                            debug!("Failed to fetch PGP key {}: {:?}", fingerprint, err);
+                            return JsonResult::Err(
+                                "Failed to fetch the public key from the keyserver".to_string(),
+                            );
+                        }
+                    },
+                };
+
+                let text = match verify_clearsigned(&armored_key, &fingerprint, &msg.signed_message)
+                {
+                    Ok(text) => text,
+                    Err(err) => {
+                        debug!("Invalid PGP signature for {}: {:?}", fingerprint, err);
+                        return JsonResult::Err("Invalid PGP signature".to_string());
+                    }
+                };
+
+                // The signed text is treated like any other incoming message.
+                db.verify_message(&ExternalMessage {
+                    origin: ExternalMessageType::PGPFingerprint(fingerprint),
+                    id: 0u32.into(),
+                    timestamp: Timestamp::now(),
+                    values: vec![text.into()],
+                })
+                .await
+                .map(|_| JsonResult::Ok(true))
+                .unwrap_or_else(|_| JsonResult::Err("Backend error, contact admin".to_string()))
+            }
+            .into_actor(self),
+        )
+    }
+}
+
+/// A message clearsigned with the key of the specified fingerprint. The public
+/// key is fetched from the keyserver if not provided.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
+#[rtype(result = "JsonResult<bool>")]
+pub struct VerifyPgpSignature {
+    pub fingerprint: String,
+    pub signed_message: String,
+    pub public_key: Option<String>,
+}
+
+pub async fn verify_pgp_signature(req: web::Json<VerifyPgpSignature>) -> HttpResponse {
+    HttpResponse::Ok().json(
+        PgpSignatureVerifier::from_registry()
+            .send(req.into_inner())
+            .await
+            .unwrap(),
+    )
+}
+
+/// Verifies the clearsigned message with the given key (or one of its signing
+/// subkeys) and returns the signed text. Revoked and expired keys are
+/// rejected, as well as keys which are not flagged for signing.
+fn verify_clearsigned(armored_key: &str, fingerprint: &str, signed: &str) -> Result<String> {
+    let (key, _) = SignedPublicKey::from_string(armored_key)?;
+    key.verify()?;
+
+    if hex::encode(key.fingerprint()) != fingerprint {
+        return Err(anyhow!("public key does not match fingerprint"));
+    }
+
+    if !key.details.revocation_signatures.is_empty() {
+        return Err(anyhow!("public key is revoked"));
+    }
+
+    let now = Timestamp::now().raw() as i64;
+
+    // The latest self-signature carries the current flags and expiry of the
+    // primary key.
+    let self_sig = latest(
+        key.details
+            .direct_signatures
+            .iter()
+            .chain(key.details.users.iter().flat_map(|user| &user.signatures)),
+    );
+
+    if let Some(sig) = self_sig {
+        if is_expired(key.primary_key.created_at().timestamp(), sig, now) {
+            return Err(anyhow!("public key is expired"));
+        }
+    }
+
+    // Keys without flags predate the key flags subpacket and may sign.
+    let primary_can_sign = self_sig
+        .map(|sig| sig.key_flags() == Default::default() || sig.key_flags().sign())
+        .unwrap_or(true);
+
+    let subkeys: Vec<&SignedPublicSubKey> = key
+        .public_subkeys
+        .iter()
+        .filter(|subkey| subkey.verify(&key.primary_key).is_ok())
+        .filter(|subkey| {
+            !subkey
+                .signatures
+                .iter()
+                .any(|sig| sig.typ() == SignatureType::SubkeyRevocation)
+        })
+        .filter(|subkey| {
+            latest(
+                subkey
+                    .signatures
+                    .iter()
+                    .filter(|sig| sig.typ() == SignatureType::SubkeyBinding),
+            )
+            .map(|sig| {
+                sig.key_flags().sign() && !is_expired(subkey.key.created_at().timestamp(), sig, now)
+            })
+            .unwrap_or(false)
+        })
+        .collect();
+
+    let (text, armored_sig) = parse_cleartext(signed)?;
+    let (sig, _) = StandaloneSignature::from_string(&armored_sig)?;
+
+    if sig.signature.typ() != SignatureType::Text {
+        return Err(anyhow!("not a text signature: {:?}", sig.signature.typ()));
+    }
+
+    // Line endings are normalized to `\r\n` and trailing whitespace is
+    // ignored (RFC 4880, 7.1).
+    let canonical = text
+        .iter()
+        .map(|line| line.trim_end_matches([' ', '\t']))
+        .collect::<Vec<&str>>()
+        .join("\r\n");
+
+    let is_valid = (primary_can_sign && sig.verify(&key, canonical.as_bytes()).is_ok())
+        || subkeys
+            .iter()
+            .any(|subkey| sig.verify(*subkey, canonical.as_bytes()).is_ok());
+
+    if !is_valid {
+        return Err(anyhow!("signature verification failed"));
+    }
+
+    Ok(text.join("\n"))
+}
+
+/// The most recently created signature.
+fn latest<'a>(signatures: impl Iterator<Item = &'a Signature>) -> Option<&'a Signature> {
+    signatures.max_by_key(|sig| sig.created().map(|created| created.timestamp()))
+}
+
+/// The key expiration time is the number of seconds after the creation of the
+/// key, but the parser returns it as a date, so its timestamp is taken as
+/// the offset. Zero means the key does not expire.
+fn is_expired(created_at: i64, sig: &Signature, now: i64) -> bool {
+    sig.key_expiration_time()
+        .map(|expiration| expiration.timestamp())
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| created_at + seconds <= now)
+        .unwrap_or(false)
+}
+
+/// Splits the cleartext signature framework into the (dash-unescaped) text
+/// lines and the armored signature.
+fn parse_cleartext(signed: &str) -> Result<(Vec<&str>, String)> {
+    let mut lines = signed.lines().skip_while(|line| line.trim().is_empty());
+
+    if lines.next().map(|line| line.trim_end()) != Some(BEGIN_SIGNED_MESSAGE) {
+        return Err(anyhow!("missing cleartext header"));
+    }
+
+    // Skip the armor headers, such as `Hash: SHA256`.
+    for line in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut text = vec![];
+    let mut armored_sig = None;
+    for line in lines.by_ref() {
+        if line.trim_end() == BEGIN_SIGNATURE {
+            armored_sig = Some(line.to_string());
+            break;
+        }
+
+        text.push(line.strip_prefix("- ").unwrap_or(line));
+    }
+
+    let mut armored_sig = armored_sig.ok_or_else(|| anyhow!("missing signature"))?;
+    for line in lines {
+        armored_sig.push('\n');
+        armored_sig.push_str(line);
+    }
+
+    Ok((text, armored_sig))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_test::start;
+    use actix_web::{web, App, HttpRequest};
+
+    const ALICE_FINGERPRINT: &str = "880cfa6c3386da66090cb163ae1e7c28d1805b29";
+    const ALICE_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatVLxhYJKwYBBAHaRw8BAQdAlxjvshHvFu296dXU0NJ1U1StfXbfnL9/Aym2
+dJ7wJQm0GUFsaWNlIDxhbGljZUBleGFtcGxlLmNvbT6IkAQTFggAOBYhBIgM+mwz
+htpmCQyxY64efCjRgFspBQJq1UvGAhsDBQsJCAcCBhUKCQgLAgQWAgMBAh4BAheA
+AAoJEK4efCjRgFspPoUA/3/atJ65oTe9dTGmxSqR0A2XG+wNdyGfY+GVOmT/ZYDJ
+AQCauldUiIimo6monfeq4Bt41nu+bpIsx3tRf64D7oAwAA==
+=hJL7
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    const ALICE_SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+e5ba85f2aec2ed39b16da1ba6e2a2e1f
+-----BEGIN PGP SIGNATURE-----
+
+iIgEARYIADAWIQSIDPpsM4baZgkMsWOuHnwo0YBbKQUCatVLxhIcYWxpY2VAZXhh
+bXBsZS5jb20ACgkQrh58KNGAWynKaAD/UdR9GCP3fgiGUqp2k3SAVfDcQ/Uoc9DV
+ZWIjYWCfaPQBAOQSsnQYKKBk5yyUxKxWYKRiW/uEnXktUza7CIQUjtcO
+=yFcY
+-----END PGP SIGNATURE-----
+";
+    // Same message, signed by a different key.
+    const EVE_SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+e5ba85f2aec2ed39b16da1ba6e2a2e1f
+-----BEGIN PGP SIGNATURE-----
+
+iIYEARYIAC4WIQTkPJZu5A/2UrvngkLpABRd4GpSVgUCatVLxhAcZXZlQGV4YW1w
+bGUuY29tAAoJEOkAFF3galJWEOEBALTdLq/sJKjjvUBU+1W0xTtLqc5xoXD7bNxs
+10lTSzRSAQD1d6YPBCBGUQjIz1afZ2SJLY+nd/YPKEDw26IDMiwuDw==
+=Ic3G
+-----END PGP SIGNATURE-----
+";
+
+    // Revoked after signing the message.
+    const BOB_FINGERPRINT: &str = "ca2e87f891fcdf0c3f67acc77afdb264416dd5e9";
+    const BOB_REVOKED_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatWIrRYJKwYBBAHaRw8BAQdArokOYAD6Pzr7rH6Lrp6uT0VRRqNjOnTy+HFs
+2InUCHqIeAQgFggAIBYhBMouh/iR/N8MP2esx3r9smRBbdXpBQJq1YitAh0AAAoJ
+EHr9smRBbdXp+84A/j2cAxGJMbhU6ZkYn19oPhM2g/z1oeIoE5cJ2lPn5nADAQC1
+kZQeqIEMrZMDBJ0qK7mYhpoGtd3XAF8PbrFqxcG6CbQVQm9iIDxib2JAZXhhbXBs
+ZS5jb20+iJAEExYIADgWIQTKLof4kfzfDD9nrMd6/bJkQW3V6QUCatWIrQIbAwUL
+CQgHAgYVCgkICwIEFgIDAQIeAQIXgAAKCRB6/bJkQW3V6fzAAQDVqXfJ3i/RUo45
+l2rSZmwX+hPeBAIrrPm+Ntpx38+UtgEA1ce5QDZcGKh6ipsr3J7gPt09z80sKlQb
+7VbrLX11ewE=
+=9gQQ
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    const BOB_SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+e5ba85f2aec2ed39b16da1ba6e2a2e1f
+-----BEGIN PGP SIGNATURE-----
+
+iIYEARYIAC4WIQTKLof4kfzfDD9nrMd6/bJkQW3V6QUCatWIrRAcYm9iQGV4YW1w
+bGUuY29tAAoJEHr9smRBbdXpr0UBAICzEP6k1qWBLcahNDWjCgzAhQMzOtQZfkUG
+AjLp7X3JAP90moBNU6reQNp9grzevLoFXkGYZrtVvJ+2imHXfifdBg==
+=W+yY
+-----END PGP SIGNATURE-----
+";
+    // Expired in 2020, one day after signing the message.
+    const CAROL_FINGERPRINT: &str = "6e0ffd10f9f399bfb06863d9dba74bfd9d68e174";
+    const CAROL_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEXgvhABYJKwYBBAHaRw8BAQdA8qoq/tMJSKlPDQ5+BEgGechoUfNyorDpZNev
+PPgOKg20GUNhcm9sIDxjYXJvbEBleGFtcGxlLmNvbT6IlgQTFggAPhYhBG4P/RD5
+85m/sGhj2dunS/2daOF0BQJeC+EAAhsDBQkAAVGABQsJCAcCBhUKCQgLAgQWAgMB
+Ah4BAheAAAoJENunS/2daOF0LBwBAL91QHhszoxQeZ9ruv5JAFq2TYFMKrlLahsq
+XzwPAGMPAPsG6HRGxyInxUHvz53Pm7qjEScGftRD8XAXRWI5kWobAw==
+=g3Bu
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    const CAROL_SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+e5ba85f2aec2ed39b16da1ba6e2a2e1f
+-----BEGIN PGP SIGNATURE-----
+
+iIgEARYIADAWIQRuD/0Q+fOZv7BoY9nbp0v9nWjhdAUCXgvvEBIcY2Fyb2xAZXhh
+bXBsZS5jb20ACgkQ26dL/Z1o4XTnWwEA8FU1LmDsqdVyHPjchdNllTJJpzDyenxD
+praNnrwnDXABAOyg9cOXumu4CB8ClhFJmqhKhfBitHsGi9936eAdnogL
+=+q92
+-----END PGP SIGNATURE-----
+";
+    // Certification-only primary key with a signing subkey, which is revoked
+    // after signing the message.
+    const DAVE_FINGERPRINT: &str = "aa9deb097af52e5cff2c8a79d61c80ab8659c45e";
+    const DAVE_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatWIthYJKwYBBAHaRw8BAQdAybpnaCm+42IZ75CixzPK3pnEUWC9b3sWEvSq
+5UzN40+0F0RhdmUgPGRhdmVAZXhhbXBsZS5jb20+iJAEExYIADgWIQSqnesJevUu
+XP8sinnWHICrhlnEXgUCatWItgIbAQULCQgHAgYVCgkICwIEFgIDAQIeAQIXgAAK
+CRDWHICrhlnEXvYVAP9vvppTrx5p94auXMgNx9llgri1o0EJckm4wZKQ76ZpawEA
+3z9wBQn9zaka19WdkvBHuk1ghA1DMk2jcLqiwYu4GgW4MwRq1Yi2FgkrBgEEAdpH
+DwEBB0C1ixn0ReQRtvXOND3Y7tJW0dIGYUoEU6CvqabFvz+JxIjvBBgWCAAgFiEE
+qp3rCXr1Llz/LIp51hyAq4ZZxF4FAmrViLYCGwIAgQkQ1hyAq4ZZxF52IAQZFggA
+HRYhBL2AhuqNkTqfXT2CsruPyljZmPilBQJq1Yi2AAoJELuPyljZmPil9M4BAKCu
+5KDhXIzQDHg38816Kwrg+Mv16gLr8eAjqCIQrnqtAQD9OYyzDInWQP8fGIniShdG
+Q3vrVTQ+zOprsqXZ4nExAKzuAQCYKVzjjVaAoDe8GzdctnX+q9XIUWjX1bKtTaLl
+i/3UvAD7BJI7aH0+9RjRtVoUcxlWRJYN7jvEyRl8fy4eT6G4HgE=
+=YEny
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    const DAVE_REVOKED_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatWIthYJKwYBBAHaRw8BAQdAybpnaCm+42IZ75CixzPK3pnEUWC9b3sWEvSq
+5UzN40+0F0RhdmUgPGRhdmVAZXhhbXBsZS5jb20+iJAEExYIADgWIQSqnesJevUu
+XP8sinnWHICrhlnEXgUCatWItgIbAQULCQgHAgYVCgkICwIEFgIDAQIeAQIXgAAK
+CRDWHICrhlnEXvYVAP9vvppTrx5p94auXMgNx9llgri1o0EJckm4wZKQ76ZpawEA
+3z9wBQn9zaka19WdkvBHuk1ghA1DMk2jcLqiwYu4GgW4MwRq1Yi2FgkrBgEEAdpH
+DwEBB0C1ixn0ReQRtvXOND3Y7tJW0dIGYUoEU6CvqabFvz+JxIh4BCgWCAAgFiEE
+qp3rCXr1Llz/LIp51hyAq4ZZxF4FAmrViLYCHQAACgkQ1hyAq4ZZxF5kdgEAuhaQ
+wXoUNGSuc9hnD/l++n4toJ9oIFOlkHOUzC+TfgwBAPCh0ulia1PgD+UfpQzjPVhl
+EHIGp7Gkf4stg9NE6/8AiO8EGBYIACAWIQSqnesJevUuXP8sinnWHICrhlnEXgUC
+atWItgIbAgCBCRDWHICrhlnEXnYgBBkWCAAdFiEEvYCG6o2ROp9dPYKyu4/KWNmY
++KUFAmrViLYACgkQu4/KWNmY+KX0zgEAoK7koOFcjNAMeDfzzXorCuD4y/XqAuvx
+4COoIhCueq0BAP05jLMMidZA/x8YieJKF0ZDe+tVND7M6muypdnicTEArO4BAJgp
+XOONVoCgN7wbN1y2df6r1chRaNfVsq1NouWL/dS8APsEkjtofT71GNG1WhRzGVZE
+lg3uO8TJGXx/Lh5PobgeAQ==
+=RU94
+-----END PGP PUBLIC KEY BLOCK-----
+";
+    const DAVE_SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+e5ba85f2aec2ed39b16da1ba6e2a2e1f
+-----BEGIN PGP SIGNATURE-----
+
+iIcEARYIAC8WIQS9gIbqjZE6n109grK7j8pY2Zj4pQUCatWIthEcZGF2ZUBleGFt
+cGxlLmNvbQAKCRC7j8pY2Zj4pa1fAP9NHWof/+XWr48eX8FLBz43Yfn2E100DpY7
+JpDyxuUvOwEA9Wuh1aYfcGEExgDv6jq9z+7P0hnsLA32UbSM+eVyoQY=
+=kEaS
+-----END PGP SIGNATURE-----
+";
+
+    #[test]
+    fn verify_signed_message() {
+        let text = verify_clearsigned(ALICE_KEY, ALICE_FINGERPRINT, ALICE_SIGNED).unwrap();
+        assert_eq!(text, "e5ba85f2aec2ed39b16da1ba6e2a2e1f");
+
+        // CRLF line endings are accepted.
+        let text = verify_clearsigned(
+            ALICE_KEY,
+            ALICE_FINGERPRINT,
+            &ALICE_SIGNED.replace('\n', "\r\n"),
+        )
+        .unwrap();
+        assert_eq!(text, "e5ba85f2aec2ed39b16da1ba6e2a2e1f");
+    }
+
+    #[test]
+    fn verify_invalid_signed_message() {
+        // Tampered text.
+        let tampered = ALICE_SIGNED.replace("e5ba85f2", "00000000");
+        assert!(verify_clearsigned(ALICE_KEY, ALICE_FINGERPRINT, &tampered).is_err());
+
+        // Signed by a different key.
+        assert!(verify_clearsigned(ALICE_KEY, ALICE_FINGERPRINT, EVE_SIGNED).is_err());
+
+        // Key does not match the fingerprint.
+        assert!(verify_clearsigned(
+            ALICE_KEY,
+            "e43c966ee40ff652bbe78242e900145de06a5256",
+            ALICE_SIGNED
+        )
+        .is_err());
+
+        // Not clearsigned.
+        assert!(verify_clearsigned(ALICE_KEY, ALICE_FINGERPRINT, "e5ba85f2").is_err());
+    }
+
+    #[test]
+    fn verify_signing_subkey() {
+        let text = verify_clearsigned(DAVE_KEY, DAVE_FINGERPRINT, DAVE_SIGNED).unwrap();
+        assert_eq!(text, "e5ba85f2aec2ed39b16da1ba6e2a2e1f");
+    }
+
+    #[test]
+    fn reject_revoked_or_expired_keys() {
+        assert!(verify_clearsigned(BOB_REVOKED_KEY, BOB_FINGERPRINT, BOB_SIGNED).is_err());
+        assert!(verify_clearsigned(CAROL_KEY, CAROL_FINGERPRINT, CAROL_SIGNED).is_err());
+        assert!(verify_clearsigned(DAVE_REVOKED_KEY, DAVE_FINGERPRINT, DAVE_SIGNED).is_err());
+    }
+
+    #[actix::test]
+    async fn fetch_key_from_keyserver() {
+        let server = start(|| {
+            App::new().route(
+                "/pks/lookup",
+                web::get().to(|req: HttpRequest| async move {
+                    if req
+                        .query_string()
+                        .contains(&format!("search=0x{}", ALICE_FINGERPRINT))
+                    {
+                        HttpResponse::Ok().body(ALICE_KEY)
+                    } else {
+                        HttpResponse::NotFound().finish()
+                    }
+                }),
+            )
+        });
+
+        let keyserver = Keyserver::new(Some(server.url("/")));
+
+        let key = keyserver.fetch_key(ALICE_FINGERPRINT).await.unwrap();
+        assert_eq!(key, ALICE_KEY);
+
+        assert!(keyserver
+            .fetch_key("e43c966ee40ff652bbe78242e900145de06a5256")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,555 @@
+use super::JsonResult;
+use crate::primitives::{IdentityContext, IdentityFieldValue};
+use crate::{RateLimit, RateLimitConfig};
+use actix_web::body::EitherBody;
+use actix_web::dev::{
+    forward_ready, Payload, ResourceDef, Service, ServiceRequest, ServiceResponse, Transform,
+};
+use actix_web::error::PayloadError;
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::web::Bytes;
+use actix_web::{Error as ActixError, HttpResponse};
+use futures::future::{ready, LocalBoxFuture, Ready};
+use futures::Stream;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const FORWARDED_FOR: &str = "x-forwarded-for";
+// Interval for removing the buckets of inactive clients.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+enum BucketKey {
+    Ip(IpAddr),
+    Identity(String),
+    // Per-identity bucket of requests which do not specify an identity.
+    Unidentified(IpAddr),
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        TokenBucket {
+            tokens: capacity(limit),
+            updated: now,
+        }
+    }
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * refill_rate(limit)).min(capacity(limit));
+        self.updated = now;
+    }
+    /// Takes a token, or returns the time until the next token is available.
+    fn take(&mut self, limit: &RateLimit, now: Instant) -> Result<(), Duration> {
+        self.refill(limit, now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / refill_rate(limit),
+            ))
+        }
+    }
+}
+
+fn capacity(limit: &RateLimit) -> f64 {
+    limit.burst.unwrap_or(limit.per_minute).max(1) as f64
+}
+
+// Tokens per second.
+fn refill_rate(limit: &RateLimit) -> f64 {
+    limit.per_minute.max(1) as f64 / 60.0
+}
+
+struct Buckets {
+    buckets: HashMap<(String, BucketKey), TokenBucket>,
+    pruned: Instant,
+}
+
+/// Limits the requests to the configured routes with token buckets per
+/// client IP and per identity. Exceeding requests are rejected with `429`.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config: Arc::new(config),
+            buckets: Arc::new(Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                pruned: Instant::now(),
+            })),
+        }
+    }
+    fn check(
+        &self,
+        route: &str,
+        key: BucketKey,
+        limit: &RateLimit,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // Full buckets are identical to new ones.
+        if now.saturating_duration_since(buckets.pruned) > PRUNE_INTERVAL {
+            let config = &self.config;
+            buckets.buckets.retain(|(route, key), bucket| {
+                config
+                    .routes
+                    .get(route)
+                    .and_then(|limits| match key {
+                        BucketKey::Ip(_) => limits.per_ip.as_ref(),
+                        BucketKey::Identity(_) | BucketKey::Unidentified(_) => {
+                            limits.per_identity.as_ref()
+                        }
+                    })
+                    .map(|limit| {
+                        let mut bucket = bucket.clone();
+                        bucket.refill(limit, now);
+                        bucket.tokens < capacity(limit)
+                    })
+                    .unwrap_or(false)
+            });
+            buckets.pruned = now;
+        }
+
+        buckets
+            .buckets
+            .entry((route.to_string(), key))
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .take(limit, now)
+    }
+    /// The address of the client. The `X-Forwarded-For` header is only
+    /// considered if the request was sent by a trusted proxy, in which case
+    /// the last address which is not a trusted proxy is used.
+    fn client_ip(&self, req: &ServiceRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr()?.ip();
+        if !self.config.trusted_proxies.contains(&peer) {
+            return Some(peer);
+        }
+
+        let forwarded: Vec<IpAddr> = req
+            .headers()
+            .get_all(FORWARDED_FOR)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|addr| addr.trim().parse().ok())
+            .collect();
+
+        Some(
+            forwarded
+                .iter()
+                .rev()
+                .find(|addr| !self.config.trusted_proxies.contains(addr))
+                .or_else(|| forwarded.first())
+                .copied()
+                .unwrap_or(peer),
+        )
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimiter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = ActixError;
+    type Transform = RateLimiterMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimiterMiddleware {
+            service: Rc::new(service),
+            limiter: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimiterMiddleware<S> {
+    service: Rc<S>,
+    limiter: RateLimiter,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimiterMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = ActixError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            // Dynamic routes are configured by their pattern, e.g.
+            // `/api/v1/identity/{chain}/{address}`.
+            let route = req
+                .match_pattern()
+                .unwrap_or_else(|| req.path().to_string());
+            let limits = match limiter.config.routes.get(&route) {
+                Some(limits) => limits.clone(),
+                None => return Ok(service.call(req).await?.map_into_left_body()),
+            };
+
+            let now = Instant::now();
+
+            if let Some(limit) = &limits.per_ip {
+                if let Some(ip) = limiter.client_ip(&req) {
+                    if let Err(retry_after) = limiter.check(&route, BucketKey::Ip(ip), limit, now) {
+                        return Ok(too_many_requests(req, retry_after));
+                    }
+                }
+            }
+
+            if let Some(limit) = &limits.per_identity {
+                let identity = match path_identity(&req, &route) {
+                    Some(identity) => Some(identity),
+                    None => {
+                        // The body is read in advance and handed to the route
+                        // again.
+                        let body = req.extract::<Bytes>().await?;
+                        let identity = identity_key(&body);
+                        req.set_payload(into_payload(body));
+
+                        identity
+                    }
+                };
+
+                // Requests without an identity are limited per client IP.
+                let key = match identity {
+                    Some(identity) => Some(BucketKey::Identity(identity)),
+                    None => limiter.client_ip(&req).map(BucketKey::Unidentified),
+                };
+
+                if let Some(key) = key {
+                    if let Err(retry_after) = limiter.check(&route, key, limit, now) {
+                        return Ok(too_many_requests(req, retry_after));
+                    }
+                }
+            }
+
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
+
+/// Identifies the target of the request by the `{chain}` and `{address}`
+/// segments of the route, if any.
+fn path_identity(req: &ServiceRequest, pattern: &str) -> Option<String> {
+    let mut path = req.match_info().clone();
+    if !ResourceDef::new(pattern).capture_match_info(&mut path) {
+        return None;
+    }
+
+    Some(format!("{}:{}", path.get("chain")?, path.get("address")?))
+}
+
+/// Identifies the target of the request by the `context` or, if not
+/// specified, by the `entry` of the JSON body.
+fn identity_key(body: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Target {
+        context: Option<IdentityContext>,
+        entry: Option<IdentityFieldValue>,
+    }
+
+    let target = serde_json::from_slice::<Target>(body).ok()?;
+    match (target.context, target.entry) {
+        (Some(context), _) => Some(format!(
+            "{}:{}",
+            context.chain.as_str(),
+            context.address.as_str()
+        )),
+        (None, Some(entry)) => serde_json::to_string(&entry).ok(),
+        (None, None) => None,
+    }
+}
+
+fn into_payload(body: Bytes) -> Payload {
+    let stream: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> =
+        Box::pin(futures::stream::once(async move { Ok(body) }));
+
+    Payload::from(stream)
+}
+
+fn too_many_requests<B>(
+    req: ServiceRequest,
+    retry_after: Duration,
+) -> ServiceResponse<EitherBody<B>> {
+    // Rounded up to full seconds.
+    let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+
+    req.into_response(
+        HttpResponse::TooManyRequests()
+            .insert_header((RETRY_AFTER, secs.to_string()))
+            .json(JsonResult::<()>::Err(format!(
+                "Too many requests, try again in {} seconds",
+                secs
+            ))),
+    )
+    .map_into_right_body()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RouteRateLimit;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{web, App};
+    use std::net::SocketAddr;
+
+    const PROXY: &str = "10.0.0.1:4000";
+
+    fn limit(per_minute: u32, burst: u32) -> RateLimit {
+        RateLimit {
+            per_minute,
+            burst: Some(burst),
+        }
+    }
+
+    fn limiter() -> RateLimiter {
+        let mut routes = HashMap::new();
+        routes.insert(
+            "/api/check_display_name".to_string(),
+            RouteRateLimit {
+                per_ip: Some(limit(1, 2)),
+                per_identity: None,
+            },
+        );
+        routes.insert(
+            "/api/verify_second_challenge".to_string(),
+            RouteRateLimit {
+                per_ip: None,
+                per_identity: Some(limit(1, 2)),
+            },
+        );
+
+        routes.insert(
+            "/api/v1/identity/{chain}/{address}".to_string(),
+            RouteRateLimit {
+                per_ip: None,
+                per_identity: Some(limit(1, 2)),
+            },
+        );
+
+        RateLimiter::new(RateLimitConfig {
+            trusted_proxies: vec![PROXY.parse::<SocketAddr>().unwrap().ip()],
+            routes,
+        })
+    }
+
+    fn post(path: &str, peer: &str) -> TestRequest {
+        TestRequest::post()
+            .uri(path)
+            .peer_addr(peer.parse().unwrap())
+    }
+
+    #[test]
+    fn token_bucket() {
+        let limit = limit(60, 2);
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(&limit, now);
+
+        assert!(bucket.take(&limit, now).is_ok());
+        assert!(bucket.take(&limit, now).is_ok());
+        assert_eq!(bucket.take(&limit, now), Err(Duration::from_secs(1)));
+
+        // One token per second.
+        let later = now + Duration::from_millis(1_500);
+        assert!(bucket.take(&limit, later).is_ok());
+        assert_eq!(bucket.take(&limit, later), Err(Duration::from_millis(500)));
+
+        // Never exceeds the burst.
+        let later = later + Duration::from_secs(60);
+        assert!(bucket.take(&limit, later).is_ok());
+        assert!(bucket.take(&limit, later).is_ok());
+        assert!(bucket.take(&limit, later).is_err());
+    }
+
+    #[test]
+    fn identity_keys() {
+        assert_eq!(
+            identity_key(
+                br#"{"context": {"address": "Alice", "chain": "polkadot"}, "challenge": "1234"}"#
+            ),
+            Some("polkadot:Alice".to_string())
+        );
+        assert_eq!(
+            identity_key(br#"{"entry": {"type": "email", "value": "alice@example.com"}, "challenge": "1234"}"#),
+            Some(r#"{"type":"email","value":"alice@example.com"}"#.to_string())
+        );
+        assert_eq!(
+            identity_key(br#"{"check": "Alice", "chain": "polkadot"}"#),
+            None
+        );
+        assert_eq!(identity_key(b"invalid"), None);
+    }
+
+    #[actix::test]
+    async fn limit_per_ip() {
+        let app = init_service(
+            App::new()
+                .wrap(limiter())
+                .route("/api/check_display_name", web::post().to(HttpResponse::Ok))
+                .route("/api/unlimited", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let path = "/api/check_display_name";
+        for _ in 0..2 {
+            let resp = call_service(&app, post(path, "1.1.1.1:1000").to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        let resp = call_service(&app, post(path, "1.1.1.1:1001").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers().get(RETRY_AFTER).unwrap(), "60");
+
+        // Other clients and routes are not affected.
+        let resp = call_service(&app, post(path, "2.2.2.2:1000").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = call_service(&app, post("/api/unlimited", "1.1.1.1:1000").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // The header is ignored if not sent by a trusted proxy.
+        let req = post(path, "1.1.1.1:1000")
+            .insert_header((FORWARDED_FOR, "3.3.3.3"))
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Clients behind the proxy are limited separately.
+        for (forwarded, status) in [
+            ("4.4.4.4", StatusCode::OK),
+            ("5.5.5.5, 4.4.4.4", StatusCode::OK),
+            ("4.4.4.4, 10.0.0.1", StatusCode::TOO_MANY_REQUESTS),
+            ("5.5.5.5", StatusCode::OK),
+        ] {
+            let req = post(path, PROXY)
+                .insert_header((FORWARDED_FOR, forwarded))
+                .to_request();
+            let resp = call_service(&app, req).await;
+            assert_eq!(resp.status(), status, "{}", forwarded);
+        }
+    }
+
+    #[actix::test]
+    async fn limit_per_identity() {
+        let app = init_service(App::new().wrap(limiter()).route(
+            "/api/verify_second_challenge",
+            web::post().to(|body: web::Json<serde_json::Value>| async move {
+                HttpResponse::Ok().json(body.into_inner())
+            }),
+        ))
+        .await;
+
+        let alice = serde_json::json!({
+            "context": { "address": "Alice", "chain": "polkadot" },
+            "entry": { "type": "email", "value": "alice@example.com" },
+            "challenge": "1234",
+        });
+        let bob = serde_json::json!({
+            "entry": { "type": "email", "value": "bob@example.com" },
+            "challenge": "1234",
+        });
+
+        // Limited regardless of the client address.
+        for (peer, status) in [
+            ("1.1.1.1:1000", StatusCode::OK),
+            ("2.2.2.2:1000", StatusCode::OK),
+            ("3.3.3.3:1000", StatusCode::TOO_MANY_REQUESTS),
+        ] {
+            let req = post("/api/verify_second_challenge", peer)
+                .set_json(&alice)
+                .to_request();
+            let resp = call_service(&app, req).await;
+            assert_eq!(resp.status(), status);
+
+            // The body is still available to the route.
+            if status == StatusCode::OK {
+                let body: serde_json::Value = read_body_json(resp).await;
+                assert_eq!(body, alice);
+            }
+        }
+
+        let req = post("/api/verify_second_challenge", "3.3.3.3:1000")
+            .set_json(&bob)
+            .to_request();
+        let resp = call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix::test]
+    async fn limit_dynamic_routes() {
+        let app = init_service(
+            App::new()
+                .wrap(limiter())
+                .route(
+                    "/api/v1/identity/{chain}/{address}",
+                    web::get().to(HttpResponse::Ok),
+                )
+                .route(
+                    "/api/verify_second_challenge",
+                    web::post().to(HttpResponse::Ok),
+                ),
+        )
+        .await;
+
+        let get = |path: &str, peer: &str| {
+            TestRequest::get()
+                .uri(path)
+                .peer_addr(peer.parse().unwrap())
+                .to_request()
+        };
+
+        // The identity is taken from the path, regardless of the client.
+        for (peer, status) in [
+            ("1.1.1.1:1000", StatusCode::OK),
+            ("2.2.2.2:1000", StatusCode::OK),
+            ("3.3.3.3:1000", StatusCode::TOO_MANY_REQUESTS),
+        ] {
+            let resp = call_service(&app, get("/api/v1/identity/polkadot/Alice", peer)).await;
+            assert_eq!(resp.status(), status);
+        }
+
+        let resp = call_service(&app, get("/api/v1/identity/polkadot/Bob", "3.3.3.3:1000")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Requests without an identity are limited per client IP.
+        for (peer, status) in [
+            ("1.1.1.1:1000", StatusCode::OK),
+            ("1.1.1.1:1001", StatusCode::OK),
+            ("1.1.1.1:1002", StatusCode::TOO_MANY_REQUESTS),
+            ("2.2.2.2:1000", StatusCode::OK),
+        ] {
+            let req = post("/api/verify_second_challenge", peer)
+                .set_json(serde_json::json!({ "challenge": "1234" }))
+                .to_request();
+            let resp = call_service(&app, req).await;
+            assert_eq!(resp.status(), status, "{}", peer);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::JsonResult;
 use crate::database::Database;
-use crate::primitives::IdentityFieldValue;
+use crate::primitives::{IdentityContext, IdentityFieldValue};
 use actix::prelude::*;
 use actix_web::{web, HttpResponse};
 
@@ -36,6 +36,15 @@ impl Handler<VerifyChallenge> for SecondChallengeVerifier {
         Box::pin(
             async move {
                 debug!("Received second challenge: {:?}", msg);
+
+                if msg.context.is_none() {
+                    if !db.allows_unscoped_second_challenge() {
+                        return JsonResult::Err("Missing identity context".to_string());
+                    }
+
+                    warn!("Received second challenge without identity context (deprecated)");
+                }
+
                 db.verify_second_challenge(msg)
                     .await
                     .map(JsonResult::Ok)
@@ -49,6 +58,9 @@ impl Handler<VerifyChallenge> for SecondChallengeVerifier {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Message)]
 #[rtype(result = "JsonResult<bool>")]
 pub struct VerifyChallenge {
+    // Requests without a context check every identity that specifies the
+    // entry. Deprecated, see `Database::with_unscoped_second_challenge`.
+    pub context: Option<IdentityContext>,
     pub entry: IdentityFieldValue,
     pub challenge: String,
 }
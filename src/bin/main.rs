@@ -1,7 +1,7 @@
-use system::{run, Result};
+use clap::Parser;
+use system::{Cli, Result};
 
 #[actix::main]
 async fn main() -> Result<()> {
-    run().await?;
-    unreachable!()
+    Cli::parse().run().await
 }
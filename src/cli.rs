@@ -0,0 +1,151 @@
+use crate::adapters::admin::{process_admin_as, Command, Role, TargetAddress};
+use crate::{open_config, open_database, serve, Config, InstanceType, Result};
+use clap::{Parser, Subcommand};
+use std::str::FromStr;
+
+#[derive(Debug, Parser)]
+#[command(name = "registrar", version, about = "Polkadot Registrar Service")]
+pub struct Cli {
+    /// Path to the config file. Defaults to `config.yaml` or
+    /// `/etc/registrar/config.yaml`.
+    #[arg(long, short, global = true)]
+    config: Option<String>,
+    /// Defaults to `serve`.
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Debug, Eq, PartialEq, Subcommand)]
+enum CliCommand {
+    /// Runs the service as specified in the config.
+    Serve,
+    /// Executes an admin command against the database, e.g. `admin status <ADDR>`.
+    Admin {
+        #[arg(required = true, trailing_var_arg = true)]
+        command: Vec<String>,
+    },
+    /// Validates the config file.
+    CheckConfig,
+    /// Prints the full judgement state of the address as JSON. The chain can
+    /// be specified as `<CHAIN>:<ADDR>`.
+    Inspect { address: String },
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<()> {
+        let config = open_config(self.config.as_deref())?;
+
+        match self.command.unwrap_or(CliCommand::Serve) {
+            CliCommand::Serve => serve(config).await,
+            CliCommand::Admin { command } => admin(config, &command.join(" ")).await,
+            CliCommand::CheckConfig => {
+                println!("{}", describe_config(&config));
+                Ok(())
+            }
+            CliCommand::Inspect { address } => inspect(config, address).await,
+        }
+    }
+}
+
+async fn admin(config: Config, command: &str) -> Result<()> {
+    let command = Command::from_str(command).map_err(|err| anyhow!("{}", err))?;
+    let db = open_database(&config).await?;
+
+    // Shell access implies full access to the database anyway.
+    let actor = format!(
+        "cli:{}",
+        std::env::var("USER").unwrap_or_else(|_| "unknown".to_string())
+    );
+
+    let resp = process_admin_as(&db, &actor, Role::Superadmin, command).await;
+    if !resp.is_success() {
+        return Err(anyhow!("{}", resp));
+    }
+
+    println!("{}", resp);
+    Ok(())
+}
+
+async fn inspect(config: Config, address: String) -> Result<()> {
+    let db = open_database(&config).await?;
+    let context = TargetAddress::from_str(&address)
+        .and_then(|target| target.resolve(db.chains()))
+        .map_err(|err| anyhow!("{}", err))?;
+
+    let state = db
+        .fetch_judgement_state(&context)
+        .await?
+        .ok_or_else(|| anyhow!("No judgement request found for {:?}", context))?;
+
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+fn describe_config(config: &Config) -> String {
+    let instance = match &config.instance {
+        InstanceType::AdapterListener(_) => "adapter_listener",
+        InstanceType::SessionNotifier(_) => "session_notifier",
+        InstanceType::SingleInstance(_) => "single_instance",
+    };
+
+    format!(
+        "Config is valid (instance: {}, database: {})",
+        instance, config.db.name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parse_cli() {
+        let cli = Cli::try_parse_from(["registrar"]).unwrap();
+        assert_eq!(cli.config, None);
+        assert_eq!(cli.command, None);
+
+        let cli = Cli::try_parse_from(["registrar", "serve", "--config", "my.yaml"]).unwrap();
+        assert_eq!(cli.config, Some("my.yaml".to_string()));
+        assert_eq!(cli.command, Some(CliCommand::Serve));
+
+        let cli = Cli::try_parse_from(["registrar", "admin", "verify", "Alice", "email"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(CliCommand::Admin {
+                command: vec![
+                    "verify".to_string(),
+                    "Alice".to_string(),
+                    "email".to_string()
+                ]
+            })
+        );
+
+        let cli = Cli::try_parse_from(["registrar", "inspect", "Alice"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(CliCommand::Inspect {
+                address: "Alice".to_string()
+            })
+        );
+
+        assert!(Cli::try_parse_from(["registrar", "admin"]).is_err());
+        assert!(Cli::try_parse_from(["registrar", "inspect"]).is_err());
+    }
+
+    #[test]
+    fn check_sample_configs() {
+        let config = open_config(Some("config/sample.adapter_listener.yaml")).unwrap();
+        assert!(describe_config(&config).contains("adapter_listener"));
+
+        let config = open_config(Some("config/sample.session_notifier.yaml")).unwrap();
+        assert!(describe_config(&config).contains("session_notifier"));
+
+        assert!(open_config(Some("config/missing.yaml")).is_err());
+    }
+}
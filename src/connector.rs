@@ -1,6 +1,9 @@
 use crate::display_name::DisplayNameVerifier;
+use crate::health::health;
+use crate::metrics::metrics;
 use crate::primitives::{
-    ChainAddress, ChainName, IdentityContext, IdentityFieldValue, JudgementState, Timestamp,
+    normalize_pgp_fingerprint, normalize_telegram_handle, ChainAddress, ChainName, IdentityContext,
+    IdentityFieldValue, JudgementState, Timestamp,
 };
 use crate::{Database, DisplayNameConfig, Result, WatcherConfig};
 use actix::io::SinkWrite;
@@ -312,6 +315,12 @@ impl Actor for Connector {
             self.start_active_display_names_task(ctx);
             self.start_judgement_candidates_task(ctx);
         });
+
+        metrics()
+            .watcher_connected
+            .with_label_values(&[self.network.as_str()])
+            .set(1);
+        health().watcher_connected(self.network);
     }
 
     fn stopped(&mut self, _ctx: &mut Context<Self>) {
@@ -328,12 +337,23 @@ impl Actor for Connector {
         let db = self.db.clone();
         let dn_verifier = self.dn_verifier.clone();
 
+        metrics()
+            .watcher_connected
+            .with_label_values(&[network.as_str()])
+            .set(0);
+        health().watcher_disconnected(network);
+
         actix::spawn(
             async move {
                 warn!("Watcher disconnected, trying to reconnect...");
 
                 let mut counter = 0;
                 loop {
+                    metrics()
+                        .watcher_reconnect_attempts
+                        .with_label_values(&[network.as_str()])
+                        .inc();
+
                     if Connector::start(endpoint.clone(), network, db.clone(), dn_verifier.clone())
                         .await
                         .is_err()
@@ -417,6 +437,11 @@ impl Handler<ClientCommand> for Connector {
                     .into(),
                 ))
                 .map_err(|err| anyhow!("failed to provide judgement: {:?}", err))?;
+
+                metrics()
+                    .judgements_submitted
+                    .with_label_values(&[self.network.as_str()])
+                    .inc();
             }
             ClientCommand::RequestPendingJudgements => {
                 debug!("Requesting pending judgements over websocket stream");
@@ -503,6 +528,7 @@ impl Handler<WatcherMessage> for Connector {
 
         // Update timestamp
         self.last_watcher_msg = Timestamp::now();
+        health().watcher_message(self.network);
 
         let network = self.network;
         let db = self.db.clone();
@@ -527,6 +553,11 @@ impl Handler<WatcherMessage> for Connector {
 
                             info!("Marking {:?} as judged", context);
                             db.set_judged(&context).await?;
+
+                            metrics()
+                                .judgements_acknowledged
+                                .with_label_values(&[network.as_str()])
+                                .inc();
                         }
                     }
                     WatcherMessage::NewJudgementRequest(data) => {
@@ -663,6 +694,10 @@ pub enum AccountType {
     Twitter,
     #[serde(rename = "matrix")]
     Matrix,
+    #[serde(rename = "telegram")]
+    Telegram,
+    #[serde(rename = "github")]
+    Github,
     #[serde(rename = "pgpFingerprint")]
     PGPFingerprint,
     #[serde(rename = "image")]
@@ -682,7 +717,13 @@ impl From<(AccountType, String)> for IdentityFieldValue {
             AccountType::Web => IdentityFieldValue::Web(value),
             AccountType::Twitter => IdentityFieldValue::Twitter(value.to_lowercase()),
             AccountType::Matrix => IdentityFieldValue::Matrix(value),
-            AccountType::PGPFingerprint => IdentityFieldValue::PGPFingerprint(()),
+            AccountType::Telegram => {
+                IdentityFieldValue::Telegram(normalize_telegram_handle(&value))
+            }
+            AccountType::Github => IdentityFieldValue::Github(value.to_lowercase()),
+            AccountType::PGPFingerprint => {
+                IdentityFieldValue::PGPFingerprint(normalize_pgp_fingerprint(&value))
+            }
             AccountType::Image => IdentityFieldValue::Image(()),
             AccountType::Additional => IdentityFieldValue::Additional(()),
         }
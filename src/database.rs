@@ -1,23 +1,41 @@
-use crate::adapters::admin::RawFieldName;
+use crate::adapters::admin::{AuditEntry, ModeratorNote, PendingApproval, RawFieldName};
 use crate::api::VerifyChallenge;
 use crate::connector::DisplayNameEntry;
+use crate::metrics::metrics;
 use crate::primitives::{
-    ChainName, ChallengeType, Event, ExpectedMessage, ExternalMessage, IdentityContext,
-    IdentityFieldValue, JudgementState, NotificationMessage, Timestamp,
+    AccountKeyProof, AccountKeyType, ChainName, ChallengeType, Event, ExpectedMessage,
+    ExternalMessage, ExternalMessageType, IdentityContext, IdentityField, IdentityFieldValue,
+    JudgementState, NotificationMessage, Timestamp,
 };
-use crate::Result;
+use crate::webhooks::WebhookDeadLetter;
+use crate::{FailedAttemptsConfig, Result};
 use bson::{doc, from_document, to_bson, to_document, Bson, Document};
 use futures::StreamExt;
-use mongodb::options::{IndexOptions, TransactionOptions, UpdateOptions};
+use mongodb::options::{
+    Collation, CollationStrength, FindOneAndUpdateOptions, FindOneOptions, FindOptions,
+    IndexOptions, ReturnDocument, TransactionOptions, UpdateOptions,
+};
 use mongodb::{Client, ClientSession, Database as MongoDb, IndexModel};
 use rand::{thread_rng, Rng};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
 const IDENTITY_COLLECTION: &str = "identities";
 const EVENT_COLLECTION: &str = "event_log";
 const DISPLAY_NAMES: &str = "display_names";
+const ADMIN_AUDIT_LOG: &str = "admin_audit_log";
+const MODERATOR_NOTES: &str = "moderator_notes";
+const PENDING_APPROVALS: &str = "pending_approvals";
+const WEBHOOK_DEAD_LETTERS: &str = "webhook_dead_letters";
+const WEBHOOK_CURSORS: &str = "webhook_cursors";
+const TELEGRAM_CHATS: &str = "telegram_chats";
+// Maximum number of audit log entries returned by a query.
+const AUDIT_LOG_LIMIT: i64 = 20;
+// Number of events returned by an event history query, if not specified
+// otherwise, and the upper limit.
+const EVENT_HISTORY_DEFAULT_LIMIT: usize = 50;
+const EVENT_HISTORY_MAX_LIMIT: usize = 200;
 
 /// Convenience trait. Converts a value to BSON.
 trait ToBson {
@@ -34,6 +52,25 @@ impl<T: Serialize> ToBson for T {
     }
 }
 
+// Buckets are ordered by period, then by chain.
+fn stats_bucket(
+    buckets: &mut BTreeMap<(String, String), StatsBucket>,
+    chain: ChainName,
+    period: String,
+) -> &mut StatsBucket {
+    buckets
+        .entry((period.clone(), chain.as_str().to_string()))
+        .or_insert_with(|| StatsBucket::new(chain, period))
+}
+
+fn ratio(part: u64, total: u64) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(part as f64 / total as f64)
+    }
+}
+
 // Keeps track of the latest, fetched events to avoid sending old messages or
 // duplicates.
 pub struct EventCursor {
@@ -50,10 +87,166 @@ impl EventCursor {
     }
 }
 
+/// Filter and pagination of the event history of an identity.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventHistoryQuery {
+    // Only returns events older than the event with this id. Used for
+    // fetching the next page.
+    pub before: Option<String>,
+    // Time range (UNIX timestamps in seconds), both inclusive.
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+impl EventHistoryQuery {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(before) = &self.before {
+            bson::oid::ObjectId::parse_str(before)
+                .map_err(|_| anyhow!("Invalid event id: {}", before))?;
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(anyhow!("The start of the time range is after its end"));
+            }
+        }
+
+        if self.limit == Some(0) {
+            return Err(anyhow!("The limit must be greater than zero"));
+        }
+
+        Ok(())
+    }
+}
+
+/// A page of events, newest first.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventHistory {
+    pub events: Vec<EventRecord>,
+    // Passed as `before` to fetch the next page, if there are older events.
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub id: String,
+    pub timestamp: Timestamp,
+    pub message: NotificationMessage,
+}
+
+/// Length of the time buckets of the registrar statistics.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl StatsInterval {
+    // Format of the bucket label (`$dateToString`). Weeks are ISO weeks.
+    fn date_format(&self) -> &'static str {
+        match self {
+            StatsInterval::Day => "%Y-%m-%d",
+            StatsInterval::Week => "%G-W%V",
+            StatsInterval::Month => "%Y-%m",
+        }
+    }
+}
+
+impl std::str::FromStr for StatsInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(StatsInterval::Day),
+            "week" => Ok(StatsInterval::Week),
+            "month" => Ok(StatsInterval::Month),
+            _ => Err(anyhow!("Unsupported interval: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StatsQuery {
+    // All chains if not specified.
+    pub chain: Option<ChainName>,
+    // Defaults to months.
+    pub interval: Option<StatsInterval>,
+    // Time range (UNIX timestamps in seconds), both inclusive.
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl StatsQuery {
+    pub fn validate(&self) -> Result<()> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(anyhow!("The start of the time range is after its end"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Registrar statistics of a chain within a time bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsBucket {
+    pub chain: ChainName,
+    // E.g. `2023-04` for months, `2023-W14` for weeks.
+    pub period: String,
+    pub requests_received: u64,
+    pub judgements_issued: u64,
+    // Seconds from the judgement request to the full verification.
+    pub median_time_to_verify: Option<u64>,
+    // Verification attempts per field type.
+    pub fields: BTreeMap<String, FieldStats>,
+    // Identities with at least one manual verification.
+    pub manual_verifications: u64,
+    // Share of the identities with manual verifications among all identities
+    // with verifications.
+    pub manual_share: Option<f64>,
+    pub display_name_rejections: u64,
+}
+
+impl StatsBucket {
+    fn new(chain: ChainName, period: String) -> Self {
+        StatsBucket {
+            chain,
+            period,
+            requests_received: 0,
+            judgements_issued: 0,
+            median_time_to_verify: None,
+            fields: BTreeMap::new(),
+            manual_verifications: 0,
+            manual_share: None,
+            display_name_rejections: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldStats {
+    pub verified: u64,
+    pub failed: u64,
+    pub success_rate: Option<f64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Database {
     client: Client,
     db: MongoDb,
+    failed_attempts: Option<FailedAttemptsConfig>,
+    challenge_ttl: Option<u64>,
+    approval_window: Option<u64>,
+    // Maps credentials (e.g. `token:<NAME>`) to the name of the admin.
+    approval_admins: HashMap<String, String>,
+    regeneration_cooldown: Option<u64>,
+    require_account_key: bool,
+    allow_unscoped_second_challenge: bool,
+    chains: Vec<ChainName>,
 }
 
 impl Database {
@@ -61,14 +254,12 @@ impl Database {
         let client = Client::with_uri_str(uri).await?;
         let db = client.database(db);
 
-        // Create collection if not exist (required for index creation)
-        if !db
-            .list_collection_names(None)
-            .await?
-            .iter()
-            .any(|e| e == IDENTITY_COLLECTION)
-        {
-            db.create_collection(IDENTITY_COLLECTION, None).await?;
+        // Create collections if not exist (required for index creation)
+        let collections = db.list_collection_names(None).await?;
+        for name in [IDENTITY_COLLECTION, EVENT_COLLECTION, PENDING_APPROVALS] {
+            if !collections.iter().any(|e| e == name) {
+                db.create_collection(name, None).await?;
+            }
         }
 
         // Create a unique constraint.
@@ -85,7 +276,139 @@ impl Database {
             .create_index(model, None)
             .await?;
 
-        Ok(Database { client, db })
+        // Speeds up the lookup of the latest events of an identity.
+        let model = IndexModel::builder()
+            .keys(doc! { "message.value.context": 1, "_id": -1 })
+            .build();
+
+        db.collection::<Event>(EVENT_COLLECTION)
+            .create_index(model, None)
+            .await?;
+
+        // Speeds up event history queries within a time range.
+        let model = IndexModel::builder()
+            .keys(doc! { "message.value.context": 1, "timestamp": -1 })
+            .build();
+
+        db.collection::<Event>(EVENT_COLLECTION)
+            .create_index(model, None)
+            .await?;
+
+        // At most one pending approval per identity. Expired approvals are
+        // replaced by new requests, so those are removed first.
+        let approvals = db.collection::<PendingApproval>(PENDING_APPROVALS);
+        approvals
+            .delete_many(
+                doc! {
+                    "expires_at": {
+                        "$lte": Timestamp::now().to_bson()?,
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        let model = IndexModel::builder()
+            .keys(doc! { "context": 1 })
+            .options({
+                let mut ops = IndexOptions::default();
+                ops.unique = Some(true);
+                ops
+            })
+            .build();
+
+        approvals.create_index(model, None).await?;
+
+        Ok(Database {
+            client,
+            db,
+            failed_attempts: None,
+            challenge_ttl: None,
+            approval_window: None,
+            approval_admins: HashMap::new(),
+            regeneration_cooldown: None,
+            require_account_key: false,
+            allow_unscoped_second_challenge: false,
+            chains: ChainName::all().to_vec(),
+        })
+    }
+    /// Challenges older than the TTL (in seconds) are rejected. Without a
+    /// TTL, challenges never expire.
+    pub fn with_challenge_ttl(mut self, ttl: u64) -> Self {
+        self.challenge_ttl = Some(ttl);
+        self
+    }
+    pub fn challenge_ttl(&self) -> Option<u64> {
+        self.challenge_ttl
+    }
+    /// Users can regenerate a challenge once it is older than the cooldown
+    /// (in seconds). Without a cooldown, challenges can be regenerated at any
+    /// time.
+    pub fn with_regeneration_cooldown(mut self, cooldown: u64) -> Self {
+        self.regeneration_cooldown = Some(cooldown);
+        self
+    }
+    /// Locks fields for a cooldown once the maximum of failed attempts is
+    /// reached. Without this policy, fields are never locked.
+    pub fn with_failed_attempts(mut self, config: FailedAttemptsConfig) -> Self {
+        self.failed_attempts = Some(config);
+        self
+    }
+    pub fn failed_attempts(&self) -> Option<&FailedAttemptsConfig> {
+        self.failed_attempts.as_ref()
+    }
+    /// Full manual verifications must be approved by a second admin within
+    /// the window (in seconds). Without a window, they are executed
+    /// immediately.
+    pub fn with_approval_window(mut self, window: u64) -> Self {
+        self.approval_window = Some(window);
+        self
+    }
+    pub fn approval_window(&self) -> Option<u64> {
+        self.approval_window
+    }
+    /// Admins and their credentials, as recorded in the audit log. All
+    /// credentials of an admin count as the same admin when approving full
+    /// manual verifications.
+    pub fn with_approval_admins(mut self, admins: &HashMap<String, Vec<String>>) -> Self {
+        self.approval_admins = admins
+            .iter()
+            .flat_map(|(name, credentials)| {
+                credentials
+                    .iter()
+                    .map(move |credential| (credential.clone(), name.clone()))
+            })
+            .collect();
+        self
+    }
+    /// The admin the credential belongs to, if any.
+    pub fn approval_admin(&self, actor: &str) -> Option<&str> {
+        self.approval_admins.get(actor).map(|name| name.as_str())
+    }
+    /// Identities are only considered fully verified once the account key
+    /// proof is verified, too.
+    pub fn with_required_account_key(mut self) -> Self {
+        self.require_account_key = true;
+        self
+    }
+    /// Accept second challenges without an identity context, which are
+    /// checked against every identity that specifies the entry. Deprecated,
+    /// only kept until all clients have migrated.
+    pub fn with_unscoped_second_challenge(mut self, allow: bool) -> Self {
+        self.allow_unscoped_second_challenge = allow;
+        self
+    }
+    pub fn allows_unscoped_second_challenge(&self) -> bool {
+        self.allow_unscoped_second_challenge
+    }
+    /// Chains which are watched for judgement requests, used to resolve the
+    /// addresses of admin commands. All supported chains by default.
+    pub fn with_chains(mut self, chains: Vec<ChainName>) -> Self {
+        self.chains = chains;
+        self
+    }
+    pub fn chains(&self) -> &[ChainName] {
+        &self.chains
     }
     async fn start_transaction(&self) -> Result<ClientSession> {
         let mut options = TransactionOptions::default();
@@ -201,8 +524,8 @@ impl Database {
 
         Ok(true)
     }
-    #[cfg(test)]
-    pub async fn delete_judgement(&self, context: &IdentityContext) -> Result<()> {
+    /// Drops the judgement request. Returns `false` if it does not exist.
+    pub async fn delete_judgement(&self, context: &IdentityContext) -> Result<bool> {
         let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
 
         let res = coll
@@ -214,11 +537,7 @@ impl Database {
             )
             .await?;
 
-        if res.deleted_count != 1 {
-            panic!()
-        }
-
-        Ok(())
+        Ok(res.deleted_count == 1)
     }
     pub async fn verify_manually(
         &self,
@@ -243,27 +562,58 @@ impl Database {
 
         let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
 
+        if let RawFieldName::All = field {
+            return Err(anyhow!(
+                "field name 'all' is abstract and cannot be verified individually"
+            ));
+        }
+
+        // Fetch the challenge type of the field. The same field can have
+        // different challenge types, depending on when the request was inserted.
+        let challenge = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                session,
+            )
+            .await?
+            .and_then(|state| {
+                state
+                    .fields
+                    .into_iter()
+                    .find(|f| f.value.as_raw_field_name().as_ref() == Some(field))
+            })
+            .map(|f| f.challenge);
+
         // Set the appropriate types for verification.
-        let update = match field {
-            // For "ChallengeType::ExpectedMessage".
-            RawFieldName::Twitter | RawFieldName::Matrix => {
+        let update = match challenge {
+            // For "ChallengeType::ExpectedMessage" (with secondary verification).
+            Some(ChallengeType::ExpectedMessage {
+                expected: _,
+                second: Some(_),
+            }) => {
                 doc! {
                     "$set": {
                         "fields.$.challenge.content.expected.is_verified": true,
+                        "fields.$.challenge.content.second.is_verified": true,
                     }
                 }
             }
-            // For "ChallengeType::ExpectedMessage" (with secondary verification).
-            RawFieldName::Email => {
+            // For "ChallengeType::ExpectedMessage".
+            Some(ChallengeType::ExpectedMessage {
+                expected: _,
+                second: None,
+            }) => {
                 doc! {
                     "$set": {
                         "fields.$.challenge.content.expected.is_verified": true,
-                        "fields.$.challenge.content.second.is_verified": true,
                     }
                 }
             }
             // For "ChallengeType::DisplayNameCheck".
-            RawFieldName::DisplayName => {
+            Some(ChallengeType::DisplayNameCheck { .. }) => {
                 doc! {
                     "$set": {
                         "fields.$.challenge.content.passed": true,
@@ -271,18 +621,14 @@ impl Database {
                 }
             }
             // For "ChallengeType::Unsupported".
-            RawFieldName::LegalName | RawFieldName::Web => {
+            Some(ChallengeType::Unsupported { .. }) => {
                 doc! {
                     "$set": {
                         "fields.$.challenge.content.is_verified": true,
                     }
                 }
             }
-            RawFieldName::All => {
-                return Err(anyhow!(
-                    "field name 'all' is abstract and cannot be verified individually"
-                ))
-            }
+            None => return Ok(None),
         };
 
         // Update field.
@@ -324,8 +670,15 @@ impl Database {
                 )
                 .await?;
 
-            // Check the new state.
+            // Check the new state. Completing the identity requires a full
+            // manual verification, the transaction is aborted otherwise.
             if let Some(state) = doc {
+                if state.check_full_verification() {
+                    return Err(anyhow!(
+                        "manual verification of individual fields must not complete the identity"
+                    ));
+                }
+
                 self.process_fully_verified(&state.context, session).await?;
             } else {
                 return Ok(None);
@@ -353,6 +706,9 @@ impl Database {
             )
             .await?;
 
+        // Outcomes of the verification attempts, recorded once committed.
+        let mut outcomes = vec![];
+
         // If a field was found, update it.
         while let Some(doc) = cursor.next(&mut session).await {
             let state: JudgementState = from_document(doc?)?;
@@ -368,17 +724,43 @@ impl Database {
             let context = state.context.clone();
             let field_value = field_state.value.clone();
 
+            // Locked fields do not accept any attempts until the cooldown is over.
+            if field_state.is_locked() {
+                debug!("Ignoring message for locked field {:?}", field_value);
+                continue;
+            }
+
             let challenge = &field_state.challenge;
             if !challenge.is_verified() {
                 match challenge {
                     ChallengeType::ExpectedMessage { expected, second } => {
+                        // Web domains can alternatively prove ownership by
+                        // referencing the account address.
+                        let is_valid = expected.is_message_valid(message)
+                            || (matches!(message.origin, ExternalMessageType::Web(_))
+                                && message.contains(context.address.as_str()));
+
                         // Only proceed if the expected challenge has not been verified yet.
                         if !expected.is_verified {
-                            if expected.is_message_valid(message) {
+                            if expected.is_expired(self.challenge_ttl) {
+                                // Expired challenges must be regenerated by
+                                // the user. This does not count as a failed
+                                // attempt.
+                                outcomes.push("expired");
+                                self.insert_event(
+                                    NotificationMessage::ChallengeExpired {
+                                        context: context.clone(),
+                                        field: field_value,
+                                    },
+                                    &mut session,
+                                )
+                                .await?;
+                            } else if is_valid {
                                 // Update field state. Be more specific with the query in order
                                 // to verify the correct field (in theory, there could be
                                 // multiple pending requests with the same external account
                                 // specified).
+                                outcomes.push("verified");
                                 coll.update_one_with_session(
                                     doc! {
                                         "context": context.to_bson()?,
@@ -413,23 +795,12 @@ impl Database {
                                     )
                                     .await?;
                                 }
+                            } else if message.origin.is_proof_lookup() {
+                                // The proof belongs to another identity
+                                // specifying the same account.
+                                debug!("Proof does not match {:?}", field_value);
                             } else {
-                                // Update field state.
-                                coll.update_many_with_session(
-                                    doc! {
-                                        "context": context.to_bson()?,
-                                        "fields.value": message.origin.to_bson()?,
-                                    },
-                                    doc! {
-                                        "$inc": {
-                                            "fields.$.failed_attempts": 1isize.to_bson()?,
-                                        }
-                                    },
-                                    None,
-                                    &mut session,
-                                )
-                                .await?;
-
+                                outcomes.push("failed");
                                 self.insert_event(
                                     NotificationMessage::FieldVerificationFailed {
                                         context: context.clone(),
@@ -438,6 +809,10 @@ impl Database {
                                     &mut session,
                                 )
                                 .await?;
+
+                                // Update field state.
+                                self.process_failed_attempt(&context, field_state, &mut session)
+                                    .await?;
                             }
                         }
                     }
@@ -456,47 +831,41 @@ impl Database {
 
         session.commit_transaction().await?;
 
+        for outcome in outcomes {
+            metrics()
+                .verifications
+                .with_label_values(&[message.origin.adapter(), outcome])
+                .inc();
+        }
+
         Ok(())
     }
-    /// Check if all fields have been verified.
-    async fn process_fully_verified(
+    /// Counts a failed verification attempt. Once the maximum of failed
+    /// attempts is reached, the field gets locked for the configured cooldown
+    /// and its pending challenges are replaced.
+    async fn process_failed_attempt(
         &self,
         context: &IdentityContext,
+        field: &IdentityField,
         session: &mut ClientSession,
     ) -> Result<()> {
-        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
-
-        // Get the full state.
-        let state = coll
-            .find_one_with_session(
-                doc! {
-                    "context": context.to_bson()?,
-                },
-                None,
-                session,
-            )
-            .await?
-            .expect("Failed to retrieve full state for processing (this is a bug)");
-
-        if state.check_full_verification() {
-            // Create a timed delay for issuing judgments. Between 30 seconds to
-            // 5 minutes. This is used to prevent timing attacks where a user
-            // updates the identity right before the judgement is issued.
-            let now = Timestamp::now();
-            let offset = thread_rng().gen_range(30..300);
-            let issue_at = Timestamp::with_offset(offset);
+        let config = match self
+            .failed_attempts
+            .as_ref()
+            .filter(|config| field.failed_attempts + 1 >= config.max_attempts)
+        {
+            Some(config) => config,
+            None => {
+                let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
 
-            let res = coll
-                .update_one_with_session(
+                coll.update_one_with_session(
                     doc! {
-                        "context": state.context.to_bson()?,
-                        "is_fully_verified": false,
+                        "context": context.to_bson()?,
+                        "fields.value": field.value.to_bson()?,
                     },
                     doc! {
-                        "$set": {
-                            "is_fully_verified": true,
-                            "completion_timestamp": now.to_bson()?,
-                            "issue_judgement_at": issue_at.to_bson()?,
+                        "$inc": {
+                            "fields.$.failed_attempts": 1isize.to_bson()?,
                         }
                     },
                     None,
@@ -504,57 +873,348 @@ impl Database {
                 )
                 .await?;
 
-            if res.modified_count != 0 {
+                return Ok(());
+            }
+        };
+
+        let locked_until = Timestamp::with_offset(config.cooldown);
+
+        self.insert_event(
+            NotificationMessage::FieldLocked {
+                context: context.clone(),
+                field: field.value.clone(),
+                locked_until,
+            },
+            session,
+        )
+        .await?;
+
+        self.rotate_challenge(
+            context,
+            field,
+            doc! {
+                "fields.$.failed_attempts": 0isize.to_bson()?,
+                "fields.$.locked_until": locked_until.to_bson()?,
+            },
+            session,
+        )
+        .await
+    }
+    /// Replaces the pending challenges of the field, alongside the additional
+    /// field state updates (`$set`) of `update`.
+    async fn rotate_challenge(
+        &self,
+        context: &IdentityContext,
+        field: &IdentityField,
+        mut update: Document,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let mut challenge = field.challenge.clone();
+        challenge.rotate();
+        update.insert("fields.$.challenge", challenge.to_bson()?);
+
+        coll.update_one_with_session(
+            doc! {
+                "context": context.to_bson()?,
+                "fields.value": field.value.to_bson()?,
+            },
+            doc! {
+                "$set": update,
+            },
+            None,
+            session,
+        )
+        .await?;
+
+        // The replaced second challenge must be sent to the user again.
+        if let ChallengeType::ExpectedMessage {
+            expected,
+            second: Some(_),
+        } = &challenge
+        {
+            if expected.is_verified {
                 self.insert_event(
-                    NotificationMessage::IdentityFullyVerified {
-                        context: state.context.clone(),
+                    NotificationMessage::AwaitingSecondChallenge {
+                        context: context.clone(),
+                        field: field.value.clone(),
                     },
                     session,
                 )
                 .await?;
             }
-        } else {
-            // Reset verification state if identity was changed.
-            let _ = coll
-                .update_one_with_session(
-                    doc! {
-                        "context": state.context.to_bson()?,
-                        "is_fully_verified": true,
-                    },
-                    doc! {
-                        "$set": {
-                            "is_fully_verified": false,
-                            "judgement_submitted": false,
-                        }
-                    },
-                    None,
-                    session,
-                )
-                .await?;
         }
 
         Ok(())
     }
-    pub async fn verify_second_challenge(&self, mut request: VerifyChallenge) -> Result<bool> {
+    /// Replaces the pending challenges of the field on request of the user,
+    /// as long as `challenge` is still the current challenge. Returns `false`
+    /// if the field does not exist, is already verified, is currently locked,
+    /// its challenge was replaced or was issued within the regeneration
+    /// cooldown.
+    pub async fn regenerate_challenge(
+        &self,
+        context: &IdentityContext,
+        field: &IdentityFieldValue,
+        challenge: &str,
+    ) -> Result<bool> {
         let mut session = self.start_transaction().await?;
         let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
 
-        let mut verified = false;
-
-        // Trim received challenge, just in case.
-        request.challenge = request.challenge.trim().to_string();
-
-        // Query database.
-        let mut cursor = coll
-            .find_with_session(
+        let field_state = coll
+            .find_one_with_session(
                 doc! {
-                    "fields.value": request.entry.to_bson()?,
+                    "context": context.to_bson()?,
                 },
                 None,
                 &mut session,
             )
-            .await?;
-
+            .await?
+            .and_then(|state| state.fields.into_iter().find(|f| &f.value == field));
+
+        let field_state = match field_state {
+            Some(field_state)
+                if matches!(
+                    &field_state.challenge,
+                    ChallengeType::ExpectedMessage { expected, .. }
+                        if expected.value == challenge
+                            && !expected.is_issued_within(self.regeneration_cooldown)
+                ) && !field_state.challenge.is_verified()
+                    && !field_state.is_locked() =>
+            {
+                field_state
+            }
+            _ => {
+                session.commit_transaction().await?;
+                return Ok(false);
+            }
+        };
+
+        self.insert_event(
+            NotificationMessage::ChallengeRegenerated {
+                context: context.clone(),
+                field: field.clone(),
+            },
+            &mut session,
+        )
+        .await?;
+
+        self.rotate_challenge(context, &field_state, doc! {}, &mut session)
+            .await?;
+
+        session.commit_transaction().await?;
+
+        Ok(true)
+    }
+    /// Returns the pending account key proof of the identity, or creates a new
+    /// one if there is none or if its nonce expired. Returns `None` if the
+    /// identity does not exist.
+    pub async fn request_account_key_proof(
+        &self,
+        context: &IdentityContext,
+    ) -> Result<Option<AccountKeyProof>> {
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let state = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                &mut session,
+            )
+            .await?;
+
+        let state = match state {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        match state.account_key {
+            Some(proof) if proof.is_verified() || !proof.nonce.is_expired(self.challenge_ttl) => {
+                Ok(Some(proof))
+            }
+            _ => {
+                let proof = AccountKeyProof::new();
+
+                coll.update_one_with_session(
+                    doc! {
+                        "context": context.to_bson()?,
+                    },
+                    doc! {
+                        "$set": {
+                            "account_key": proof.to_bson()?,
+                        }
+                    },
+                    None,
+                    &mut session,
+                )
+                .await?;
+
+                session.commit_transaction().await?;
+
+                Ok(Some(proof))
+            }
+        }
+    }
+    /// Marks the account key proof as verified, as long as the nonce was not
+    /// replaced in the meantime. Returns `false` otherwise.
+    pub async fn set_account_key_verified(
+        &self,
+        context: &IdentityContext,
+        nonce: &str,
+        key_type: AccountKeyType,
+    ) -> Result<bool> {
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let res = coll
+            .update_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                    "account_key.nonce.value": nonce,
+                    "account_key.nonce.is_verified": false,
+                },
+                doc! {
+                    "$set": {
+                        "account_key.nonce.is_verified": true,
+                        "account_key.key_type": key_type.to_bson()?,
+                    }
+                },
+                None,
+                &mut session,
+            )
+            .await?;
+
+        if res.modified_count == 0 {
+            session.commit_transaction().await?;
+            return Ok(false);
+        }
+
+        self.insert_event(
+            NotificationMessage::AccountKeyVerified {
+                context: context.clone(),
+            },
+            &mut session,
+        )
+        .await?;
+
+        self.process_fully_verified(context, &mut session).await?;
+
+        session.commit_transaction().await?;
+
+        Ok(true)
+    }
+    /// Check if all fields have been verified. If required, the account key
+    /// must be verified, too. A full manual verification overrides the account
+    /// key requirement (e.g. for multisig or proxy accounts, which cannot sign),
+    /// so identities are only reset if one of their fields is unverified.
+    async fn process_fully_verified(
+        &self,
+        context: &IdentityContext,
+        session: &mut ClientSession,
+    ) -> Result<()> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        // Get the full state.
+        let state = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                session,
+            )
+            .await?
+            .expect("Failed to retrieve full state for processing (this is a bug)");
+
+        let account_key_verified = !self.require_account_key || state.is_account_key_verified();
+
+        if state.check_full_verification() && account_key_verified {
+            // Create a timed delay for issuing judgments. Between 30 seconds to
+            // 5 minutes. This is used to prevent timing attacks where a user
+            // updates the identity right before the judgement is issued.
+            let now = Timestamp::now();
+            let offset = thread_rng().gen_range(30..300);
+            let issue_at = Timestamp::with_offset(offset);
+
+            let res = coll
+                .update_one_with_session(
+                    doc! {
+                        "context": state.context.to_bson()?,
+                        "is_fully_verified": false,
+                    },
+                    doc! {
+                        "$set": {
+                            "is_fully_verified": true,
+                            "completion_timestamp": now.to_bson()?,
+                            "issue_judgement_at": issue_at.to_bson()?,
+                        }
+                    },
+                    None,
+                    session,
+                )
+                .await?;
+
+            if res.modified_count != 0 {
+                self.insert_event(
+                    NotificationMessage::IdentityFullyVerified {
+                        context: state.context.clone(),
+                    },
+                    session,
+                )
+                .await?;
+            }
+        } else if !state.check_full_verification() {
+            // Reset verification state if identity was changed.
+            let _ = coll
+                .update_one_with_session(
+                    doc! {
+                        "context": state.context.to_bson()?,
+                        "is_fully_verified": true,
+                    },
+                    doc! {
+                        "$set": {
+                            "is_fully_verified": false,
+                            "judgement_submitted": false,
+                        }
+                    },
+                    None,
+                    session,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+    pub async fn verify_second_challenge(&self, mut request: VerifyChallenge) -> Result<bool> {
+        if request.context.is_none() && !self.allow_unscoped_second_challenge {
+            return Err(anyhow!("Second challenge without identity context"));
+        }
+
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let mut verified = false;
+
+        // Trim received challenge, just in case.
+        request.challenge = request.challenge.trim().to_string();
+
+        // Only the specified identity is checked, unless the (deprecated)
+        // request without a context is used.
+        let mut filter = doc! {
+            "fields.value": request.entry.to_bson()?,
+        };
+
+        if let Some(context) = &request.context {
+            filter.insert("context", context.to_bson()?);
+        }
+
+        // Query database.
+        let mut cursor = coll.find_with_session(filter, None, &mut session).await?;
+
         while let Some(state) = cursor.next(&mut session).await {
             let mut state = state?;
             let field_state = state
@@ -566,6 +1226,16 @@ impl Database {
             let context = state.context.clone();
             let field_value = field_state.value.clone();
 
+            // Locked fields do not accept any attempts until the cooldown is over.
+            if field_state.is_locked() {
+                debug!(
+                    "Ignoring second challenge for locked field {:?}",
+                    field_value
+                );
+                continue;
+            }
+
+            let field = field_state.clone();
             match &mut field_state.challenge {
                 ChallengeType::ExpectedMessage {
                     expected: _,
@@ -578,11 +1248,29 @@ impl Database {
                     }
 
                     let second = second.as_mut().unwrap();
-                    if request.challenge.contains(&second.value) {
+
+                    // Verified challenges do not accept any further attempts,
+                    // otherwise wrong guesses would eventually lock the field.
+                    if second.is_verified {
+                        continue;
+                    }
+
+                    if second.is_expired(self.challenge_ttl) {
+                        // Does not count as a failed attempt.
+                        self.insert_event(
+                            NotificationMessage::ChallengeExpired {
+                                context: context.clone(),
+                                field: field_value.clone(),
+                            },
+                            &mut session,
+                        )
+                        .await?;
+                    } else if request.challenge.contains(&second.value) {
                         verified = true;
 
                         coll.update_one_with_session(
                             doc! {
+                                "context": context.to_bson()?,
                                 "fields.value": request.entry.to_bson()?,
                                 "fields.challenge.content.second.value": request.challenge.to_bson()?,
                             },
@@ -613,6 +1301,13 @@ impl Database {
                             &mut session,
                         )
                         .await?;
+
+                        // Requests without a context are not tied to the
+                        // identity, so they do not count as failed attempts.
+                        if request.context.is_some() {
+                            self.process_failed_attempt(&context, &field, &mut session)
+                                .await?;
+                        }
                     }
                 }
                 _ => {
@@ -673,10 +1368,36 @@ impl Database {
             Err(anyhow!("No entry found for {:?}", field))
         }
     }
+    /// Marks the second challenge as issued once it was sent to the user, so
+    /// it expires relative to the delivery.
+    pub async fn set_second_challenge_issued(
+        &self,
+        context: &IdentityContext,
+        field: &IdentityFieldValue,
+    ) -> Result<()> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        coll.update_one(
+            doc! {
+                "context": context.to_bson()?,
+                "fields.value": field.to_bson()?,
+            },
+            doc! {
+                "$set": {
+                    "fields.$.challenge.content.second.issued_at": Timestamp::now().to_bson()?,
+                }
+            },
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+    /// Fetches new events, including their ids.
     pub async fn fetch_events(
         &mut self,
         event_tracker: &mut EventCursor,
-    ) -> Result<Vec<NotificationMessage>> {
+    ) -> Result<Vec<(String, NotificationMessage)>> {
         #[derive(Debug, Deserialize)]
         struct EventWrapper {
             #[serde(rename = "_id")]
@@ -724,11 +1445,11 @@ impl Database {
             .retain(|_, timestamp| timestamp.raw() > current - 10);
 
         // Sort by id, ascending.
-        events.sort_by(|a, b| a.id.cmp(&b.id));
+        events.sort_by_key(|a| a.id);
 
         Ok(events
             .into_iter()
-            .map(|wrapper| wrapper.event.message)
+            .map(|wrapper| (wrapper.id.to_hex(), wrapper.event.message))
             .collect())
     }
     pub async fn fetch_judgement_state(
@@ -754,6 +1475,53 @@ impl Database {
             Ok(None)
         }
     }
+    /// Fetches all fields of the given type whose expected message has not
+    /// been verified yet. Used by verifiers that actively look up proofs.
+    pub async fn fetch_unverified_challenges(
+        &self,
+        field: &RawFieldName,
+    ) -> Result<Vec<(IdentityContext, IdentityFieldValue, ExpectedMessage)>> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let mut cursor = coll
+            .find(
+                doc! {
+                    "fields": {
+                        "$elemMatch": {
+                            "value.type": field.to_string(),
+                            "challenge.type": "expected_message",
+                            "challenge.content.expected.is_verified": false,
+                        }
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        let mut pending = vec![];
+        while let Some(state) = cursor.next().await {
+            let state = state?;
+
+            for f in state.fields {
+                // Locked fields do not accept any attempts anyway.
+                if f.value.as_raw_field_name().as_ref() != Some(field) || f.is_locked() {
+                    continue;
+                }
+
+                if let ChallengeType::ExpectedMessage {
+                    expected,
+                    second: _,
+                } = f.challenge
+                {
+                    if !expected.is_verified && !expected.is_expired(self.challenge_ttl) {
+                        pending.push((state.context.clone(), f.value, expected));
+                    }
+                }
+            }
+        }
+
+        Ok(pending)
+    }
     pub async fn fetch_judgement_candidates(
         &self,
         network: ChainName,
@@ -838,6 +1606,20 @@ impl Database {
             let _ = self
                 .verify_manually(context, &RawFieldName::Matrix, false, Some(&mut session))
                 .await?;
+            let _ = self
+                .verify_manually(context, &RawFieldName::Telegram, false, Some(&mut session))
+                .await?;
+            let _ = self
+                .verify_manually(context, &RawFieldName::Github, false, Some(&mut session))
+                .await?;
+            let _ = self
+                .verify_manually(
+                    context,
+                    &RawFieldName::PGPFingerprint,
+                    false,
+                    Some(&mut session),
+                )
+                .await?;
 
             self.insert_event(
                 NotificationMessage::FullManualVerification {
@@ -854,7 +1636,268 @@ impl Database {
             Ok(false)
         }
     }
-    pub async fn set_judged(&self, context: &IdentityContext) -> Result<()> {
+    /// Reverts a (manual) verification of the fields. The challenges are
+    /// replaced, so the user has to verify the fields again. All fields are
+    /// reverted within the same transaction, returns `None` if the identity
+    /// or any of the fields does not exist.
+    pub async fn unverify_manually(
+        &self,
+        context: &IdentityContext,
+        fields: &[RawFieldName],
+    ) -> Result<Option<()>> {
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        if fields.iter().any(|f| matches!(f, RawFieldName::All)) {
+            return Err(anyhow!(
+                "field name 'all' is abstract and cannot be unverified individually"
+            ));
+        }
+
+        let state = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                &mut session,
+            )
+            .await?;
+
+        let state = match state {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        for field in fields {
+            let challenge = state
+                .fields
+                .iter()
+                .find(|f| f.value.as_raw_field_name().as_ref() == Some(field))
+                .map(|f| f.challenge.clone());
+
+            let mut challenge = match challenge {
+                Some(challenge) => challenge,
+                None => return Ok(None),
+            };
+
+            challenge.reset();
+
+            coll.update_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                    "fields.value.type": field.to_string(),
+                },
+                doc! {
+                    "$set": {
+                        "fields.$.challenge": challenge.to_bson()?,
+                        "fields.$.failed_attempts": 0isize.to_bson()?,
+                        "fields.$.locked_until": Bson::Null,
+                    }
+                },
+                None,
+                &mut session,
+            )
+            .await?;
+
+            self.insert_event(
+                NotificationMessage::ManuallyUnverified {
+                    context: context.clone(),
+                    field: field.clone(),
+                },
+                &mut session,
+            )
+            .await?;
+        }
+
+        // Resets the verification state of the identity, if necessary.
+        self.process_fully_verified(context, &mut session).await?;
+
+        session.commit_transaction().await?;
+
+        Ok(Some(()))
+    }
+    /// Resets all fields of the identity to their initial state, including
+    /// failed attempts and locks. Returns `false` if the identity does not
+    /// exist.
+    pub async fn reset_judgement(&self, context: &IdentityContext) -> Result<bool> {
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let state = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                &mut session,
+            )
+            .await?;
+
+        let mut state = match state {
+            Some(state) => state,
+            None => return Ok(false),
+        };
+
+        for field in &mut state.fields {
+            field.challenge.reset();
+            field.failed_attempts = 0;
+            field.locked_until = None;
+        }
+
+        coll.update_one_with_session(
+            doc! {
+                "context": context.to_bson()?,
+            },
+            doc! {
+                "$set": {
+                    "fields": state.fields.to_bson()?,
+                    "is_fully_verified": false,
+                    "judgement_submitted": false,
+                    "completion_timestamp": Bson::Null,
+                    "issue_judgement_at": Bson::Null,
+                    "account_key": Bson::Null,
+                }
+            },
+            None,
+            &mut session,
+        )
+        .await?;
+
+        self.insert_event(
+            NotificationMessage::IdentityReset {
+                context: context.clone(),
+            },
+            &mut session,
+        )
+        .await?;
+
+        session.commit_transaction().await?;
+
+        Ok(true)
+    }
+    /// Fetches all requests which have not been judged yet, oldest first.
+    pub async fn fetch_pending_judgements(
+        &self,
+        chain: Option<ChainName>,
+    ) -> Result<Vec<JudgementState>> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let mut filter = doc! {
+            "judgement_submitted": false,
+        };
+
+        if let Some(chain) = chain {
+            filter.insert("context.chain", chain.as_str().to_bson()?);
+        }
+
+        let mut cursor = coll
+            .find(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "inserted_timestamp": 1, "_id": 1 })
+                    .build(),
+            )
+            .await?;
+
+        let mut pending = vec![];
+        while let Some(state) = cursor.next().await {
+            pending.push(state?);
+        }
+
+        Ok(pending)
+    }
+    /// Counts the requests of the chain which have not been judged yet.
+    pub async fn count_pending_requests(&self, chain: ChainName) -> Result<u64> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        Ok(coll
+            .count_documents(
+                doc! {
+                    "context.chain": chain.as_str().to_bson()?,
+                    "judgement_submitted": false,
+                },
+                None,
+            )
+            .await?)
+    }
+    /// Searches for identities which specify the given field value, such as
+    /// an email address or an account handle. The search is case insensitive.
+    pub async fn search_judgements(&self, value: &str) -> Result<Vec<JudgementState>> {
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let mut cursor = coll
+            .find(
+                doc! {
+                    "fields.value.value": value.trim().to_bson()?,
+                },
+                FindOptions::builder()
+                    .collation(
+                        Collation::builder()
+                            .locale("en")
+                            .strength(CollationStrength::Secondary)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .await?;
+
+        let mut found = vec![];
+        while let Some(state) = cursor.next().await {
+            found.push(state?);
+        }
+
+        Ok(found)
+    }
+    /// Sends the second email challenge again, if the email address is
+    /// verified but the second challenge is not. Returns `false` otherwise.
+    pub async fn resend_second_challenge(&self, context: &IdentityContext) -> Result<bool> {
+        let mut session = self.start_transaction().await?;
+        let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
+
+        let field = coll
+            .find_one_with_session(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                None,
+                &mut session,
+            )
+            .await?
+            .and_then(|state| {
+                state.fields.into_iter().find(|f| {
+                    matches!(f.value, IdentityFieldValue::Email(_))
+                        && !f.is_locked()
+                        && matches!(
+                            &f.challenge,
+                            ChallengeType::ExpectedMessage {
+                                expected,
+                                second: Some(second),
+                            } if expected.is_verified && !second.is_verified
+                        )
+                })
+            });
+
+        let field = match field {
+            Some(field) => field,
+            None => return Ok(false),
+        };
+
+        // The email adapter sends the challenge on this event.
+        self.insert_event(
+            NotificationMessage::AwaitingSecondChallenge {
+                context: context.clone(),
+                field: field.value,
+            },
+            &mut session,
+        )
+        .await?;
+
+        session.commit_transaction().await?;
+
+        Ok(true)
+    }
+    pub async fn set_judged(&self, context: &IdentityContext) -> Result<()> {
         let mut session = self.start_transaction().await?;
         let coll = self.db.collection::<JudgementState>(IDENTITY_COLLECTION);
 
@@ -1003,6 +2046,834 @@ impl Database {
 
         Ok(())
     }
+    pub async fn insert_audit_entry(&self, entry: &AuditEntry) -> Result<()> {
+        let coll = self.db.collection::<AuditEntry>(ADMIN_AUDIT_LOG);
+        coll.insert_one(entry, None).await?;
+
+        Ok(())
+    }
+    /// Fetches the latest audit log entries, optionally only those referring
+    /// to the given identity.
+    pub async fn fetch_audit_entries(
+        &self,
+        context: Option<&IdentityContext>,
+    ) -> Result<Vec<AuditEntry>> {
+        let coll = self.db.collection::<AuditEntry>(ADMIN_AUDIT_LOG);
+
+        let filter = match context {
+            Some(context) => doc! {
+                "context": context.to_bson()?,
+            },
+            None => doc! {},
+        };
+
+        let mut cursor = coll
+            .find(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "timestamp": -1, "_id": -1 })
+                    .limit(AUDIT_LOG_LIMIT)
+                    .build(),
+            )
+            .await?;
+
+        let mut entries = vec![];
+        while let Some(entry) = cursor.next().await {
+            entries.push(entry?);
+        }
+
+        Ok(entries)
+    }
+    /// Records a webhook delivery which failed on all attempts.
+    pub async fn insert_webhook_dead_letter(&self, dead_letter: &WebhookDeadLetter) -> Result<()> {
+        let coll = self
+            .db
+            .collection::<WebhookDeadLetter>(WEBHOOK_DEAD_LETTERS);
+        coll.insert_one(dead_letter, None).await?;
+
+        Ok(())
+    }
+    /// Fetches the id of the last event which was handled for the webhook
+    /// subscriber, either delivered or recorded as dead letter.
+    pub async fn fetch_webhook_cursor(&self, subscriber: &str) -> Result<Option<String>> {
+        let coll = self.db.collection::<Document>(WEBHOOK_CURSORS);
+
+        let doc = coll
+            .find_one(
+                doc! {
+                    "subscriber": subscriber,
+                },
+                None,
+            )
+            .await?;
+
+        Ok(doc.and_then(|doc| doc.get_str("event_id").ok().map(|id| id.to_string())))
+    }
+    pub async fn set_webhook_cursor(&self, subscriber: &str, event_id: &str) -> Result<()> {
+        let coll = self.db.collection::<Document>(WEBHOOK_CURSORS);
+
+        coll.update_one(
+            doc! {
+                "subscriber": subscriber,
+            },
+            doc! {
+                "$set": {
+                    "event_id": event_id,
+                }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+        Ok(())
+    }
+    /// Fetches the id of the newest event in the event log, if any.
+    pub async fn fetch_latest_event_id(&self) -> Result<Option<String>> {
+        let coll = self.db.collection::<Document>(EVENT_COLLECTION);
+
+        let doc = coll
+            .find_one(
+                doc! {},
+                FindOneOptions::builder()
+                    .sort(doc! { "_id": -1 })
+                    .projection(doc! { "_id": 1 })
+                    .build(),
+            )
+            .await?;
+
+        Ok(doc.and_then(|doc| doc.get_object_id("_id").ok().map(|id| id.to_hex())))
+    }
+    /// Fetches up to `limit` events which were inserted after the event with
+    /// the given id (or all events if none is given), oldest first. Events
+    /// younger than `settle` seconds are skipped: ids are assigned before
+    /// the insertion, so concurrently inserted events might become visible
+    /// out of order.
+    pub async fn fetch_events_for_delivery(
+        &self,
+        after: Option<&str>,
+        settle: u64,
+        limit: i64,
+    ) -> Result<Vec<EventRecord>> {
+        #[derive(Debug, Deserialize)]
+        struct EventWrapper {
+            #[serde(rename = "_id")]
+            id: bson::oid::ObjectId,
+            #[serde(flatten)]
+            event: Event,
+        }
+
+        let mut filter = doc! {
+            "timestamp": {
+                "$lte": Timestamp::now().raw().saturating_sub(settle).to_bson()?,
+            },
+        };
+
+        if let Some(after) = after {
+            let after = bson::oid::ObjectId::parse_str(after)
+                .map_err(|_| anyhow!("Invalid event id: {}", after))?;
+
+            filter.insert("_id", doc! { "$gt": after });
+        }
+
+        let coll = self.db.collection::<EventWrapper>(EVENT_COLLECTION);
+
+        let mut cursor = coll
+            .find(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "_id": 1 })
+                    .limit(limit)
+                    .build(),
+            )
+            .await?;
+
+        let mut events = vec![];
+        while let Some(wrapper) = cursor.next().await {
+            let wrapper = wrapper?;
+            events.push(EventRecord {
+                id: wrapper.id.to_hex(),
+                timestamp: wrapper.event.timestamp,
+                message: wrapper.event.message,
+            });
+        }
+
+        Ok(events)
+    }
+    #[cfg(test)]
+    pub async fn fetch_webhook_dead_letters(&self) -> Result<Vec<WebhookDeadLetter>> {
+        let coll = self
+            .db
+            .collection::<WebhookDeadLetter>(WEBHOOK_DEAD_LETTERS);
+
+        let mut cursor = coll.find(doc! {}, None).await?;
+
+        let mut dead_letters = vec![];
+        while let Some(dead_letter) = cursor.next().await {
+            dead_letters.push(dead_letter?);
+        }
+
+        Ok(dead_letters)
+    }
+    /// Stores the Id of the private chat between the bot and the Telegram
+    /// handle, which is required for sending replies.
+    pub async fn set_telegram_chat_id(&self, handle: &str, chat_id: i64) -> Result<()> {
+        let coll = self.db.collection::<Document>(TELEGRAM_CHATS);
+
+        coll.update_one(
+            doc! {
+                "handle": handle,
+            },
+            doc! {
+                "$set": {
+                    "chat_id": chat_id,
+                }
+            },
+            {
+                let mut opt = UpdateOptions::default();
+                opt.upsert = Some(true);
+                Some(opt)
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+    pub async fn fetch_telegram_chat_id(&self, handle: &str) -> Result<Option<i64>> {
+        let coll = self.db.collection::<Document>(TELEGRAM_CHATS);
+
+        Ok(coll
+            .find_one(doc! { "handle": handle }, None)
+            .await?
+            .and_then(|doc| doc.get_i64("chat_id").ok()))
+    }
+    pub async fn insert_note(&self, note: &ModeratorNote) -> Result<()> {
+        let coll = self.db.collection::<ModeratorNote>(MODERATOR_NOTES);
+        coll.insert_one(note, None).await?;
+
+        Ok(())
+    }
+    /// Fetches all notes of the identity, oldest first.
+    pub async fn fetch_notes(&self, context: &IdentityContext) -> Result<Vec<ModeratorNote>> {
+        let coll = self.db.collection::<ModeratorNote>(MODERATOR_NOTES);
+
+        let mut cursor = coll
+            .find(
+                doc! {
+                    "context": context.to_bson()?,
+                },
+                FindOptions::builder()
+                    .sort(doc! { "timestamp": 1, "_id": 1 })
+                    .build(),
+            )
+            .await?;
+
+        let mut notes = vec![];
+        while let Some(note) = cursor.next().await {
+            notes.push(note?);
+        }
+
+        Ok(notes)
+    }
+    /// Inserts the approval, unless the identity already has an unexpired
+    /// one, which is returned instead.
+    pub async fn insert_approval(
+        &self,
+        approval: &PendingApproval,
+    ) -> Result<Option<PendingApproval>> {
+        let coll = self.db.collection::<PendingApproval>(PENDING_APPROVALS);
+
+        // Remove expired approvals.
+        coll.delete_many(
+            doc! {
+                "context": approval.context.to_bson()?,
+                "expires_at": {
+                    "$lte": Timestamp::now().to_bson()?,
+                }
+            },
+            None,
+        )
+        .await?;
+
+        // Only inserted if there is no pending approval yet, which is
+        // enforced by the unique index on the context.
+        let existing = coll
+            .find_one_and_update(
+                doc! {
+                    "context": approval.context.to_bson()?,
+                },
+                doc! {
+                    "$setOnInsert": approval.to_document()?,
+                },
+                FindOneAndUpdateOptions::builder()
+                    .upsert(true)
+                    .return_document(ReturnDocument::Before)
+                    .build(),
+            )
+            .await?;
+
+        Ok(existing)
+    }
+    /// Fetches the unexpired approval of the identity, if any.
+    pub async fn fetch_approval(
+        &self,
+        context: &IdentityContext,
+    ) -> Result<Option<PendingApproval>> {
+        let coll = self.db.collection::<PendingApproval>(PENDING_APPROVALS);
+
+        let approval = coll
+            .find_one(
+                doc! {
+                    "context": context.to_bson()?,
+                    "expires_at": {
+                        "$gt": Timestamp::now().to_bson()?,
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(approval)
+    }
+    /// Fetches all unexpired approvals, oldest first.
+    pub async fn fetch_approvals(&self) -> Result<Vec<PendingApproval>> {
+        let coll = self.db.collection::<PendingApproval>(PENDING_APPROVALS);
+
+        let mut cursor = coll
+            .find(
+                doc! {
+                    "expires_at": {
+                        "$gt": Timestamp::now().to_bson()?,
+                    }
+                },
+                FindOptions::builder()
+                    .sort(doc! { "requested_at": 1, "_id": 1 })
+                    .build(),
+            )
+            .await?;
+
+        let mut approvals = vec![];
+        while let Some(approval) = cursor.next().await {
+            approvals.push(approval?);
+        }
+
+        Ok(approvals)
+    }
+    /// Removes and returns the unexpired approval of the identity, unless it
+    /// was requested by the given admin.
+    pub async fn take_approval(
+        &self,
+        context: &IdentityContext,
+        approver: &str,
+    ) -> Result<Option<PendingApproval>> {
+        let coll = self.db.collection::<PendingApproval>(PENDING_APPROVALS);
+
+        let approval = coll
+            .find_one_and_delete(
+                doc! {
+                    "context": context.to_bson()?,
+                    "requested_by": {
+                        "$ne": approver,
+                    },
+                    "expires_at": {
+                        "$gt": Timestamp::now().to_bson()?,
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(approval)
+    }
+    /// Removes the unexpired approval of the identity. Returns whether one
+    /// existed.
+    pub async fn delete_approval(&self, context: &IdentityContext) -> Result<bool> {
+        let coll = self.db.collection::<PendingApproval>(PENDING_APPROVALS);
+
+        let res = coll
+            .delete_one(
+                doc! {
+                    "context": context.to_bson()?,
+                    "expires_at": {
+                        "$gt": Timestamp::now().to_bson()?,
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(res.deleted_count > 0)
+    }
+    /// Fetches the most recent event of the identity, including its id.
+    pub async fn fetch_latest_event(
+        &self,
+        context: &IdentityContext,
+    ) -> Result<Option<(String, Event)>> {
+        #[derive(Debug, Deserialize)]
+        struct EventWrapper {
+            #[serde(rename = "_id")]
+            id: bson::oid::ObjectId,
+            #[serde(flatten)]
+            event: Event,
+        }
+
+        let coll = self.db.collection::<EventWrapper>(EVENT_COLLECTION);
+
+        let wrapper = coll
+            .find_one(
+                doc! {
+                    "message.value.context": context.to_bson()?,
+                },
+                FindOneOptions::builder().sort(doc! { "_id": -1 }).build(),
+            )
+            .await?;
+
+        Ok(wrapper.map(|wrapper| (wrapper.id.to_hex(), wrapper.event)))
+    }
+    /// Fetches the events of the identity which were inserted after the event
+    /// with the given id, ordered from oldest to newest.
+    pub async fn fetch_events_after(
+        &self,
+        context: &IdentityContext,
+        after: &str,
+    ) -> Result<Vec<(String, NotificationMessage)>> {
+        #[derive(Debug, Deserialize)]
+        struct EventWrapper {
+            #[serde(rename = "_id")]
+            id: bson::oid::ObjectId,
+            #[serde(flatten)]
+            event: Event,
+        }
+
+        let after = bson::oid::ObjectId::parse_str(after)
+            .map_err(|_| anyhow!("Invalid event id: {}", after))?;
+
+        let coll = self.db.collection::<EventWrapper>(EVENT_COLLECTION);
+
+        let mut cursor = coll
+            .find(
+                doc! {
+                    "message.value.context": context.to_bson()?,
+                    "_id": {
+                        "$gt": after,
+                    },
+                },
+                FindOptions::builder().sort(doc! { "_id": 1 }).build(),
+            )
+            .await?;
+
+        let mut events = vec![];
+        while let Some(wrapper) = cursor.next().await {
+            let wrapper = wrapper?;
+            events.push((wrapper.id.to_hex(), wrapper.event.message));
+        }
+
+        Ok(events)
+    }
+    /// Fetches the events of the identity, newest first, as specified by the
+    /// query.
+    pub async fn fetch_event_history(
+        &self,
+        context: &IdentityContext,
+        query: &EventHistoryQuery,
+    ) -> Result<EventHistory> {
+        #[derive(Debug, Deserialize)]
+        struct EventWrapper {
+            #[serde(rename = "_id")]
+            id: bson::oid::ObjectId,
+            #[serde(flatten)]
+            event: Event,
+        }
+
+        query.validate()?;
+
+        let mut filter = doc! {
+            "message.value.context": context.to_bson()?,
+        };
+
+        if let Some(before) = &query.before {
+            filter.insert(
+                "_id",
+                doc! { "$lt": bson::oid::ObjectId::parse_str(before)? },
+            );
+        }
+
+        let mut range = Document::new();
+        if let Some(from) = query.from {
+            range.insert("$gte", from.to_bson()?);
+        }
+        if let Some(to) = query.to {
+            range.insert("$lte", to.to_bson()?);
+        }
+        if !range.is_empty() {
+            filter.insert("timestamp", range);
+        }
+
+        let limit = query
+            .limit
+            .unwrap_or(EVENT_HISTORY_DEFAULT_LIMIT)
+            .min(EVENT_HISTORY_MAX_LIMIT);
+
+        let coll = self.db.collection::<EventWrapper>(EVENT_COLLECTION);
+
+        // Fetch one more event in order to determine whether there is a next
+        // page.
+        let mut cursor = coll
+            .find(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "_id": -1 })
+                    .limit(limit as i64 + 1)
+                    .build(),
+            )
+            .await?;
+
+        let mut events = vec![];
+        while let Some(wrapper) = cursor.next().await {
+            let wrapper = wrapper?;
+            events.push(EventRecord {
+                id: wrapper.id.to_hex(),
+                timestamp: wrapper.event.timestamp,
+                message: wrapper.event.message,
+            });
+        }
+
+        let next = if events.len() > limit {
+            events.truncate(limit);
+            events.last().map(|event| event.id.clone())
+        } else {
+            None
+        };
+
+        Ok(EventHistory { events, next })
+    }
+    /// Aggregates the registrar statistics per chain and time bucket, ordered
+    /// by period.
+    pub async fn fetch_stats(&self, query: &StatsQuery) -> Result<Vec<StatsBucket>> {
+        #[derive(Debug, Deserialize)]
+        struct BucketId {
+            chain: ChainName,
+            period: String,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct RequestsRow {
+            #[serde(rename = "_id")]
+            id: BucketId,
+            requests: i64,
+            display_name_rejections: i64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct CountRow {
+            #[serde(rename = "_id")]
+            id: BucketId,
+            count: i64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct DurationRow {
+            duration: i64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct ManualRow {
+            #[serde(rename = "_id")]
+            id: BucketId,
+            identities: i64,
+            manual: i64,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct EventId {
+            chain: ChainName,
+            period: String,
+            #[serde(rename = "type")]
+            ty: String,
+            field: Option<String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct EventsRow {
+            #[serde(rename = "_id")]
+            id: EventId,
+            count: i64,
+        }
+
+        query.validate()?;
+
+        let interval = query.interval.unwrap_or(StatsInterval::Month);
+
+        // Converts a timestamp (in seconds) to the label of its bucket.
+        let period = |field: &str| {
+            doc! {
+                "$dateToString": {
+                    "format": interval.date_format(),
+                    "date": { "$toDate": { "$multiply": [format!("${}", field), 1000] } },
+                }
+            }
+        };
+
+        let mut range = doc! { "$ne": Bson::Null };
+        if let Some(from) = query.from {
+            range.insert("$gte", from.to_bson()?);
+        }
+        if let Some(to) = query.to {
+            range.insert("$lte", to.to_bson()?);
+        }
+
+        let chain_filter = |filter: &mut Document, field: &str| -> Result<()> {
+            if let Some(chain) = query.chain {
+                filter.insert(field, chain.as_str().to_bson()?);
+            }
+            Ok(())
+        };
+
+        let identities = self.db.collection::<Document>(IDENTITY_COLLECTION);
+        let events = self.db.collection::<Document>(EVENT_COLLECTION);
+        let mut buckets: BTreeMap<(String, String), StatsBucket> = BTreeMap::new();
+
+        // Received requests, including the ones whose display name is too
+        // similar to an existing one.
+        let mut filter = doc! { "inserted_timestamp": range.clone() };
+        chain_filter(&mut filter, "context.chain")?;
+
+        let mut cursor = identities
+            .aggregate(
+                [
+                    doc! { "$match": filter },
+                    doc! {
+                        "$group": {
+                            "_id": {
+                                "chain": "$context.chain",
+                                "period": period("inserted_timestamp"),
+                            },
+                            "requests": { "$sum": 1 },
+                            "display_name_rejections": {
+                                "$sum": {
+                                    "$cond": [{
+                                        "$gt": [{
+                                            "$size": {
+                                                "$filter": {
+                                                    "input": "$fields",
+                                                    "cond": {
+                                                        "$and": [
+                                                            { "$eq": ["$$this.challenge.type", "display_name_check"] },
+                                                            { "$eq": ["$$this.challenge.content.passed", false] },
+                                                        ]
+                                                    }
+                                                }
+                                            }
+                                        }, 0]
+                                    }, 1, 0]
+                                }
+                            },
+                        }
+                    },
+                ],
+                None,
+            )
+            .await?;
+
+        while let Some(doc) = cursor.next().await {
+            let row: RequestsRow = from_document(doc?)?;
+            let bucket = stats_bucket(&mut buckets, row.id.chain, row.id.period);
+
+            bucket.requests_received = row.requests as u64;
+            bucket.display_name_rejections = row.display_name_rejections as u64;
+        }
+
+        // Time to verify, bucketed by the time of the full verification. The
+        // durations are counted per bucket first, the median is then picked
+        // by its position among the sorted durations of the bucket, so the
+        // durations never have to be collected into a single document.
+        let mut filter = doc! { "completion_timestamp": range.clone() };
+        chain_filter(&mut filter, "context.chain")?;
+
+        let durations = [
+            doc! { "$match": filter },
+            doc! {
+                "$project": {
+                    "_id": 0,
+                    "chain": "$context.chain",
+                    "period": period("completion_timestamp"),
+                    "duration": {
+                        "$max": [{ "$subtract": ["$completion_timestamp", "$inserted_timestamp"] }, 0]
+                    },
+                }
+            },
+        ];
+
+        let mut counts = vec![];
+        let mut cursor = identities
+            .aggregate(
+                durations.iter().cloned().chain([doc! {
+                    "$group": {
+                        "_id": { "chain": "$chain", "period": "$period" },
+                        "count": { "$sum": 1 },
+                    }
+                }]),
+                None,
+            )
+            .await?;
+
+        while let Some(doc) = cursor.next().await {
+            counts.push(from_document::<CountRow>(doc?)?);
+        }
+
+        for row in counts {
+            // The middle element, or the two middle elements if the count is
+            // even.
+            let skip = (row.count - 1) / 2;
+            let limit = if row.count % 2 == 0 { 2 } else { 1 };
+
+            let mut cursor = identities
+                .aggregate(
+                    durations.iter().cloned().chain([
+                        doc! {
+                            "$match": {
+                                "chain": row.id.chain.as_str(),
+                                "period": row.id.period.as_str(),
+                            }
+                        },
+                        doc! { "$sort": { "duration": 1 } },
+                        doc! { "$skip": skip },
+                        doc! { "$limit": limit },
+                    ]),
+                    None,
+                )
+                .await?;
+
+            let mut middle = vec![];
+            while let Some(doc) = cursor.next().await {
+                middle.push(from_document::<DurationRow>(doc?)?.duration);
+            }
+
+            if middle.is_empty() {
+                continue;
+            }
+
+            let median = middle.iter().sum::<i64>() / middle.len() as i64;
+            let bucket = stats_bucket(&mut buckets, row.id.chain, row.id.period);
+
+            bucket.median_time_to_verify = Some(median as u64);
+        }
+
+        // Identities with (manual) verifications. Counted per identity, since
+        // full manual verifications cover all fields at once.
+        let mut filter = doc! {
+            "timestamp": range.clone(),
+            "message.type": {
+                "$in": [
+                    "field_verified",
+                    "manually_verified",
+                    "full_manual_verification",
+                ]
+            },
+        };
+        chain_filter(&mut filter, "message.value.context.chain")?;
+
+        let mut cursor = events
+            .aggregate(
+                [
+                    doc! { "$match": filter },
+                    doc! {
+                        "$group": {
+                            "_id": {
+                                "chain": "$message.value.context.chain",
+                                "period": period("timestamp"),
+                                "context": "$message.value.context",
+                            },
+                            "manual": {
+                                "$max": {
+                                    "$cond": [{
+                                        "$in": ["$message.type", ["manually_verified", "full_manual_verification"]]
+                                    }, 1, 0]
+                                }
+                            },
+                        }
+                    },
+                    doc! {
+                        "$group": {
+                            "_id": {
+                                "chain": "$_id.chain",
+                                "period": "$_id.period",
+                            },
+                            "identities": { "$sum": 1 },
+                            "manual": { "$sum": "$manual" },
+                        }
+                    },
+                ],
+                None,
+            )
+            .await?;
+
+        while let Some(doc) = cursor.next().await {
+            let row: ManualRow = from_document(doc?)?;
+            let bucket = stats_bucket(&mut buckets, row.id.chain, row.id.period);
+
+            bucket.manual_verifications = row.manual as u64;
+            bucket.manual_share = ratio(row.manual as u64, row.identities as u64);
+        }
+
+        // Verification attempts and issued judgements.
+        let mut filter = doc! {
+            "timestamp": range,
+            "message.type": {
+                "$in": [
+                    "field_verified",
+                    "field_verification_failed",
+                    "judgement_provided",
+                ]
+            },
+        };
+        chain_filter(&mut filter, "message.value.context.chain")?;
+
+        let mut cursor = events
+            .aggregate(
+                [
+                    doc! { "$match": filter },
+                    doc! {
+                        "$group": {
+                            "_id": {
+                                "chain": "$message.value.context.chain",
+                                "period": period("timestamp"),
+                                "type": "$message.type",
+                                "field": "$message.value.field.type",
+                            },
+                            "count": { "$sum": 1 },
+                        }
+                    },
+                ],
+                None,
+            )
+            .await?;
+
+        while let Some(doc) = cursor.next().await {
+            let row: EventsRow = from_document(doc?)?;
+            let bucket = stats_bucket(&mut buckets, row.id.chain, row.id.period);
+
+            let count = row.count as u64;
+            match (row.id.ty.as_str(), row.id.field) {
+                ("field_verified", Some(field)) => {
+                    bucket.fields.entry(field).or_default().verified += count
+                }
+                ("field_verification_failed", Some(field)) => {
+                    bucket.fields.entry(field).or_default().failed += count
+                }
+                ("judgement_provided", _) => bucket.judgements_issued += count,
+                _ => {}
+            }
+        }
+
+        let mut stats: Vec<StatsBucket> = buckets.into_values().collect();
+        for bucket in &mut stats {
+            for field in bucket.fields.values_mut() {
+                field.success_rate = ratio(field.verified, field.verified + field.failed);
+            }
+        }
+
+        Ok(stats)
+    }
     async fn insert_event<T: Into<Event>>(
         &self,
         event: T,
@@ -3,6 +3,8 @@ use crate::database::Database;
 use crate::primitives::{ChainName, IdentityContext, JudgementState};
 use crate::{DisplayNameConfig, Result};
 use strsim::jaro;
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::confusable_detection::skeleton;
 
 const VIOLATIONS_CAP: usize = 5;
 
@@ -75,6 +77,15 @@ impl DisplayNameVerifier {
 }
 
 fn is_too_similar(existing: &str, new: &str, limit: f64) -> bool {
+    let existing = normalize(existing);
+    let new = normalize(new);
+
+    // Names which look the same are always too similar, no matter their
+    // Jaro score (e.g. with a Cyrillic 'о' instead of a Latin 'o').
+    if is_confusable(&existing, &new) {
+        return true;
+    }
+
     let name_str = existing.to_lowercase();
     let account_str = new.to_lowercase();
 
@@ -86,6 +97,45 @@ fn is_too_similar(existing: &str, new: &str, limit: f64) -> bool {
     similarities.iter().any(|&s| s > limit)
 }
 
+/// Applies NFKC (e.g. fullwidth to regular letters) and removes invisible and
+/// bidi control characters.
+fn normalize(name: &str) -> String {
+    name.nfkc().filter(|&c| !is_invisible(c)).collect()
+}
+
+// Control and format characters which are not rendered, or only change the
+// direction of the text.
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00AD}' // Soft hyphen
+                | '\u{034F}' // Combining grapheme joiner
+                | '\u{061C}' // Arabic letter mark
+                | '\u{115F}' | '\u{1160}' | '\u{3164}' | '\u{FFA0}' // Hangul fillers
+                | '\u{17B4}' | '\u{17B5}' // Khmer inherent vowels
+                | '\u{180B}'..='\u{180F}' // Mongolian variation selectors
+                | '\u{200B}'..='\u{200F}' // Zero width characters, LRM and RLM
+                | '\u{202A}'..='\u{202E}' // Bidi embeddings and overrides
+                | '\u{2060}'..='\u{206F}' // Word joiner, bidi isolates
+                | '\u{FE00}'..='\u{FE0F}' // Variation selectors
+                | '\u{FEFF}' // Zero width no-break space
+                | '\u{1D173}'..='\u{1D17A}' // Musical formatting
+                | '\u{E0000}'..='\u{E0FFF}' // Tags, variation selectors
+        )
+}
+
+/// Whether the (normalized) names map to the same confusables skeleton as
+/// defined in Unicode TR39, with or without lowercasing them first.
+fn is_confusable(left: &str, right: &str) -> bool {
+    fn skeleton_of(name: &str) -> String {
+        skeleton(name).flat_map(char::to_lowercase).collect()
+    }
+
+    skeleton_of(left) == skeleton_of(right)
+        || skeleton_of(&left.to_lowercase()) == skeleton_of(&right.to_lowercase())
+}
+
 fn jaro_words(left: &str, right: &str, delimiter: &[&str]) -> f64 {
     fn splitter<'a>(string: &'a str, delimiter: &[&str]) -> Vec<&'a str> {
         let mut all = vec![];
@@ -122,5 +172,53 @@ fn jaro_words(left: &str, right: &str, delimiter: &[&str]) -> f64 {
         total += temp;
     }
 
-    total as f64 / left_words.len().max(right_words.len()) as f64
+    total / left_words.len().max(right_words.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_names() {
+        // Fullwidth letters.
+        assert_eq!(normalize("\u{FF21}lice"), "Alice");
+        // Zero width and bidi control characters.
+        assert_eq!(normalize("Al\u{200B}ice\u{200D}"), "Alice");
+        assert_eq!(normalize("\u{202E}Alice\u{202C}"), "Alice");
+        assert_eq!(normalize("\u{2067}Alice\u{2069}"), "Alice");
+        // Regular names are unchanged.
+        assert_eq!(normalize("Alice Bob-Eve"), "Alice Bob-Eve");
+        assert_eq!(normalize("Аlice"), "Аlice");
+    }
+
+    #[test]
+    fn confusable_names() {
+        for name in [
+            // Cyrillic 'о'.
+            "P\u{043E}lkadot",
+            // Greek 'Ρ' and Cyrillic 'о'.
+            "\u{03A1}\u{043E}lkadot",
+            // Capital 'I' instead of 'l'.
+            "PoIkadot",
+            // Zero width space.
+            "Polka\u{200B}dot",
+            // Fullwidth letters.
+            "\u{FF30}\u{FF4F}\u{FF4C}\u{FF4B}\u{FF41}\u{FF44}\u{FF4F}\u{FF54}",
+            "POLKADOT",
+        ] {
+            assert!(
+                is_confusable(&normalize(name), &normalize("Polkadot")),
+                "{}",
+                name
+            );
+            // Reported regardless of the Jaro limit.
+            assert!(is_too_similar("Polkadot", name, 1.0), "{}", name);
+        }
+
+        for name in ["Kusama", "Polkadot Fan", "Polkadots"] {
+            assert!(!is_confusable(&normalize(name), &normalize("Polkadot")));
+            assert!(!is_too_similar("Polkadot", name, 1.0), "{}", name);
+        }
+    }
 }
@@ -0,0 +1,439 @@
+use crate::database::Database;
+use crate::metrics::serve_metrics;
+use crate::primitives::{ChainName, Timestamp};
+use crate::{HealthConfig, Result};
+use actix_web::{web, App, HttpResponse, HttpServer};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+// Upper limit for the database ping, the driver itself only gives up after
+// its server selection timeout.
+const DATABASE_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Ordered from best to worst.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The worst status of all components.
+    pub status: Status,
+    pub database: DatabaseStatus,
+    pub watchers: BTreeMap<String, WatcherStatus>,
+    pub adapters: BTreeMap<String, AdapterStatus>,
+    pub notifier: Option<NotifierStatus>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseStatus {
+    pub status: Status,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WatcherStatus {
+    pub status: Status,
+    pub connected: bool,
+    // Seconds since the connection was established.
+    pub connection_age: Option<u64>,
+    pub last_message: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AdapterStatus {
+    pub status: Status,
+    pub last_success: Option<Timestamp>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NotifierStatus {
+    pub status: Status,
+    pub last_run: Timestamp,
+    // Seconds since the last completed run of the notifier loop.
+    pub lag: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct WatcherState {
+    connected_since: Option<Timestamp>,
+    last_message: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone)]
+struct AdapterState {
+    // Polling interval in seconds.
+    interval: u64,
+    registered: Timestamp,
+    last_success: Option<Timestamp>,
+    last_error: Option<String>,
+}
+
+/// Process wide state of the components, as reported by `/readyz`.
+#[derive(Default)]
+pub struct Health {
+    config: RwLock<HealthConfig>,
+    watchers: RwLock<HashMap<ChainName, WatcherState>>,
+    adapters: RwLock<HashMap<String, AdapterState>>,
+    notifier: RwLock<Option<Timestamp>>,
+}
+
+impl Health {
+    pub fn configure(&self, config: HealthConfig) {
+        *self.config.write().unwrap() = config;
+    }
+    pub fn watcher_connected(&self, chain: ChainName) {
+        let now = Timestamp::now();
+        self.watchers.write().unwrap().insert(
+            chain,
+            WatcherState {
+                connected_since: Some(now),
+                last_message: Some(now),
+            },
+        );
+    }
+    pub fn watcher_disconnected(&self, chain: ChainName) {
+        self.watchers
+            .write()
+            .unwrap()
+            .entry(chain)
+            .or_default()
+            .connected_since = None;
+    }
+    pub fn watcher_message(&self, chain: ChainName) {
+        self.watchers
+            .write()
+            .unwrap()
+            .entry(chain)
+            .or_default()
+            .last_message = Some(Timestamp::now());
+    }
+    pub fn adapter_started(&self, name: &str, interval: u64) {
+        self.adapters.write().unwrap().insert(
+            name.to_string(),
+            AdapterState {
+                interval,
+                registered: Timestamp::now(),
+                last_success: None,
+                last_error: None,
+            },
+        );
+    }
+    pub fn adapter_fetched<T>(&self, name: &str, result: &Result<T>) {
+        let mut adapters = self.adapters.write().unwrap();
+        if let Some(adapter) = adapters.get_mut(name) {
+            match result {
+                Ok(_) => {
+                    adapter.last_success = Some(Timestamp::now());
+                    adapter.last_error = None;
+                }
+                Err(err) => adapter.last_error = Some(err.to_string()),
+            }
+        }
+    }
+    pub fn notifier_ran(&self) {
+        *self.notifier.write().unwrap() = Some(Timestamp::now());
+    }
+    fn report(&self, now: Timestamp, database: DatabaseStatus) -> HealthReport {
+        let config = self.config.read().unwrap().clone();
+        let now = now.raw();
+
+        let watchers: BTreeMap<String, WatcherStatus> = self
+            .watchers
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(chain, state)| {
+                // Disconnected watchers do not receive messages either.
+                let silence = state
+                    .last_message
+                    .map(|last| now.saturating_sub(last.raw()))
+                    .unwrap_or(u64::MAX);
+
+                let status = if silence > config.watcher_down_timeout {
+                    Status::Down
+                } else if state.connected_since.is_none() || silence > config.watcher_timeout {
+                    Status::Degraded
+                } else {
+                    Status::Ok
+                };
+
+                (
+                    chain.as_str().to_string(),
+                    WatcherStatus {
+                        status,
+                        connected: state.connected_since.is_some(),
+                        connection_age: state
+                            .connected_since
+                            .map(|since| now.saturating_sub(since.raw())),
+                        last_message: state.last_message,
+                    },
+                )
+            })
+            .collect();
+
+        let adapters: BTreeMap<String, AdapterStatus> = self
+            .adapters
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, state)| {
+                // Adapters are only expected to fetch once per interval.
+                let last = state.last_success.unwrap_or(state.registered);
+                let elapsed = now.saturating_sub(last.raw());
+                let status = if elapsed > state.interval + config.adapter_down_timeout {
+                    Status::Down
+                } else if elapsed > state.interval + config.adapter_timeout {
+                    Status::Degraded
+                } else {
+                    Status::Ok
+                };
+
+                (
+                    name.clone(),
+                    AdapterStatus {
+                        status,
+                        last_success: state.last_success,
+                        last_error: state.last_error.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        let notifier = self.notifier.read().unwrap().map(|last_run| {
+            let lag = now.saturating_sub(last_run.raw());
+
+            NotifierStatus {
+                status: if lag > config.notifier_down_timeout {
+                    Status::Down
+                } else if lag > config.notifier_timeout {
+                    Status::Degraded
+                } else {
+                    Status::Ok
+                },
+                last_run,
+                lag,
+            }
+        });
+
+        let status = watchers
+            .values()
+            .map(|watcher| watcher.status)
+            .chain(adapters.values().map(|adapter| adapter.status))
+            .chain(notifier.iter().map(|notifier| notifier.status))
+            .fold(database.status, Status::max);
+
+        HealthReport {
+            status,
+            database,
+            watchers,
+            adapters,
+            notifier,
+        }
+    }
+}
+
+pub fn health() -> &'static Health {
+    static HEALTH: OnceLock<Health> = OnceLock::new();
+    HEALTH.get_or_init(Health::default)
+}
+
+pub fn monitoring_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/livez", web::get().to(livez))
+        .route("/readyz", web::get().to(readyz))
+        .route("/metrics", web::get().to(serve_metrics));
+}
+
+/// Serves the monitoring endpoints on a separate port, for instances without
+/// an API server.
+pub fn run_monitoring_server(address: &str, db: Database) -> Result<()> {
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(db.clone()))
+            .configure(monitoring_routes)
+    })
+    .bind(address)?;
+
+    actix::spawn(async move {
+        if let Err(err) = server.run().await {
+            error!("Monitoring server stopped: {:?}", err);
+        }
+    });
+
+    Ok(())
+}
+
+/// The process is able to serve requests.
+async fn livez() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": Status::Ok }))
+}
+
+/// Reports the status of each component. Fails if any component is down,
+/// e.g. the database is unavailable or a Watcher was silent for too long.
+/// Degraded components are reported as such.
+async fn readyz(db: web::Data<Database>) -> HttpResponse {
+    let database = match tokio::time::timeout(DATABASE_PING_TIMEOUT, db.connectivity_check()).await
+    {
+        Ok(Ok(())) => DatabaseStatus {
+            status: Status::Ok,
+            error: None,
+        },
+        Ok(Err(err)) => DatabaseStatus {
+            status: Status::Down,
+            error: Some(err.to_string()),
+        },
+        Err(_) => DatabaseStatus {
+            status: Status::Down,
+            error: Some("Database ping timed out".to_string()),
+        },
+    };
+
+    let report = health().report(Timestamp::now(), database);
+
+    if report.status == Status::Down {
+        HttpResponse::ServiceUnavailable().json(report)
+    } else {
+        HttpResponse::Ok().json(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database_ok() -> DatabaseStatus {
+        DatabaseStatus {
+            status: Status::Ok,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn health_report() {
+        let health = Health::default();
+        let now = Timestamp::now();
+
+        let report = health.report(now, database_ok());
+        assert_eq!(report.status, Status::Ok);
+        assert!(report.watchers.is_empty());
+        assert_eq!(report.notifier, None);
+
+        health.watcher_connected(ChainName::Polkadot);
+        health.adapter_started("email", 5);
+        health.adapter_fetched("email", &Ok(()));
+        health.notifier_ran();
+
+        let report = health.report(now, database_ok());
+        assert_eq!(report.status, Status::Ok);
+        assert!(report.watchers["polkadot"].connected);
+        assert_eq!(report.adapters["email"].status, Status::Ok);
+        assert_eq!(report.notifier.unwrap().status, Status::Ok);
+
+        // Failed fetches only degrade the adapter once the timeout is
+        // exceeded.
+        health.adapter_fetched::<()>("email", &Err(anyhow!("login failed")));
+        let report = health.report(now, database_ok());
+        assert_eq!(report.status, Status::Ok);
+        assert_eq!(
+            report.adapters["email"].last_error,
+            Some("login failed".to_string())
+        );
+
+        // The notifier would be down by then.
+        let config = HealthConfig {
+            notifier_down_timeout: 3600,
+            ..HealthConfig::default()
+        };
+        health.configure(config.clone());
+
+        let later = Timestamp::with_offset(5 + config.adapter_timeout + 1);
+        let report = health.report(later, database_ok());
+        assert_eq!(report.adapters["email"].status, Status::Degraded);
+        assert_eq!(report.status, Status::Degraded);
+
+        // Disconnected watcher.
+        health.adapter_fetched("email", &Ok(()));
+        health.watcher_disconnected(ChainName::Polkadot);
+        let report = health.report(now, database_ok());
+        assert_eq!(report.status, Status::Degraded);
+        assert!(!report.watchers["polkadot"].connected);
+        assert_eq!(report.watchers["polkadot"].connection_age, None);
+
+        // Unavailable database.
+        let report = health.report(
+            now,
+            DatabaseStatus {
+                status: Status::Down,
+                error: Some("unreachable".to_string()),
+            },
+        );
+        assert_eq!(report.status, Status::Down);
+    }
+
+    #[test]
+    fn configurable_thresholds() {
+        let health = Health::default();
+        health.watcher_connected(ChainName::Kusama);
+        health.notifier_ran();
+
+        let later = Timestamp::with_offset(30);
+        let report = health.report(later, database_ok());
+        assert_eq!(report.status, Status::Ok);
+
+        health.configure(HealthConfig {
+            watcher_timeout: 10,
+            adapter_timeout: 10,
+            notifier_timeout: 10,
+            ..HealthConfig::default()
+        });
+
+        let report = health.report(later, database_ok());
+        assert_eq!(report.watchers["kusama"].status, Status::Degraded);
+        assert_eq!(report.notifier.as_ref().unwrap().status, Status::Degraded);
+        assert!(report.notifier.unwrap().lag >= 30);
+
+        health.configure(HealthConfig {
+            watcher_down_timeout: 20,
+            notifier_down_timeout: 20,
+            ..HealthConfig::default()
+        });
+
+        let report = health.report(later, database_ok());
+        assert_eq!(report.watchers["kusama"].status, Status::Down);
+        assert_eq!(report.notifier.unwrap().status, Status::Down);
+        assert_eq!(report.status, Status::Down);
+    }
+
+    #[test]
+    fn critical_components() {
+        let health = Health::default();
+        let config = HealthConfig::default();
+        health.watcher_connected(ChainName::Polkadot);
+        health.adapter_started("email", 5);
+
+        // A disconnected watcher is degraded, until it was silent for too
+        // long.
+        health.watcher_disconnected(ChainName::Polkadot);
+        let report = health.report(Timestamp::now(), database_ok());
+        assert_eq!(report.watchers["polkadot"].status, Status::Degraded);
+
+        let later = Timestamp::with_offset(config.watcher_down_timeout + 1);
+        let report = health.report(later, database_ok());
+        assert_eq!(report.watchers["polkadot"].status, Status::Down);
+        assert_eq!(report.status, Status::Down);
+
+        // Adapters which keep failing are down.
+        health.watcher_connected(ChainName::Polkadot);
+        health.adapter_fetched::<()>("email", &Err(anyhow!("login failed")));
+        let later = Timestamp::with_offset(5 + config.adapter_down_timeout + 1);
+        let report = health.report(later, database_ok());
+        assert_eq!(report.adapters["email"].status, Status::Down);
+        assert_eq!(report.status, Status::Down);
+    }
+}
@@ -8,34 +8,59 @@ extern crate serde;
 extern crate async_trait;
 
 use actix::clock::sleep;
-use adapters::matrix::MatrixHandle;
+use adapters::admin::Role;
+use adapters::matrix::MatrixAdmin;
 use primitives::ChainName;
+use std::collections::HashMap;
 use std::fs;
+use std::net::IpAddr;
 use std::time::Duration;
 
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
 
+// Minimum age of a challenge in seconds before users can regenerate it.
+const DEFAULT_REGENERATION_COOLDOWN: u64 = 60;
+
+pub use cli::Cli;
+
 use adapters::run_adapters;
 use api::run_rest_api_server;
 use connector::run_connector;
 use database::Database;
+use health::{health, run_monitoring_server};
 use notifier::run_session_notifier;
+use webhooks::WebhookDispatcher;
 
 mod adapters;
 mod api;
+mod cli;
 mod connector;
 mod database;
 mod display_name;
+mod health;
+mod metrics;
 mod notifier;
 mod primitives;
 #[cfg(test)]
 mod tests;
+mod webhooks;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 struct Config {
     pub log_level: LogLevel,
     pub db: DatabaseConfig,
+    pub failed_attempts: Option<FailedAttemptsConfig>,
+    // Lifetime of challenges in seconds. Challenges never expire if not set.
+    pub challenge_ttl: Option<u64>,
+    // Minimum age of a challenge in seconds before users can regenerate it.
+    // Defaults to 60 seconds.
+    pub regeneration_cooldown: Option<u64>,
+    // Require a second admin to approve full manual verifications.
+    pub approval: Option<ApprovalConfig>,
+    pub account_key: Option<AccountKeyConfig>,
+    // Thresholds of the `/readyz` endpoint.
+    pub health: Option<HealthConfig>,
     pub instance: InstanceType,
 }
 
@@ -61,6 +86,8 @@ impl LogLevel {
     }
 }
 
+// Only constructed once, when parsing the config.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "role", content = "config")]
 enum InstanceType {
@@ -83,12 +110,189 @@ struct DatabaseConfig {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct FailedAttemptsConfig {
+    // Failed attempts (per field) until the field gets locked.
+    pub max_attempts: usize,
+    // Lock duration in seconds.
+    pub cooldown: u64,
+    // Alert the admins (via Matrix) when a field gets locked.
+    pub notify_admins: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ApprovalConfig {
+    // Seconds within which a full manual verification must be approved.
+    pub window: u64,
+    // Credentials of each admin, as recorded in the audit log (e.g.
+    // `@alice:matrix.org`, `token:alice` or `cert:alice`). Only listed admins
+    // can request and approve full manual verifications, and all credentials
+    // of an admin count as the same admin.
+    #[serde(default)]
+    pub admins: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountKeyConfig {
+    // Only issue judgements once the requester signed a nonce with the
+    // account key.
+    pub required: bool,
+}
+
+// Missing thresholds fall back to their defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct HealthConfig {
+    // Seconds without a message until a Watcher connection is degraded.
+    pub watcher_timeout: u64,
+    // Seconds, in addition to the polling interval, without a successful
+    // fetch until an adapter is degraded.
+    pub adapter_timeout: u64,
+    // Seconds since the last run of the session notifier loop until it is
+    // degraded.
+    pub notifier_timeout: u64,
+    // Like the timeouts above, but until the component is down, which fails
+    // `/readyz`.
+    pub watcher_down_timeout: u64,
+    pub adapter_down_timeout: u64,
+    pub notifier_down_timeout: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            watcher_timeout: 180,
+            adapter_timeout: 300,
+            notifier_timeout: 30,
+            watcher_down_timeout: 600,
+            adapter_down_timeout: 1800,
+            notifier_down_timeout: 300,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct NotifierConfig {
     pub api_address: String,
     pub cors_allow_origin: Vec<String>,
     pub display_name: DisplayNameConfig,
+    pub pgp: Option<PgpConfig>,
+    // Deprecated: accept second challenges without an identity context.
+    #[serde(default)]
+    pub allow_unscoped_second_challenge: bool,
+    pub admin_api: Option<AdminApiConfig>,
+    pub webhooks: Option<WebhooksConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimitConfig {
+    // Proxies whose `X-Forwarded-For` header is trusted, e.g. a load balancer
+    // in front of the API server.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+    // Limits per path, e.g. `/api/check_display_name`. Other paths are not
+    // limited.
+    #[serde(default)]
+    pub routes: HashMap<String, RouteRateLimit>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RouteRateLimit {
+    pub per_ip: Option<RateLimit>,
+    // Keyed by the identity (`context`) or, if not specified, the field
+    // (`entry`) in the JSON body of the request.
+    pub per_identity: Option<RateLimit>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimit {
+    // Sustained rate of requests.
+    pub per_minute: u32,
+    // Requests which can be sent at once. Defaults to `per_minute`.
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebhooksConfig {
+    pub subscribers: Vec<WebhookConfig>,
+    // Delivery attempts until an event is recorded as dead letter. Defaults
+    // to 5.
+    pub max_attempts: Option<usize>,
+    // Seconds until the first retry, doubled after each attempt. Defaults to
+    // 2.
+    pub backoff: Option<u64>,
+    // Request timeout in seconds. Defaults to 10.
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebhookConfig {
+    // Identifies the subscriber in logs, metrics and dead letters.
+    pub name: String,
+    pub url: String,
+    // Key of the HMAC-SHA256 signature of the request body.
+    pub secret: String,
+    // Types of `NotificationMessage`, e.g. `identity_fully_verified`. All
+    // events if empty.
+    #[serde(default)]
+    pub events: Vec<String>,
+    // All chains if empty.
+    #[serde(default)]
+    pub chains: Vec<ChainName>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AdminApiConfig {
+    // Internal listener for the `/admin` endpoints, which authenticates
+    // clients by their bearer token. Must not be publicly reachable.
+    pub address: Option<String>,
+    // Bearer tokens accepted by the `/admin` endpoints of the internal
+    // listener.
+    #[serde(default)]
+    pub tokens: Vec<AdminToken>,
+    // Separate listener for the `/admin` endpoints, which authenticates
+    // clients by their certificate (mTLS).
+    pub tls: Option<AdminTlsConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AdminToken {
+    // Recorded in the audit log.
+    pub name: String,
+    pub token: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AdminTlsConfig {
+    pub address: String,
+    // PEM files of the server certificate (chain) and its private key.
+    pub cert: String,
+    pub key: String,
+    // PEM file of the CA which issues the client certificates.
+    pub client_ca: String,
+    // Role of all clients with a valid certificate.
+    pub client_role: Role,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PgpConfig {
+    // HKP keyserver for fetching public keys. Defaults to `keys.openpgp.org`.
+    pub keyserver: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -98,7 +302,13 @@ pub struct AdapterConfig {
     pub matrix: MatrixConfig,
     pub twitter: TwitterConfig,
     pub email: EmailConfig,
+    pub telegram: Option<TelegramConfig>,
+    pub web: Option<WebConfig>,
+    pub github: Option<GithubConfig>,
     pub display_name: DisplayNameConfig,
+    // Exposes `/metrics`, `/livez` and `/readyz` on a separate port (e.g.
+    // `0.0.0.0:9100`).
+    pub monitoring_address: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -121,7 +331,7 @@ pub struct MatrixConfig {
     pub username: String,
     pub password: String,
     pub db_path: String,
-    pub admins: Option<Vec<MatrixHandle>>,
+    pub admins: Option<Vec<MatrixAdmin>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -147,13 +357,49 @@ pub struct EmailConfig {
     pub request_interval: u64,
 }
 
-fn open_config() -> Result<Config> {
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    pub token: String,
+    // Defaults to the official Telegram Bot API.
+    pub api_base: Option<String>,
+    pub request_interval: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebConfig {
+    pub enabled: bool,
+    // DNS-over-HTTPS resolver (JSON API). Defaults to Google's public resolver.
+    pub dns_resolver: Option<String>,
+    // Defaults to `https://{domain}/.well-known/polkadot-identity`.
+    pub well_known_url: Option<String>,
+    pub request_interval: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct GithubConfig {
+    pub enabled: bool,
+    // Defaults to the official GitHub API.
+    pub api_base: Option<String>,
+    // Optional access token for a higher API rate limit.
+    pub token: Option<String>,
+    pub request_interval: u64,
+}
+
+fn open_config(path: Option<&str>) -> Result<Config> {
     // Open config file.
-    let content = fs::read_to_string("config.yaml")
-        .or_else(|_| fs::read_to_string("/etc/registrar/config.yaml"))
-        .map_err(|_| {
-            anyhow!("Failed to open config at 'config.yaml' or '/etc/registrar/config.yaml'.")
-        })?;
+    let content = match path {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to open config at '{}': {:?}", path, err))?,
+        None => fs::read_to_string("config.yaml")
+            .or_else(|_| fs::read_to_string("/etc/registrar/config.yaml"))
+            .map_err(|_| {
+                anyhow!("Failed to open config at 'config.yaml' or '/etc/registrar/config.yaml'.")
+            })?,
+    };
 
     // Parse config file as JSON.
     let config = serde_yaml::from_str::<Config>(&content)
@@ -165,11 +411,25 @@ fn open_config() -> Result<Config> {
 async fn config_adapter_listener(db: Database, config: AdapterConfig) -> Result<()> {
     let watchers = config.watcher.clone();
     let dn_config = config.display_name.clone();
+
+    if let Some(address) = &config.monitoring_address {
+        info!("Starting monitoring server on {}", address);
+        run_monitoring_server(address, db.clone())?;
+    }
+
     run_adapters(config.clone(), db.clone()).await?;
     run_connector(db, watchers, dn_config).await
 }
 
 async fn config_session_notifier(db: Database, not_config: NotifierConfig) -> Result<()> {
+    if let Some(config) = not_config.webhooks.clone() {
+        info!(
+            subscribers = config.subscribers.len(),
+            "Delivering events to webhooks"
+        );
+        WebhookDispatcher::new(db.clone(), config)?.run();
+    }
+
     let lookup = run_rest_api_server(not_config, db.clone()).await?;
 
     actix::spawn(async move { run_session_notifier(db, lookup).await });
@@ -177,21 +437,92 @@ async fn config_session_notifier(db: Database, not_config: NotifierConfig) -> Re
     Ok(())
 }
 
-pub async fn run() -> Result<()> {
-    let root = open_config()?;
-    let (db_config, instance) = (root.db, root.instance);
+async fn open_database(root: &Config) -> Result<Database> {
+    info!("Initializing connection to database");
+    let mut db = Database::new(&root.db.uri, &root.db.name).await?;
+    db.connectivity_check().await?;
 
+    if let Some(config) = root.failed_attempts.clone() {
+        info!(
+            max_attempts = config.max_attempts,
+            cooldown = config.cooldown,
+            "Locking fields after too many failed attempts"
+        );
+        db = db.with_failed_attempts(config);
+    }
+
+    if let Some(ttl) = root.challenge_ttl {
+        info!(ttl, "Challenges expire after the specified TTL");
+        db = db.with_challenge_ttl(ttl);
+    }
+
+    db = db.with_regeneration_cooldown(
+        root.regeneration_cooldown
+            .unwrap_or(DEFAULT_REGENERATION_COOLDOWN),
+    );
+
+    if let Some(config) = &root.approval {
+        info!(
+            window = config.window,
+            "Full manual verifications require the approval of a second admin"
+        );
+        // The user of the CLI is taken from the environment.
+        if let Some(credential) = config
+            .admins
+            .values()
+            .flatten()
+            .find(|credential| credential.starts_with("cli:"))
+        {
+            return Err(anyhow!(
+                "CLI credentials cannot be used for approvals: {}",
+                credential
+            ));
+        }
+
+        db = db
+            .with_approval_window(config.window)
+            .with_approval_admins(&config.admins);
+    }
+
+    if root
+        .account_key
+        .as_ref()
+        .map(|c| c.required)
+        .unwrap_or(false)
+    {
+        info!("Judgements require a proof of the account key");
+        db = db.with_required_account_key();
+    }
+
+    // Session notifiers do not know the watched chains, all supported chains
+    // are assumed.
+    let watchers = match &root.instance {
+        InstanceType::AdapterListener(config) => Some(&config.watcher),
+        InstanceType::SingleInstance(config) => Some(&config.adapter.watcher),
+        InstanceType::SessionNotifier(_) => None,
+    };
+
+    if let Some(watchers) = watchers {
+        db = db.with_chains(watchers.iter().map(|w| w.network).collect());
+    }
+
+    Ok(db)
+}
+
+async fn serve(root: Config) -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(format!("system={}", root.log_level.as_str()))
         .init();
 
     info!("Starting registrar service");
 
-    info!("Initializing connection to database");
-    let db = Database::new(&db_config.uri, &db_config.name).await?;
-    db.connectivity_check().await?;
+    let db = open_database(&root).await?;
+
+    if let Some(config) = root.health.clone() {
+        health().configure(config);
+    }
 
-    match instance {
+    match root.instance {
         InstanceType::AdapterListener(config) => {
             info!("Starting adapter listener instance");
             config_adapter_listener(db, config).await?;
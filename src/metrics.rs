@@ -0,0 +1,180 @@
+use crate::database::Database;
+use crate::primitives::ChainName;
+use crate::Result;
+use actix_web::{web, HttpResponse};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+
+/// Process wide metrics, exposed on `/metrics` in the Prometheus text format.
+pub struct Metrics {
+    registry: Registry,
+    /// Judgement requests which have not been judged yet, per chain.
+    pub pending_requests: IntGaugeVec,
+    /// Verification attempts per adapter and outcome (`verified`, `failed`
+    /// or `expired`).
+    pub verifications: IntCounterVec,
+    pub adapter_poll_duration: HistogramVec,
+    pub adapter_poll_errors: IntCounterVec,
+    /// Whether the connection to the Watcher is up (`1`), per chain.
+    pub watcher_connected: IntGaugeVec,
+    pub watcher_reconnect_attempts: IntCounterVec,
+    pub judgements_submitted: IntCounterVec,
+    pub judgements_acknowledged: IntCounterVec,
+    pub account_state_subscribers: IntGauge,
+    /// Seconds between the creation of the latest events and their
+    /// processing by the session notifier.
+    pub event_log_lag: IntGauge,
+    /// Webhook deliveries per subscriber and outcome (`delivered` or
+    /// `failed`).
+    pub webhook_deliveries: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Result<Self> {
+        let metrics = Metrics {
+            registry: Registry::new_custom(Some("registrar".to_string()), None)?,
+            pending_requests: IntGaugeVec::new(
+                Opts::new(
+                    "pending_requests",
+                    "Judgement requests which are not judged yet",
+                ),
+                &["chain"],
+            )?,
+            verifications: IntCounterVec::new(
+                Opts::new("verifications_total", "Verification attempts of fields"),
+                &["adapter", "outcome"],
+            )?,
+            adapter_poll_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "adapter_poll_duration_seconds",
+                    "Duration of fetching messages from the adapter",
+                ),
+                &["adapter"],
+            )?,
+            adapter_poll_errors: IntCounterVec::new(
+                Opts::new(
+                    "adapter_poll_errors_total",
+                    "Failures to fetch messages from the adapter",
+                ),
+                &["adapter"],
+            )?,
+            watcher_connected: IntGaugeVec::new(
+                Opts::new("watcher_connected", "Connection state of the Watcher"),
+                &["chain"],
+            )?,
+            watcher_reconnect_attempts: IntCounterVec::new(
+                Opts::new(
+                    "watcher_reconnect_attempts_total",
+                    "Attempts to reconnect to the Watcher",
+                ),
+                &["chain"],
+            )?,
+            judgements_submitted: IntCounterVec::new(
+                Opts::new(
+                    "judgements_submitted_total",
+                    "Judgements submitted to the Watcher",
+                ),
+                &["chain"],
+            )?,
+            judgements_acknowledged: IntCounterVec::new(
+                Opts::new(
+                    "judgements_acknowledged_total",
+                    "Judgements acknowledged by the Watcher",
+                ),
+                &["chain"],
+            )?,
+            account_state_subscribers: IntGauge::new(
+                "account_state_subscribers",
+                "Active subscriptions to account state updates",
+            )?,
+            event_log_lag: IntGauge::new(
+                "event_log_lag_seconds",
+                "Age of the latest events processed by the session notifier",
+            )?,
+            webhook_deliveries: IntCounterVec::new(
+                Opts::new(
+                    "webhook_deliveries_total",
+                    "Events delivered to webhook subscribers",
+                ),
+                &["subscriber", "outcome"],
+            )?,
+        };
+
+        let registry = &metrics.registry;
+        registry.register(Box::new(metrics.pending_requests.clone()))?;
+        registry.register(Box::new(metrics.verifications.clone()))?;
+        registry.register(Box::new(metrics.adapter_poll_duration.clone()))?;
+        registry.register(Box::new(metrics.adapter_poll_errors.clone()))?;
+        registry.register(Box::new(metrics.watcher_connected.clone()))?;
+        registry.register(Box::new(metrics.watcher_reconnect_attempts.clone()))?;
+        registry.register(Box::new(metrics.judgements_submitted.clone()))?;
+        registry.register(Box::new(metrics.judgements_acknowledged.clone()))?;
+        registry.register(Box::new(metrics.account_state_subscribers.clone()))?;
+        registry.register(Box::new(metrics.event_log_lag.clone()))?;
+        registry.register(Box::new(metrics.webhook_deliveries.clone()))?;
+
+        Ok(metrics)
+    }
+    /// Encodes all metrics in the Prometheus text format.
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("failed to register metrics"))
+}
+
+/// Counts the pending requests at scrape time, then renders all metrics.
+pub async fn serve_metrics(db: web::Data<Database>) -> HttpResponse {
+    for chain in ChainName::all() {
+        match db.count_pending_requests(*chain).await {
+            Ok(count) => metrics()
+                .pending_requests
+                .with_label_values(&[chain.as_str()])
+                .set(count as i64),
+            Err(err) => error!("Failed to count pending requests: {:?}", err),
+        }
+    }
+
+    match metrics().encode() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type(prometheus::TEXT_FORMAT)
+            .body(body),
+        Err(err) => {
+            error!("Failed to encode metrics: {:?}", err);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_metrics() {
+        metrics()
+            .judgements_submitted
+            .with_label_values(&["kusama"])
+            .inc();
+        metrics()
+            .verifications
+            .with_label_values(&["email", "verified"])
+            .inc();
+
+        let body = metrics().encode().unwrap();
+        assert!(body.contains("# TYPE registrar_judgements_submitted_total counter"));
+        assert!(body.contains("registrar_judgements_submitted_total{chain=\"kusama\"}"));
+        assert!(
+            body.contains("registrar_verifications_total{adapter=\"email\",outcome=\"verified\"}")
+        );
+        assert!(body.contains("registrar_account_state_subscribers"));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::api::{LookupServer, NotifyAccountState};
 use crate::database::{Database, EventCursor};
-use crate::primitives::{IdentityContext, JudgementState};
+use crate::health::health;
+use crate::metrics::metrics;
+use crate::primitives::{IdentityContext, JudgementState, Timestamp};
 use crate::Result;
 use actix::prelude::*;
+use bson::oid::ObjectId;
 use std::collections::HashMap;
 use tokio::time::{sleep, Duration};
 
@@ -15,7 +18,17 @@ pub async fn run_session_notifier(mut db: Database, server: Addr<LookupServer>)
         let events = db.fetch_events(cursor).await?;
         let mut cache: HashMap<IdentityContext, JudgementState> = HashMap::new();
 
-        for event in events {
+        // Event ids contain the creation time of the event.
+        let now = Timestamp::now().raw() as i64;
+        let lag = events
+            .iter()
+            .filter_map(|(event_id, _)| ObjectId::parse_str(event_id).ok())
+            .map(|id| now - id.timestamp().timestamp_millis() / 1_000)
+            .max()
+            .unwrap_or(0);
+        metrics().event_log_lag.set(lag.max(0));
+
+        for (event_id, event) in events {
             let state = match cache.get(event.context()) {
                 Some(state) => state.clone(),
                 None => {
@@ -33,6 +46,7 @@ pub async fn run_session_notifier(mut db: Database, server: Addr<LookupServer>)
             };
 
             server.do_send(NotifyAccountState {
+                event_id: Some(event_id),
                 state: state.into(),
                 notifications: vec![event],
             });
@@ -43,8 +57,9 @@ pub async fn run_session_notifier(mut db: Database, server: Addr<LookupServer>)
 
     let mut cursor = EventCursor::new();
     loop {
-        if let Err(err) = local(&mut db, &server, &mut cursor).await {
-            error!("Error in session notifier event loop: {:?}", err);
+        match local(&mut db, &server, &mut cursor).await {
+            Ok(()) => health().notifier_ran(),
+            Err(err) => error!("Error in session notifier event loop: {:?}", err),
         }
 
         // Fetch events based on intervals until ["Change
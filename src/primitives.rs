@@ -1,7 +1,12 @@
 use crate::adapters::admin::RawFieldName;
 use crate::connector::{AccountType, DisplayNameEntry, VerifiedEntry};
 use actix::Message;
+use blake2::{Blake2b512, Digest};
 use std::collections::HashMap;
+use std::str::FromStr;
+
+// Addresses with this SS58 prefix are valid on any Substrate chain.
+const GENERIC_SS58_PREFIX: u16 = 42;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -27,6 +32,86 @@ impl ChainAddress {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+    /// Decodes the SS58 network prefix. Returns `None` if the address is not a
+    /// valid SS58 encoded 32-byte account.
+    pub fn ss58_prefix(&self) -> Option<u16> {
+        self.decode_ss58().map(|(prefix, _)| prefix)
+    }
+    /// Decodes the public key of the account. Returns `None` if the address is
+    /// not a valid SS58 encoded 32-byte account.
+    pub fn public_key(&self) -> Option<[u8; 32]> {
+        self.decode_ss58().map(|(_, key)| key)
+    }
+    fn decode_ss58(&self) -> Option<(u16, [u8; 32])> {
+        let data = bs58::decode(&self.0).into_vec().ok()?;
+
+        let (prefix, prefix_len) = match *data.first()? {
+            b @ 0..=63 => (b as u16, 1),
+            64..=127 if data.len() > 1 => {
+                let lower = (data[0] << 2) | (data[1] >> 6);
+                let upper = data[1] & 0b0011_1111;
+                ((lower as u16) | ((upper as u16) << 8), 2)
+            }
+            _ => return None,
+        };
+
+        // Prefix, account and two bytes of checksum.
+        if data.len() != prefix_len + 32 + 2 {
+            return None;
+        }
+
+        let (payload, checksum) = data.split_at(data.len() - 2);
+        let hash = Blake2b512::new()
+            .chain_update(b"SS58PRE")
+            .chain_update(payload)
+            .finalize();
+
+        if &hash[..2] != checksum {
+            return None;
+        }
+
+        let mut key = [0; 32];
+        key.copy_from_slice(&payload[prefix_len..]);
+
+        Some((prefix, key))
+    }
+    /// Encodes the public key with the SS58 prefix.
+    pub fn from_public_key(prefix: u16, key: &[u8; 32]) -> Self {
+        let mut data = match prefix {
+            0..=63 => vec![prefix as u8],
+            _ => {
+                let lower = (prefix & 0xff) as u8;
+                let upper = (prefix >> 8) as u8;
+                vec![0b0100_0000 | (lower >> 2), (lower << 6) | upper]
+            }
+        };
+        data.extend_from_slice(key);
+
+        let hash = Blake2b512::new()
+            .chain_update(b"SS58PRE")
+            .chain_update(&data)
+            .finalize();
+        data.extend_from_slice(&hash[..2]);
+
+        ChainAddress(bs58::encode(data).into_string())
+    }
+    /// Whether the address uses the generic Substrate prefix, which is valid
+    /// on all chains.
+    pub fn is_generic(&self) -> bool {
+        self.ss58_prefix() == Some(GENERIC_SS58_PREFIX)
+    }
+    /// The supported chains the address is valid on.
+    pub fn valid_chains(&self) -> Vec<ChainName> {
+        match self.ss58_prefix() {
+            Some(GENERIC_SS58_PREFIX) => ChainName::all().to_vec(),
+            Some(prefix) => ChainName::all()
+                .iter()
+                .filter(|chain| chain.ss58_prefix() == prefix)
+                .cloned()
+                .collect(),
+            None => vec![],
+        }
+    }
 }
 
 impl From<String> for ChainAddress {
@@ -43,12 +128,33 @@ pub enum ChainName {
 }
 
 impl ChainName {
+    pub fn all() -> &'static [ChainName] {
+        &[ChainName::Polkadot, ChainName::Kusama]
+    }
     pub fn as_str(&self) -> &str {
         match self {
             ChainName::Polkadot => "polkadot",
             ChainName::Kusama => "kusama",
         }
     }
+    pub fn ss58_prefix(&self) -> u16 {
+        match self {
+            ChainName::Polkadot => 0,
+            ChainName::Kusama => 2,
+        }
+    }
+}
+
+impl FromStr for ChainName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "polkadot" => Ok(ChainName::Polkadot),
+            "kusama" => Ok(ChainName::Kusama),
+            _ => Err(anyhow!("Unsupported chain: {}", s)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -57,6 +163,8 @@ pub struct IdentityField {
     pub value: IdentityFieldValue,
     pub challenge: ChallengeType,
     pub failed_attempts: usize,
+    // Set once too many verification attempts failed.
+    pub locked_until: Option<Timestamp>,
 }
 
 impl IdentityField {
@@ -66,8 +174,6 @@ impl IdentityField {
         let challenge = {
             match val {
                 LegalName(_) => ChallengeType::Unsupported { is_verified: None },
-                Web(_) => ChallengeType::Unsupported { is_verified: None },
-                PGPFingerprint(_) => ChallengeType::Unsupported { is_verified: None },
                 Image(_) => ChallengeType::Unsupported { is_verified: None },
                 Additional(_) => ChallengeType::Unsupported { is_verified: None },
                 DisplayName(_) => ChallengeType::DisplayNameCheck {
@@ -86,6 +192,23 @@ impl IdentityField {
                     expected: ExpectedMessage::random(),
                     second: None,
                 },
+                Telegram(_) => ChallengeType::ExpectedMessage {
+                    expected: ExpectedMessage::random(),
+                    second: None,
+                },
+                Github(_) => ChallengeType::ExpectedMessage {
+                    expected: ExpectedMessage::random(),
+                    second: None,
+                },
+                Web(_) => ChallengeType::ExpectedMessage {
+                    expected: ExpectedMessage::random(),
+                    second: None,
+                },
+                // The challenge must be clearsigned with the specified key.
+                PGPFingerprint(_) => ChallengeType::ExpectedMessage {
+                    expected: ExpectedMessage::random(),
+                    second: None,
+                },
             }
         };
 
@@ -93,8 +216,14 @@ impl IdentityField {
             value: val,
             challenge,
             failed_attempts: 0,
+            locked_until: None,
         }
     }
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .map(|until| until.raw() > Timestamp::now().raw())
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -115,6 +244,32 @@ pub enum ChallengeType {
 }
 
 impl ChallengeType {
+    /// Replaces the pending (unverified) challenges with new, random ones.
+    pub fn rotate(&mut self) {
+        if let ChallengeType::ExpectedMessage { expected, second } = self {
+            if !expected.is_verified {
+                *expected = ExpectedMessage::random();
+            }
+
+            if let Some(second) = second.as_mut().filter(|second| !second.is_verified) {
+                *second = ExpectedMessage::random();
+            }
+        }
+    }
+    /// Resets the challenge to its initial, unverified state. All challenges
+    /// are replaced, including verified ones. The display name check is left
+    /// untouched, since it is only re-evaluated when the fields of the
+    /// identity change.
+    pub fn reset(&mut self) {
+        match self {
+            ChallengeType::ExpectedMessage { expected, second } => {
+                *expected = ExpectedMessage::random();
+                *second = second.as_ref().map(|_| ExpectedMessage::random());
+            }
+            ChallengeType::DisplayNameCheck { .. } => {}
+            ChallengeType::Unsupported { is_verified } => *is_verified = None,
+        }
+    }
     pub fn is_verified(&self) -> bool {
         match self {
             ChallengeType::ExpectedMessage { expected, second } => {
@@ -138,6 +293,9 @@ impl ChallengeType {
 pub struct ExpectedMessage {
     pub value: String,
     pub is_verified: bool,
+    // Not set for challenges issued before expiry was introduced. Those never
+    // expire.
+    pub issued_at: Option<Timestamp>,
 }
 
 impl ExpectedMessage {
@@ -148,16 +306,27 @@ impl ExpectedMessage {
         ExpectedMessage {
             value: hex::encode(random),
             is_verified: false,
+            issued_at: Some(Timestamp::now()),
         }
     }
-    pub fn is_message_valid(&self, message: &ExternalMessage) -> bool {
-        for value in &message.values {
-            if value.0.contains(&self.value) {
-                return true;
-            }
+    /// Whether the challenge is older than the given TTL (in seconds). Without
+    /// a TTL, challenges never expire.
+    pub fn is_expired(&self, ttl: Option<u64>) -> bool {
+        match (self.issued_at, ttl) {
+            (Some(issued_at), Some(ttl)) => issued_at.raw() + ttl < Timestamp::now().raw(),
+            _ => false,
         }
-
-        false
+    }
+    /// Whether the challenge was issued within the last `secs` seconds.
+    /// Challenges without an issue time were issued a long time ago.
+    pub fn is_issued_within(&self, secs: Option<u64>) -> bool {
+        match (self.issued_at, secs) {
+            (Some(issued_at), Some(secs)) => issued_at.raw() + secs > Timestamp::now().raw(),
+            _ => false,
+        }
+    }
+    pub fn is_message_valid(&self, message: &ExternalMessage) -> bool {
+        message.contains(&self.value)
     }
     #[cfg(test)]
     pub fn set_verified(&mut self) {
@@ -169,6 +338,45 @@ impl ExpectedMessage {
     }
 }
 
+/// Proves control over the account key by signing a nonce with it, in
+/// addition to the challenges of the identity fields.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountKeyProof {
+    pub nonce: ExpectedMessage,
+    // Set once verified.
+    pub key_type: Option<AccountKeyType>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKeyType {
+    Sr25519,
+    Ed25519,
+}
+
+impl AccountKeyProof {
+    pub fn new() -> Self {
+        AccountKeyProof {
+            nonce: ExpectedMessage::random(),
+            key_type: None,
+        }
+    }
+    pub fn is_verified(&self) -> bool {
+        self.nonce.is_verified
+    }
+    /// The message that must be signed with the account key. Binds the nonce
+    /// to the identity.
+    pub fn message(&self, context: &IdentityContext) -> String {
+        format!(
+            "polkadot-registrar:{}:{}:{}",
+            context.chain.as_str(),
+            context.address.as_str(),
+            self.nonce.value
+        )
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum IdentityFieldValue {
@@ -178,11 +386,42 @@ pub enum IdentityFieldValue {
     Web(String),
     Twitter(String),
     Matrix(String),
-    PGPFingerprint(()),
+    Telegram(String),
+    Github(String),
+    // Older entries do not contain the fingerprint.
+    PGPFingerprint(#[serde(deserialize_with = "null_as_default")] String),
     Image(()),
     Additional(()),
 }
 
+fn null_as_default<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.unwrap_or_default())
+}
+
+/// Converts a PGP fingerprint into lowercase hex without any prefix or
+/// whitespace, e.g. `0xAB12 CD34...` into `ab12cd34...`.
+pub fn normalize_pgp_fingerprint(value: &str) -> String {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    value
+        .strip_prefix("0x")
+        .unwrap_or(&value)
+        .to_ascii_lowercase()
+}
+
+/// Converts a Telegram handle into lowercase with a single `@` prefix, e.g.
+/// `Alice` or `@ALICE` into `@alice`.
+pub fn normalize_telegram_handle(value: &str) -> String {
+    let value = value.trim();
+    format!(
+        "@{}",
+        value.strip_prefix('@').unwrap_or(value).to_lowercase()
+    )
+}
+
 impl IdentityFieldValue {
     pub fn as_account_type(&self) -> (AccountType, String) {
         match self {
@@ -192,11 +431,32 @@ impl IdentityFieldValue {
             IdentityFieldValue::Web(val) => (AccountType::Web, val.to_string()),
             IdentityFieldValue::Twitter(val) => (AccountType::Twitter, val.to_string()),
             IdentityFieldValue::Matrix(val) => (AccountType::Matrix, val.to_string()),
-            IdentityFieldValue::PGPFingerprint(_) => (AccountType::PGPFingerprint, String::new()),
+            IdentityFieldValue::Telegram(val) => (AccountType::Telegram, val.to_string()),
+            IdentityFieldValue::Github(val) => (AccountType::Github, val.to_string()),
+            IdentityFieldValue::PGPFingerprint(val) => {
+                (AccountType::PGPFingerprint, val.to_string())
+            }
             IdentityFieldValue::Image(_) => (AccountType::Image, String::new()),
             IdentityFieldValue::Additional(_) => (AccountType::Additional, String::new()),
         }
     }
+    /// The field name as used by the admin interface, if the field can be
+    /// verified manually.
+    pub fn as_raw_field_name(&self) -> Option<RawFieldName> {
+        match self {
+            IdentityFieldValue::LegalName(_) => Some(RawFieldName::LegalName),
+            IdentityFieldValue::DisplayName(_) => Some(RawFieldName::DisplayName),
+            IdentityFieldValue::Email(_) => Some(RawFieldName::Email),
+            IdentityFieldValue::Web(_) => Some(RawFieldName::Web),
+            IdentityFieldValue::Twitter(_) => Some(RawFieldName::Twitter),
+            IdentityFieldValue::Matrix(_) => Some(RawFieldName::Matrix),
+            IdentityFieldValue::Telegram(_) => Some(RawFieldName::Telegram),
+            IdentityFieldValue::Github(_) => Some(RawFieldName::Github),
+            IdentityFieldValue::PGPFingerprint(_) => Some(RawFieldName::PGPFingerprint),
+            IdentityFieldValue::Image(_) => None,
+            IdentityFieldValue::Additional(_) => None,
+        }
+    }
     pub fn matches_type(&self, ty: &AccountType, value: &str) -> bool {
         match (self, ty) {
             (IdentityFieldValue::LegalName(val), AccountType::LegalName) => val == value,
@@ -205,7 +465,13 @@ impl IdentityFieldValue {
             (IdentityFieldValue::Web(val), AccountType::Web) => val == value,
             (IdentityFieldValue::Twitter(val), AccountType::Twitter) => val == value,
             (IdentityFieldValue::Matrix(val), AccountType::Matrix) => val == value,
-            (IdentityFieldValue::PGPFingerprint(_), AccountType::PGPFingerprint) => true,
+            (IdentityFieldValue::Telegram(val), AccountType::Telegram) => {
+                *val == normalize_telegram_handle(value)
+            }
+            (IdentityFieldValue::Github(val), AccountType::Github) => val == value,
+            (IdentityFieldValue::PGPFingerprint(val), AccountType::PGPFingerprint) => {
+                *val == normalize_pgp_fingerprint(value)
+            }
             (IdentityFieldValue::Image(_), AccountType::Image) => true,
             (IdentityFieldValue::Additional(_), AccountType::Additional) => true,
             _ => false,
@@ -225,6 +491,22 @@ impl IdentityFieldValue {
                 ExternalMessageType::Matrix(n2) => n1 == n2,
                 _ => false,
             },
+            IdentityFieldValue::Telegram(n1) => match &message.origin {
+                ExternalMessageType::Telegram(n2) => n1 == n2,
+                _ => false,
+            },
+            IdentityFieldValue::Github(n1) => match &message.origin {
+                ExternalMessageType::Github(n2) => n1 == n2,
+                _ => false,
+            },
+            IdentityFieldValue::Web(n1) => match &message.origin {
+                ExternalMessageType::Web(n2) => n1 == n2,
+                _ => false,
+            },
+            IdentityFieldValue::PGPFingerprint(n1) => match &message.origin {
+                ExternalMessageType::PGPFingerprint(n2) => n1 == n2,
+                _ => false,
+            },
             _ => false,
         }
     }
@@ -242,6 +524,7 @@ pub struct JudgementStateBlanked {
     pub completion_timestamp: Option<Timestamp>,
     pub judgement_submitted: bool,
     pub fields: Vec<IdentityFieldBlanked>,
+    pub account_key: Option<AccountKeyProof>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -250,6 +533,7 @@ pub struct IdentityFieldBlanked {
     pub value: IdentityFieldValue,
     pub challenge: ChallengeTypeBlanked,
     failed_attempts: usize,
+    locked_until: Option<Timestamp>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -308,8 +592,10 @@ impl From<JudgementState> for JudgementStateBlanked {
                         }
                     },
                     failed_attempts: f.failed_attempts,
+                    locked_until: f.locked_until,
                 })
                 .collect(),
+            account_key: s.account_key,
         }
     }
 }
@@ -324,6 +610,9 @@ pub struct JudgementState {
     pub judgement_submitted: bool,
     pub issue_judgement_at: Option<Timestamp>,
     pub fields: Vec<IdentityField>,
+    // Proof of control over the account key, if requested.
+    #[serde(default)]
+    pub account_key: Option<AccountKeyProof>,
 }
 
 impl JudgementState {
@@ -336,13 +625,32 @@ impl JudgementState {
             judgement_submitted: false,
             issue_judgement_at: None,
             fields: fields.into_iter().map(IdentityField::new).collect(),
+            account_key: None,
         }
     }
+    pub fn is_account_key_verified(&self) -> bool {
+        self.account_key
+            .as_ref()
+            .map(|proof| proof.is_verified())
+            .unwrap_or(false)
+    }
     pub fn check_full_verification(&self) -> bool {
         self.fields
             .iter()
             .all(|field| field.challenge.is_verified())
     }
+    /// Whether all fields are verified once the given fields are verified,
+    /// too.
+    pub fn is_completed_by(&self, fields: &[RawFieldName]) -> bool {
+        self.fields.iter().all(|field| {
+            field.challenge.is_verified()
+                || field
+                    .value
+                    .as_raw_field_name()
+                    .map(|raw| fields.contains(&raw))
+                    .unwrap_or(false)
+        })
+    }
     pub fn display_name(&self) -> Option<&str> {
         self.fields
             .iter()
@@ -391,12 +699,48 @@ pub struct ExternalMessage {
     pub values: Vec<MessagePart>,
 }
 
+impl ExternalMessage {
+    pub fn contains(&self, value: &str) -> bool {
+        self.values.iter().any(|part| part.0.contains(value))
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "value")]
 pub enum ExternalMessageType {
     Email(String),
     Twitter(String),
     Matrix(String),
+    Telegram(String),
+    Github(String),
+    Web(String),
+    PGPFingerprint(String),
+}
+
+impl ExternalMessageType {
+    /// Name of the adapter (or API) which received the message.
+    pub fn adapter(&self) -> &'static str {
+        match self {
+            ExternalMessageType::Email(_) => "email",
+            ExternalMessageType::Twitter(_) => "twitter",
+            ExternalMessageType::Matrix(_) => "matrix",
+            ExternalMessageType::Telegram(_) => "telegram",
+            ExternalMessageType::Github(_) => "github",
+            ExternalMessageType::Web(_) => "web",
+            ExternalMessageType::PGPFingerprint(_) => "pgp",
+        }
+    }
+    /// Whether the message is a proof looked up on behalf of the identities
+    /// rather than a message sent by a user. Such proofs are shared by all
+    /// identities specifying the same account, so they only verify the
+    /// identities whose challenge they contain and never count as failed
+    /// attempts.
+    pub fn is_proof_lookup(&self) -> bool {
+        matches!(
+            self,
+            ExternalMessageType::Web(_) | ExternalMessageType::Github(_)
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
@@ -509,6 +853,22 @@ pub enum NotificationMessage {
         context: IdentityContext,
         field: IdentityFieldValue,
     },
+    FieldLocked {
+        context: IdentityContext,
+        field: IdentityFieldValue,
+        locked_until: Timestamp,
+    },
+    ChallengeExpired {
+        context: IdentityContext,
+        field: IdentityFieldValue,
+    },
+    ChallengeRegenerated {
+        context: IdentityContext,
+        field: IdentityFieldValue,
+    },
+    AccountKeyVerified {
+        context: IdentityContext,
+    },
     IdentityFullyVerified {
         context: IdentityContext,
     },
@@ -522,9 +882,36 @@ pub enum NotificationMessage {
     FullManualVerification {
         context: IdentityContext,
     },
+    ManuallyUnverified {
+        context: IdentityContext,
+        field: RawFieldName,
+    },
+    IdentityReset {
+        context: IdentityContext,
+    },
 }
 
 impl NotificationMessage {
+    /// Values of the `type` field of all notifications.
+    pub const TYPES: &'static [&'static str] = &[
+        "identity_inserted",
+        "identity_updated",
+        "field_verified",
+        "field_verification_failed",
+        "second_field_verified",
+        "second_field_verification_failed",
+        "awaiting_second_challenge",
+        "field_locked",
+        "challenge_expired",
+        "challenge_regenerated",
+        "account_key_verified",
+        "identity_fully_verified",
+        "judgement_provided",
+        "manually_verified",
+        "full_manual_verification",
+        "manually_unverified",
+        "identity_reset",
+    ];
     pub fn context(&self) -> &IdentityContext {
         use NotificationMessage::*;
 
@@ -536,21 +923,24 @@ impl NotificationMessage {
             SecondFieldVerified { context, field: _ } => context,
             SecondFieldVerificationFailed { context, field: _ } => context,
             AwaitingSecondChallenge { context, field: _ } => context,
+            FieldLocked {
+                context,
+                field: _,
+                locked_until: _,
+            } => context,
+            ChallengeExpired { context, field: _ } => context,
+            ChallengeRegenerated { context, field: _ } => context,
+            AccountKeyVerified { context } => context,
             IdentityFullyVerified { context } => context,
             JudgementProvided { context } => context,
             ManuallyVerified { context, field: _ } => context,
             FullManualVerification { context } => context,
+            ManuallyUnverified { context, field: _ } => context,
+            IdentityReset { context } => context,
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub struct IdentityJudged {
-    context: IdentityContext,
-    timestamp: Timestamp,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -589,6 +979,7 @@ mod tests {
                     IdentityField::new(IdentityFieldValue::ALICE_TWITTER()),
                     IdentityField::new(IdentityFieldValue::ALICE_MATRIX()),
                 ],
+                account_key: None,
             }
         }
         pub fn get_field<'a>(&'a self, ty: &IdentityFieldValue) -> &'a IdentityField {
@@ -608,6 +999,10 @@ mod tests {
                 ExternalMessageType::Email(n) => IdentityFieldValue::Email(n),
                 ExternalMessageType::Twitter(n) => IdentityFieldValue::Twitter(n),
                 ExternalMessageType::Matrix(n) => IdentityFieldValue::Matrix(n),
+                ExternalMessageType::Telegram(n) => IdentityFieldValue::Telegram(n),
+                ExternalMessageType::Github(n) => IdentityFieldValue::Github(n),
+                ExternalMessageType::Web(n) => IdentityFieldValue::Web(n),
+                ExternalMessageType::PGPFingerprint(n) => IdentityFieldValue::PGPFingerprint(n),
             }
         }
     }
@@ -694,6 +1089,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn challenge_expiry() {
+        let mut expected = ExpectedMessage::random();
+        assert!(!expected.is_expired(None));
+        assert!(!expected.is_expired(Some(60)));
+
+        expected.issued_at = Some(Timestamp(Timestamp::now().raw() - 120));
+        assert!(expected.is_expired(Some(60)));
+        assert!(!expected.is_expired(None));
+
+        assert!(!expected.is_issued_within(Some(60)));
+        assert!(expected.is_issued_within(Some(180)));
+        assert!(!expected.is_issued_within(None));
+
+        // Legacy challenges never expire.
+        expected.issued_at = None;
+        assert!(!expected.is_expired(Some(60)));
+        assert!(!expected.is_issued_within(Some(60)));
+    }
+    #[test]
+    fn ss58_address_chains() {
+        let polkadot =
+            ChainAddress::from("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5".to_string());
+        assert_eq!(polkadot.ss58_prefix(), Some(0));
+        assert_eq!(polkadot.valid_chains(), vec![ChainName::Polkadot]);
+
+        let kusama =
+            ChainAddress::from("HNZata7iMYWmk5RvZRTiAsSDhV8366zq2YGb3tLH5Upf74F".to_string());
+        assert_eq!(kusama.ss58_prefix(), Some(2));
+        assert_eq!(kusama.valid_chains(), vec![ChainName::Kusama]);
+
+        // Generic Substrate prefix, e.g. Westend.
+        let generic =
+            ChainAddress::from("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string());
+        assert_eq!(generic.ss58_prefix(), Some(42));
+        assert_eq!(
+            generic.public_key().map(hex::encode),
+            Some("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d".to_string())
+        );
+        assert_eq!(
+            ChainAddress::from_public_key(42, &generic.public_key().unwrap()),
+            generic
+        );
+        assert!(generic.is_generic());
+        assert!(!polkadot.is_generic());
+
+        // The same account on the different chains.
+        let key = generic.public_key().unwrap();
+        assert_eq!(ChainAddress::from_public_key(0, &key), polkadot);
+        assert_eq!(ChainAddress::from_public_key(2, &key), kusama);
+
+        // Two byte prefix.
+        assert_eq!(
+            ChainAddress::from_public_key(1284, &key).ss58_prefix(),
+            Some(1284)
+        );
+        assert_eq!(
+            generic.valid_chains(),
+            vec![ChainName::Polkadot, ChainName::Kusama]
+        );
+
+        // Invalid checksum.
+        let invalid =
+            ChainAddress::from("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp6".to_string());
+        assert_eq!(invalid.ss58_prefix(), None);
+        assert_eq!(invalid.public_key(), None);
+        assert!(invalid.valid_chains().is_empty());
+
+        let invalid = ChainAddress::from("Alice".to_string());
+        assert_eq!(invalid.ss58_prefix(), None);
+    }
+    #[test]
+    fn telegram_handles() {
+        assert_eq!(normalize_telegram_handle("Alice"), "@alice");
+        assert_eq!(normalize_telegram_handle(" @ALICE "), "@alice");
+
+        // On-chain values match the handles reported by the adapter.
+        let field: IdentityFieldValue = (AccountType::Telegram, "Alice".to_string()).into();
+        assert_eq!(field, IdentityFieldValue::Telegram("@alice".to_string()));
+        assert!(field.matches_type(&AccountType::Telegram, "@Alice"));
+    }
     #[test]
     fn has_same_fields_as() {
         let id = IdentityContext::alice();
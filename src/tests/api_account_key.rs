@@ -0,0 +1,163 @@
+use super::*;
+use crate::adapters::admin::{process_admin, Command, RawFieldName, Response};
+use crate::api::VerifyChallenge;
+use crate::primitives::{
+    AccountKeyType, ExternalMessage, ExternalMessageType, MessageId, Timestamp,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Value};
+
+#[actix::test]
+async fn verify_account_key() {
+    let (db, connector, api, _) = new_env().await;
+    let (key, context, request) = carol();
+
+    // No judgement request yet.
+    let mut res = api
+        .post("/api/account_key/challenge")
+        .send_json(&json!({ "context": context }))
+        .await
+        .unwrap();
+    let body: JsonResult<Value> = res.json().await.unwrap();
+    assert!(matches!(body, JsonResult::Err(_)));
+
+    connector.inject(request).await;
+    let _ = connector.inserted_states().await;
+
+    // Request the message to sign.
+    let mut res = api
+        .post("/api/account_key/challenge")
+        .send_json(&json!({ "context": context }))
+        .await
+        .unwrap();
+    let challenge = match res.json::<JsonResult<Value>>().await.unwrap() {
+        JsonResult::Ok(challenge) => challenge,
+        _ => panic!(),
+    };
+    assert_eq!(challenge["is_verified"], json!(false));
+
+    let message = challenge["message"].as_str().unwrap().to_string();
+    assert!(message.contains(context.address.as_str()));
+
+    // The pending challenge is reused.
+    let mut res = api
+        .post("/api/account_key/challenge")
+        .send_json(&json!({ "context": context }))
+        .await
+        .unwrap();
+    let body: JsonResult<Value> = res.json().await.unwrap();
+    assert_eq!(body, JsonResult::Ok(challenge));
+
+    // Signed by a different key.
+    let other = SigningKey::from_bytes(&[8; 32]);
+    let mut res = api
+        .post("/api/account_key/verify")
+        .send_json(&json!({
+            "context": context,
+            "signature": hex::encode(other.sign(message.as_bytes()).to_bytes()),
+        }))
+        .await
+        .unwrap();
+    let body: JsonResult<bool> = res.json().await.unwrap();
+    assert!(matches!(body, JsonResult::Err(_)));
+
+    let mut res = api
+        .post("/api/account_key/verify")
+        .send_json(&json!({
+            "context": context,
+            "signature": format!("0x{}", hex::encode(key.sign(message.as_bytes()).to_bytes())),
+        }))
+        .await
+        .unwrap();
+    let body: JsonResult<bool> = res.json().await.unwrap();
+    assert_eq!(body, JsonResult::Ok(true));
+
+    let state = db.fetch_judgement_state(&context).await.unwrap().unwrap();
+    assert!(state.is_account_key_verified());
+    assert_eq!(
+        state.account_key.unwrap().key_type,
+        Some(AccountKeyType::Ed25519)
+    );
+
+    // The proof is not required by default.
+    assert!(!state.is_fully_verified);
+}
+
+#[actix::test]
+async fn required_account_key() {
+    let (db, connector, _api, _) = new_env().await;
+    let db = db.with_required_account_key();
+    let (_, context, request) = carol();
+
+    connector.inject(request).await;
+    let states = connector.inserted_states().await;
+    let carol = states[0].clone();
+    let email = IdentityFieldValue::Email("carol@email.com".to_string());
+
+    // All fields are verified, but the account key is not.
+    db.verify_message(&ExternalMessage {
+        origin: ExternalMessageType::Email("carol@email.com".to_string()),
+        id: MessageId::from(0u32),
+        timestamp: Timestamp::now(),
+        values: carol
+            .get_field(&email)
+            .expected_message()
+            .to_message_parts(),
+    })
+    .await
+    .unwrap();
+
+    assert!(db
+        .verify_second_challenge(VerifyChallenge {
+            context: Some(context.clone()),
+            entry: email.clone(),
+            challenge: carol.get_field(&email).expected_second().value.clone(),
+        })
+        .await
+        .unwrap());
+
+    let state = db.fetch_judgement_state(&context).await.unwrap().unwrap();
+    assert!(state.check_full_verification());
+    assert!(!state.is_fully_verified);
+
+    let proof = db
+        .request_account_key_proof(&context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(db
+        .set_account_key_verified(&context, &proof.nonce.value, AccountKeyType::Sr25519)
+        .await
+        .unwrap());
+
+    let state = db.fetch_judgement_state(&context).await.unwrap().unwrap();
+    assert!(state.is_fully_verified);
+
+    // A nonce can only be used once.
+    assert!(!db
+        .set_account_key_verified(&context, &proof.nonce.value, AccountKeyType::Sr25519)
+        .await
+        .unwrap());
+}
+
+#[actix::test]
+async fn required_account_key_manual_override() {
+    let (db, connector, _api, _) = new_env().await;
+    let db = db.with_required_account_key();
+    let (_, context, request) = carol();
+
+    connector.inject(request).await;
+    let _ = connector.inserted_states().await;
+
+    // A full manual verification does not require the account key.
+    let resp = process_admin(
+        &db,
+        Command::Verify(context.clone().into(), vec![RawFieldName::All]),
+    )
+    .await;
+    assert_eq!(resp, Response::FullyVerified(context.clone()));
+
+    let state = db.fetch_judgement_state(&context).await.unwrap().unwrap();
+    assert!(state.is_fully_verified);
+    assert!(!state.is_account_key_verified());
+}
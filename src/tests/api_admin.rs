@@ -0,0 +1,186 @@
+use super::*;
+use crate::adapters::admin::{AuditOutcome, IdentityStatus, RawFieldName, Response, Role};
+use crate::api::tests::{ADMIN_TEST_TOKEN, ADMIN_VIEWER_TEST_TOKEN};
+use crate::primitives::JudgementStateBlanked;
+use actix_http::StatusCode;
+
+#[actix::test]
+async fn admin_api_requires_token() {
+    let (db, connector, api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let path = format!("/admin/status/{}", alice.context.address.as_str());
+
+    // Missing token.
+    let res = api.get(&path).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Invalid token.
+    let res = api.get(&path).bearer_auth("invalid").send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Unauthenticated calls are not recorded in the audit log.
+    assert!(db.fetch_audit_entries(None).await.unwrap().is_empty());
+
+    // Valid token.
+    let mut res = api
+        .get(&path)
+        .bearer_auth(ADMIN_TEST_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(
+        body,
+        serde_json::to_value(JsonResult::Ok(Response::Status(Box::new(IdentityStatus {
+            state: JudgementStateBlanked::from(alice),
+            notes: vec![],
+        }))))
+        .unwrap()
+    );
+}
+
+#[actix::test]
+async fn admin_api_verify() {
+    let (db, connector, api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let mut res = api
+        .post("/admin/verify")
+        .bearer_auth(ADMIN_TEST_TOKEN)
+        .send_json(&serde_json::json!({
+            "address": alice.context.address,
+            "fields": ["email", "twitter"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(
+        body,
+        serde_json::to_value(JsonResult::Ok(Response::Verified(
+            alice.context.clone(),
+            vec![RawFieldName::Email, RawFieldName::Twitter]
+        )))
+        .unwrap()
+    );
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(current.get_field(&F::ALICE_EMAIL()).challenge.is_verified());
+    assert!(current
+        .get_field(&F::ALICE_TWITTER())
+        .challenge
+        .is_verified());
+
+    // Invalid field name.
+    let res = api
+        .post("/admin/verify")
+        .bearer_auth(ADMIN_TEST_TOKEN)
+        .send_json(&serde_json::json!({
+            "address": alice.context.address,
+            "fields": ["unknown"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Unknown identity.
+    let res = api
+        .post("/admin/reset")
+        .bearer_auth(ADMIN_TEST_TOKEN)
+        .send_json(&serde_json::json!({
+            "address": IdentityContext::bob().address,
+        }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix::test]
+async fn admin_api_role_permissions() {
+    let (db, connector, api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    // Viewers can read the status...
+    let res = api
+        .get(format!("/admin/status/{}", alice.context.address.as_str()))
+        .bearer_auth(ADMIN_VIEWER_TEST_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // ... but not modify it.
+    let mut res = api
+        .post("/admin/verify")
+        .bearer_auth(ADMIN_VIEWER_TEST_TOKEN)
+        .send_json(&serde_json::json!({
+            "address": alice.context.address,
+            "fields": ["email"],
+        }))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(
+        body,
+        serde_json::to_value(JsonResult::<()>::Err(
+            Response::PermissionDenied(Role::Viewer).to_string()
+        ))
+        .unwrap()
+    );
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!current.get_field(&F::ALICE_EMAIL()).challenge.is_verified());
+
+    // The denied attempt is recorded in the audit log.
+    let entries = db.fetch_audit_entries(Some(&alice.context)).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].actor, "token:viewer");
+    assert_eq!(entries[0].outcome, AuditOutcome::Forbidden);
+    assert_eq!(entries[1].outcome, AuditOutcome::Success);
+
+    // Viewers cannot read the audit log.
+    let res = api
+        .get("/admin/audit")
+        .bearer_auth(ADMIN_VIEWER_TEST_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = api
+        .get(format!(
+            "/admin/audit?address={}",
+            alice.context.address.as_str()
+        ))
+        .bearer_auth(ADMIN_TEST_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
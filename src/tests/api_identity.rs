@@ -0,0 +1,394 @@
+use super::*;
+use crate::adapters::admin::{process_admin, Command, RawFieldName};
+use crate::database::{EventHistory, StatsBucket};
+use crate::primitives::{ChainName, JudgementStateBlanked, NotificationMessage, Timestamp};
+use actix_http::error::PayloadError;
+use actix_http::header::{HeaderValue, CONTENT_TYPE, ETAG, IF_NONE_MATCH, LAST_MODIFIED};
+use actix_http::StatusCode;
+use actix_web::web::Bytes;
+use futures::Stream;
+
+// Reads the next server-sent event, skipping keep-alive comments.
+async fn next_event<S>(
+    stream: &mut S,
+    buffer: &mut String,
+) -> (Option<String>, JsonResult<ResponseAccountState>)
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    loop {
+        if let Some(end) = buffer.find("\n\n") {
+            let frame = buffer[..end].to_string();
+            buffer.replace_range(..end + 2, "");
+
+            let mut id = None;
+            let mut data = None;
+            for line in frame.lines() {
+                if let Some(value) = line.strip_prefix("id: ") {
+                    id = Some(value.to_string());
+                } else if let Some(value) = line.strip_prefix("data: ") {
+                    data = Some(serde_json::from_str(value).unwrap());
+                }
+            }
+
+            if let Some(data) = data {
+                return (id, data);
+            }
+
+            continue;
+        }
+
+        let chunk = stream.next().await.unwrap().unwrap();
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+}
+
+#[actix::test]
+async fn get_identity_state() {
+    let (db, connector, api, _) = new_env().await;
+
+    let alice = IdentityContext::alice();
+    let path = format!("/api/v1/identity/polkadot/{}", alice.address.as_str());
+
+    // No judgement request yet.
+    let mut res = api.get(&path).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let body: JsonResult<()> = res.json().await.unwrap();
+    assert!(matches!(body, JsonResult::Err(_)));
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let mut res = api.get(&path).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().contains_key(LAST_MODIFIED));
+
+    let etag = res.headers().get(ETAG).unwrap().clone();
+
+    let body: JsonResult<JudgementStateBlanked> = res.json().await.unwrap();
+    assert_eq!(body, JsonResult::Ok(alice.clone().into()));
+
+    // Not modified.
+    let res = api
+        .get(&path)
+        .insert_header((IF_NONE_MATCH, etag.clone()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers().get(ETAG), Some(&etag));
+
+    // Any change creates a new event, respectively a new ETag.
+    let _ = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Email]),
+    )
+    .await;
+
+    let res = api
+        .get(&path)
+        .insert_header((IF_NONE_MATCH, etag.clone()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(res.headers().get(ETAG), Some(&etag));
+}
+
+#[actix::test]
+async fn get_identity_invalid_path() {
+    let (_db, _connector, api, _) = new_env().await;
+
+    let alice = IdentityContext::alice();
+
+    // Unsupported chain.
+    let mut res = api
+        .get(format!(
+            "/api/v1/identity/westend/{}",
+            alice.address.as_str()
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let body: JsonResult<()> = res.json().await.unwrap();
+    assert!(matches!(body, JsonResult::Err(_)));
+
+    // Not a valid Kusama address.
+    let res = api
+        .get(format!(
+            "/api/v1/identity/kusama/{}",
+            alice.address.as_str()
+        ))
+        .insert_header((IF_NONE_MATCH, HeaderValue::from_static("*")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix::test]
+async fn identity_events_stream() {
+    let (db, connector, api, _) = new_env().await;
+
+    let alice = IdentityContext::alice();
+    let path = format!(
+        "/api/v1/identity/polkadot/{}/events",
+        alice.address.as_str()
+    );
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let mut stream = api.get(&path).send().await.unwrap();
+    assert_eq!(stream.status(), StatusCode::OK);
+    assert_eq!(
+        stream.headers().get(CONTENT_TYPE).unwrap(),
+        "text/event-stream"
+    );
+
+    // Current state, including the id of the latest event.
+    let mut buffer = String::new();
+    let (first_id, resp) = next_event(&mut stream, &mut buffer).await;
+    assert!(first_id.is_some());
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(alice.clone()))
+    );
+
+    // Any change is streamed as a notification.
+    let _ = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Email]),
+    )
+    .await;
+
+    let (last_id, resp) = next_event(&mut stream, &mut buffer).await;
+    assert!(last_id.is_some());
+    assert_ne!(last_id, first_id);
+    match resp {
+        JsonResult::Ok(resp) => {
+            assert_eq!(
+                resp.notifications,
+                vec![NotificationMessage::ManuallyVerified {
+                    context: alice.context.clone(),
+                    field: RawFieldName::Email,
+                }]
+            );
+        }
+        _ => panic!(),
+    }
+
+    // Resuming replays the missed events.
+    let mut stream = api
+        .get(&path)
+        .insert_header(("Last-Event-ID", first_id.unwrap()))
+        .send()
+        .await
+        .unwrap();
+
+    let mut buffer = String::new();
+    let (id, resp) = next_event(&mut stream, &mut buffer).await;
+    assert_eq!(id, last_id);
+    assert!(matches!(resp, JsonResult::Ok(resp) if !resp.notifications.is_empty()));
+
+    // Nothing was missed, only the current state is sent.
+    let mut stream = api
+        .get(&path)
+        .insert_header(("Last-Event-ID", last_id.clone().unwrap()))
+        .send()
+        .await
+        .unwrap();
+
+    let mut buffer = String::new();
+    let (id, resp) = next_event(&mut stream, &mut buffer).await;
+    assert_eq!(id, last_id);
+    assert!(matches!(resp, JsonResult::Ok(resp) if resp.notifications.is_empty()));
+}
+
+#[actix::test]
+async fn identity_events_no_judgement_request() {
+    let (_db, _connector, api, _) = new_env().await;
+
+    let alice = IdentityContext::alice();
+    let mut stream = api
+        .get(format!(
+            "/api/v1/identity/polkadot/{}/events",
+            alice.address.as_str()
+        ))
+        .send()
+        .await
+        .unwrap();
+
+    let mut buffer = String::new();
+    let (id, resp) = next_event(&mut stream, &mut buffer).await;
+    assert_eq!(id, None);
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    // The stream is closed.
+    assert!(stream.next().await.is_none());
+}
+
+#[actix::test]
+async fn identity_history() {
+    let (db, connector, api, _) = new_env().await;
+
+    let alice = IdentityContext::alice();
+    let path = format!(
+        "/api/v1/identity/polkadot/{}/history",
+        alice.address.as_str()
+    );
+
+    // No judgement request yet.
+    let res = api.get(&path).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Insert judgement request and create some events.
+    connector.inject(alice_judgement_request()).await;
+    for field in [RawFieldName::Email, RawFieldName::Twitter] {
+        let _ = process_admin(&db, Command::Verify(alice.clone().into(), vec![field])).await;
+    }
+
+    let mut res = api.get(&path).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let all = match res.json::<JsonResult<EventHistory>>().await.unwrap() {
+        JsonResult::Ok(history) => history,
+        JsonResult::Err(err) => panic!("{}", err),
+    };
+    assert!(all.events.len() >= 2);
+    assert_eq!(all.next, None);
+    assert!(all
+        .events
+        .iter()
+        .any(|event| matches!(&event.message, NotificationMessage::ManuallyVerified { field, .. } if *field == RawFieldName::Email)));
+
+    // Newest first.
+    assert!(all.events.windows(2).all(|pair| pair[0].id > pair[1].id));
+
+    // Paginate.
+    let mut paginated = vec![];
+    let mut before: Option<String> = None;
+    loop {
+        let query = match &before {
+            Some(before) => format!("{}?limit=1&before={}", path, before),
+            None => format!("{}?limit=1", path),
+        };
+
+        let mut res = api.get(query).send().await.unwrap();
+        let page = match res.json::<JsonResult<EventHistory>>().await.unwrap() {
+            JsonResult::Ok(page) => page,
+            JsonResult::Err(err) => panic!("{}", err),
+        };
+        assert_eq!(page.events.len(), 1);
+
+        paginated.extend(page.events);
+        before = page.next;
+        if before.is_none() {
+            break;
+        }
+    }
+    assert_eq!(paginated, all.events);
+
+    // Time range.
+    let now = Timestamp::now().raw();
+    let mut res = api
+        .get(format!("{}?from={}", path, now + 3600))
+        .send()
+        .await
+        .unwrap();
+    let body: JsonResult<EventHistory> = res.json().await.unwrap();
+    assert!(matches!(body, JsonResult::Ok(history) if history.events.is_empty()));
+
+    let mut res = api
+        .get(format!("{}?from=0&to={}", path, now + 3600))
+        .send()
+        .await
+        .unwrap();
+    let body: JsonResult<EventHistory> = res.json().await.unwrap();
+    assert_eq!(body, JsonResult::Ok(all));
+
+    // Invalid queries.
+    for query in ["before=invalid", "from=10&to=5", "limit=0"] {
+        let res = api.get(format!("{}?{}", path, query)).send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+async fn stats(api: &TestServer, query: &str) -> Vec<StatsBucket> {
+    let mut res = api
+        .get(format!("/api/v1/stats{}", query))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    match res.json::<JsonResult<Vec<StatsBucket>>>().await.unwrap() {
+        JsonResult::Ok(stats) => stats,
+        JsonResult::Err(err) => panic!("{}", err),
+    }
+}
+
+#[actix::test]
+async fn registrar_stats() {
+    let (db, connector, api, _) = new_env().await;
+
+    // No requests yet.
+    assert!(stats(&api, "").await.is_empty());
+
+    // Insert judgement request and verify some fields manually.
+    let alice = IdentityContext::alice();
+    connector.inject(alice_judgement_request()).await;
+    for field in [RawFieldName::Email, RawFieldName::Twitter] {
+        let _ = process_admin(&db, Command::Verify(alice.clone().into(), vec![field])).await;
+    }
+
+    let buckets = stats(&api, "").await;
+    assert_eq!(buckets.len(), 1);
+
+    let bucket = &buckets[0];
+    assert_eq!(bucket.chain, ChainName::Polkadot);
+    // Months by default, e.g. `2023-04`.
+    assert_eq!(bucket.period.len(), 7);
+    assert_eq!(bucket.requests_received, 1);
+    assert_eq!(bucket.judgements_issued, 0);
+    assert_eq!(bucket.median_time_to_verify, None);
+    // Counted per identity.
+    assert_eq!(bucket.manual_verifications, 1);
+    assert_eq!(bucket.manual_share, Some(1.0));
+    assert_eq!(bucket.display_name_rejections, 0);
+
+    // Intervals.
+    let buckets = stats(&api, "?interval=day").await;
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].period.len(), 10);
+
+    let buckets = stats(&api, "?interval=week").await;
+    assert_eq!(buckets.len(), 1);
+    assert!(buckets[0].period.contains("-W"));
+
+    // Filters.
+    assert!(stats(&api, "?chain=kusama").await.is_empty());
+    assert_eq!(stats(&api, "?chain=polkadot").await.len(), 1);
+
+    let now = Timestamp::now().raw();
+    assert!(stats(&api, &format!("?from={}", now + 3600))
+        .await
+        .is_empty());
+
+    // Invalid queries.
+    for query in ["from=10&to=5", "interval=year", "chain=unknown"] {
+        let res = api
+            .get(format!("/api/v1/stats?{}", query))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,12 +1,19 @@
 use super::*;
+use crate::adapters::admin::{process_admin, Command, RawFieldName};
 use crate::api::VerifyChallenge;
-use crate::api::{JsonResult, ResponseAccountState};
+use crate::api::{
+    JsonResult, RegenerateChallenge, ResponseAccountState, WsEnvelope, WsOperation, WsReply,
+    WsRequest, WS_PROTOCOL_VERSION,
+};
 use crate::connector::WatcherMessage;
+use crate::database::EventHistoryQuery;
 use crate::primitives::{
     ExpectedMessage, ExternalMessage, ExternalMessageType, IdentityContext, MessageId,
     NotificationMessage, Timestamp,
 };
+use crate::FailedAttemptsConfig;
 use actix_http::StatusCode;
+use ed25519_dalek::Signer;
 use futures::{FutureExt, StreamExt};
 
 #[actix::test]
@@ -380,6 +387,356 @@ async fn verify_valid_message() {
     assert!(stream.next().now_or_never().is_none());
 }
 
+#[actix::test]
+async fn verify_valid_web_message_address_proof() {
+    let (_db, connector, mut api, injector) = new_env().await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request with a web domain.
+    connector
+        .inject(WatcherMessage::new_judgement_request({
+            let mut req = JudgementRequest::alice();
+            req.accounts
+                .insert(AccountType::Web, "alice.com".to_string());
+            req
+        }))
+        .await;
+    let states = connector.inserted_states().await;
+    let mut alice = states[0].clone();
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut stream, IdentityContext::alice()).await;
+
+    // Check current state.
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(alice.clone()))
+    );
+
+    // The proof references the account address instead of the challenge.
+    injector
+        .send(ExternalMessage {
+            origin: ExternalMessageType::Web("alice.com".to_string()),
+            id: MessageId::from(0u32),
+            timestamp: Timestamp::now(),
+            values: vec![alice.context.address.as_str().to_string().into()],
+        })
+        .await;
+
+    // Web domain of Alice is now verified.
+    let web = IdentityFieldValue::Web("alice.com".to_string());
+    alice
+        .get_field_mut(&web)
+        .expected_message_mut()
+        .set_verified();
+
+    let expected = ResponseAccountState {
+        state: alice.clone().into(),
+        notifications: vec![NotificationMessage::FieldVerified {
+            context: alice.context.clone(),
+            field: web,
+        }],
+    };
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Empty stream.
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[actix::test]
+async fn verify_web_proof_shared_domain() {
+    let (db, connector, mut api, injector) = new_env().await;
+    let mut alice_stream = api.ws_at("/api/account_status").await.unwrap();
+    let mut bob_stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement requests with the same web domain.
+    for mut req in [JudgementRequest::alice(), JudgementRequest::bob()] {
+        req.accounts
+            .insert(AccountType::Web, "alice.com".to_string());
+        connector
+            .inject(WatcherMessage::new_judgement_request(req))
+            .await;
+    }
+    let states = connector.inserted_states().await;
+    let mut alice = states[0].clone();
+    let bob = states[1].clone();
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut alice_stream, IdentityContext::alice()).await;
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(alice.clone()))
+    );
+
+    let resp = subscribe_context(&mut bob_stream, IdentityContext::bob()).await;
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(bob.clone()))
+    );
+
+    // The proof only contains the challenge of Alice.
+    let web = IdentityFieldValue::Web("alice.com".to_string());
+    injector
+        .send(ExternalMessage {
+            origin: ExternalMessageType::Web("alice.com".to_string()),
+            id: MessageId::from(0u32),
+            timestamp: Timestamp::now(),
+            values: vec![alice
+                .get_field(&web)
+                .expected_message()
+                .value
+                .clone()
+                .into()],
+        })
+        .await;
+
+    // Web domain of Alice is now verified.
+    alice
+        .get_field_mut(&web)
+        .expected_message_mut()
+        .set_verified();
+
+    let expected = ResponseAccountState {
+        state: alice.clone().into(),
+        notifications: vec![NotificationMessage::FieldVerified {
+            context: alice.context.clone(),
+            field: web.clone(),
+        }],
+    };
+
+    let resp: JsonResult<ResponseAccountState> = alice_stream.next().await.into();
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Bob is neither verified nor charged with a failed attempt.
+    let state = db
+        .fetch_judgement_state(&bob.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state, bob);
+    assert_eq!(state.get_field(&web).failed_attempts, 0);
+
+    // Empty stream.
+    assert!(alice_stream.next().now_or_never().is_none());
+    assert!(bob_stream.next().now_or_never().is_none());
+}
+
+#[actix::test]
+async fn verify_invalid_message_field_locked() {
+    let (db, connector, mut api, injector) =
+        new_env_with_failed_attempts(Some(FailedAttemptsConfig {
+            max_attempts: 2,
+            cooldown: 3600,
+            notify_admins: false,
+        }))
+        .await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let mut alice = states[0].clone();
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut stream, IdentityContext::alice()).await;
+
+    // Check current state.
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(alice.clone()))
+    );
+
+    let invalid = ExternalMessage {
+        origin: ExternalMessageType::Email("alice@email.com".to_string()),
+        id: MessageId::from(0u32),
+        timestamp: Timestamp::now(),
+        values: ExpectedMessage::random().to_message_parts(),
+    };
+
+    // First failed attempt.
+    injector.send(invalid.clone()).await;
+
+    *alice.get_field_mut(&F::ALICE_EMAIL()).failed_attempts_mut() = 1;
+
+    let expected = ResponseAccountState {
+        state: alice.clone().into(),
+        notifications: vec![NotificationMessage::FieldVerificationFailed {
+            context: alice.context.clone(),
+            field: F::ALICE_EMAIL(),
+        }],
+    };
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Second failed attempt locks the field.
+    injector.send(invalid).await;
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+
+    // The challenges were replaced and the counter reset.
+    let locked = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    let field = locked.get_field(&F::ALICE_EMAIL());
+    assert!(field.is_locked());
+    assert_eq!(field.failed_attempts, 0);
+    assert_ne!(
+        field.expected_message(),
+        alice.get_field(&F::ALICE_EMAIL()).expected_message()
+    );
+    assert_ne!(
+        field.expected_second(),
+        alice.get_field(&F::ALICE_EMAIL()).expected_second()
+    );
+
+    let expected = ResponseAccountState {
+        state: locked.clone().into(),
+        notifications: vec![NotificationMessage::FieldVerificationFailed {
+            context: alice.context.clone(),
+            field: F::ALICE_EMAIL(),
+        }],
+    };
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    let expected = ResponseAccountState {
+        state: locked.clone().into(),
+        notifications: vec![NotificationMessage::FieldLocked {
+            context: alice.context.clone(),
+            field: F::ALICE_EMAIL(),
+            locked_until: field.locked_until.unwrap(),
+        }],
+    };
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Even the new, valid challenge is ignored during the cooldown.
+    injector
+        .send(ExternalMessage {
+            origin: ExternalMessageType::Email("alice@email.com".to_string()),
+            id: MessageId::from(0u32),
+            timestamp: Timestamp::now(),
+            values: field.expected_message().to_message_parts(),
+        })
+        .await;
+
+    // Give the listener time to process the message.
+    sleep(Duration::from_secs(3)).await;
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current, locked);
+
+    // Empty stream.
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[actix::test]
+async fn regenerate_challenge() {
+    let (db, connector, mut api, _) = new_env().await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request.
+    let (key, context, request) = carol();
+    connector.inject(request).await;
+    let states = connector.inserted_states().await;
+    let carol = states[0].clone();
+
+    let email = F::Email("carol@email.com".to_string());
+    let challenge = carol.get_field(&email).expected_message().value.clone();
+    let message = RegenerateChallenge::message(&context, &challenge);
+
+    // Regenerating requires a subscription to the identity.
+    let regenerate = RegenerateChallenge {
+        context: context.clone(),
+        field: email.clone(),
+        signature: hex::encode(key.sign(message.as_bytes()).to_bytes()),
+    };
+
+    stream.send(regenerate.to_ws()).await.unwrap();
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut stream, context.clone()).await;
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(carol.clone()))
+    );
+
+    // Signed by a different key.
+    let other = SigningKey::from_bytes(&[8; 32]);
+    stream
+        .send(
+            RegenerateChallenge {
+                signature: hex::encode(other.sign(message.as_bytes()).to_bytes()),
+                ..regenerate.clone()
+            }
+            .to_ws(),
+        )
+        .await
+        .unwrap();
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    stream.send(regenerate.to_ws()).await.unwrap();
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+
+    // The challenges of the field were replaced.
+    let current = db.fetch_judgement_state(&context).await.unwrap().unwrap();
+    let field = current.get_field(&email);
+    assert_ne!(
+        field.expected_message(),
+        carol.get_field(&email).expected_message()
+    );
+    assert_ne!(
+        field.expected_second(),
+        carol.get_field(&email).expected_second()
+    );
+
+    let expected = ResponseAccountState {
+        state: current.clone().into(),
+        notifications: vec![NotificationMessage::ChallengeRegenerated {
+            context: context.clone(),
+            field: email.clone(),
+        }],
+    };
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // The signature cannot be replayed, since it references the replaced
+    // challenge.
+    stream.send(regenerate.to_ws()).await.unwrap();
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    // Fields without a challenge cannot be regenerated.
+    stream
+        .send(
+            RegenerateChallenge {
+                field: F::DisplayName("Carol".to_string()),
+                ..regenerate.clone()
+            }
+            .to_ws(),
+        )
+        .await
+        .unwrap();
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    // Empty stream.
+    assert!(stream.next().now_or_never().is_none());
+}
+
 #[actix::test]
 async fn verify_valid_message_duplicate_account_name() {
     let (_db, connector, mut api, injector) = new_env().await;
@@ -524,6 +881,7 @@ async fn verify_valid_message_awaiting_second_challenge() {
 
     // Verify second challenge.
     let challenge = VerifyChallenge {
+        context: Some(alice.context.clone()),
         entry: F::ALICE_EMAIL(),
         challenge: alice
             .get_field(&F::ALICE_EMAIL())
@@ -638,6 +996,7 @@ async fn verify_invalid_message_awaiting_second_challenge() {
 
     // Send invalid second challenge.
     let challenge = VerifyChallenge {
+        context: Some(alice.context.clone()),
         entry: F::ALICE_EMAIL(),
         challenge: "INVALID".to_string(),
     };
@@ -651,6 +1010,9 @@ async fn verify_invalid_message_awaiting_second_challenge() {
 
     assert_eq!(res.status(), StatusCode::OK);
 
+    // The failed attempt is counted.
+    *alice.get_field_mut(&F::ALICE_EMAIL()).failed_attempts_mut() = 1;
+
     // Check for `SecondFieldVerified` notification.
     let expected = ResponseAccountState {
         state: alice.clone().into(),
@@ -675,6 +1037,159 @@ async fn verify_invalid_message_awaiting_second_challenge() {
     assert!(stream.next().now_or_never().is_none());
 }
 
+#[actix::test]
+async fn verify_second_challenge_scoped_to_context() {
+    let (db, connector, api, _) = new_env().await;
+
+    // Bob specifies the same email address as Alice.
+    let mut request = JudgementRequest::bob();
+    request
+        .accounts
+        .insert(AccountType::Email, "alice@email.com".to_string());
+
+    // Insert judgement requests.
+    connector.inject(alice_judgement_request()).await;
+    connector
+        .inject(WatcherMessage::new_judgement_request(request))
+        .await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+    let bob = states[1].clone();
+
+    // The second challenge of Alice is not valid for Bob.
+    let verified = db
+        .verify_second_challenge(VerifyChallenge {
+            context: Some(bob.context.clone()),
+            entry: F::ALICE_EMAIL(),
+            challenge: alice
+                .get_field(&F::ALICE_EMAIL())
+                .expected_second()
+                .value
+                .clone(),
+        })
+        .await
+        .unwrap();
+
+    assert!(!verified);
+
+    // Only Bob's failed attempt is counted.
+    let current = db
+        .fetch_judgement_state(&bob.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current.get_field(&F::ALICE_EMAIL()).failed_attempts, 1);
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current, alice);
+
+    // Requests without a context are rejected by default, also by the
+    // database.
+    let res = db
+        .verify_second_challenge(VerifyChallenge {
+            context: None,
+            entry: F::ALICE_EMAIL(),
+            challenge: alice
+                .get_field(&F::ALICE_EMAIL())
+                .expected_second()
+                .value
+                .clone(),
+        })
+        .await;
+    assert!(res.is_err());
+
+    let mut res = api
+        .post("/api/verify_second_challenge")
+        .send_json(&serde_json::json!({
+            "entry": F::ALICE_EMAIL(),
+            "challenge": alice.get_field(&F::ALICE_EMAIL()).expected_second().value,
+        }))
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let resp: JsonResult<bool> = res.json().await.unwrap();
+    assert!(matches!(resp, JsonResult::Err(_)));
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current, alice);
+}
+
+#[actix::test]
+async fn verify_second_challenge_verified_field_not_locked() {
+    let (db, connector, _api, _) = new_env_with_failed_attempts(Some(FailedAttemptsConfig {
+        max_attempts: 2,
+        cooldown: 3600,
+        notify_admins: false,
+    }))
+    .await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    // Verify the second challenge.
+    let verified = db
+        .verify_second_challenge(VerifyChallenge {
+            context: Some(alice.context.clone()),
+            entry: F::ALICE_EMAIL(),
+            challenge: alice
+                .get_field(&F::ALICE_EMAIL())
+                .expected_second()
+                .value
+                .clone(),
+        })
+        .await
+        .unwrap();
+
+    assert!(verified);
+
+    let expected = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        expected
+            .get_field(&F::ALICE_EMAIL())
+            .expected_second()
+            .is_verified
+    );
+
+    // Wrong guesses against the verified challenge are ignored.
+    for _ in 0..3 {
+        let verified = db
+            .verify_second_challenge(VerifyChallenge {
+                context: Some(alice.context.clone()),
+                entry: F::ALICE_EMAIL(),
+                challenge: "invalid".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert!(!verified);
+    }
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    let field = current.get_field(&F::ALICE_EMAIL());
+    assert!(!field.is_locked());
+    assert_eq!(field.failed_attempts, 0);
+    assert_eq!(current, expected);
+}
+
 #[actix::test]
 async fn verify_full_identity() {
     let (db, connector, mut api, _injector) = new_env().await;
@@ -807,6 +1322,7 @@ async fn verify_full_identity() {
         .set_verified();
 
     db.verify_second_challenge(VerifyChallenge {
+        context: Some(alice.context.clone()),
         entry: F::ALICE_EMAIL(),
         challenge: alice
             .get_field(&F::ALICE_EMAIL())
@@ -895,3 +1411,247 @@ async fn verify_full_identity() {
     // Empty stream.
     assert!(stream_alice.next().now_or_never().is_none());
 }
+
+fn ws_request(id: &str, op: WsOperation) -> Message {
+    WsRequest {
+        version: WS_PROTOCOL_VERSION,
+        id: Some(id.to_string()),
+        op,
+    }
+    .to_ws()
+}
+
+fn ws_response(id: &str, result: JsonResult<WsReply>) -> WsEnvelope {
+    WsEnvelope::Response {
+        version: WS_PROTOCOL_VERSION,
+        id: Some(id.to_string()),
+        result,
+    }
+}
+
+fn ws_envelope(frame: Option<Result<Frame, ProtocolError>>) -> WsEnvelope {
+    match frame.unwrap().unwrap() {
+        Frame::Text(t) => serde_json::from_slice(&t).unwrap(),
+        _ => panic!(),
+    }
+}
+
+#[actix::test]
+async fn versioned_subscriptions() {
+    let (db, connector, mut api, _) = new_env().await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let subscribe = WsOperation::Subscribe {
+        context: alice.context.clone(),
+    };
+
+    // The current state follows the response. Subscribing twice is allowed.
+    for id in ["1", "2"] {
+        stream
+            .send(ws_request(id, subscribe.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            ws_envelope(stream.next().await),
+            ws_response(
+                id,
+                JsonResult::Ok(WsReply::Subscribed(alice.context.clone()))
+            )
+        );
+
+        match ws_envelope(stream.next().await) {
+            WsEnvelope::Update {
+                version,
+                event_id,
+                message,
+            } => {
+                assert_eq!(version, WS_PROTOCOL_VERSION);
+                assert!(event_id.is_some());
+                assert_eq!(
+                    message,
+                    ResponseAccountState::with_no_notifications(alice.clone())
+                );
+            }
+            _ => panic!(),
+        }
+    }
+
+    // There is no judgement request from Bob.
+    stream
+        .send(ws_request(
+            "3",
+            WsOperation::Subscribe {
+                context: IdentityContext::bob(),
+            },
+        ))
+        .await
+        .unwrap();
+    assert!(matches!(
+        ws_envelope(stream.next().await),
+        WsEnvelope::Response { id: Some(id), result: JsonResult::Err(_), .. } if id == "3"
+    ));
+
+    stream
+        .send(ws_request("4", WsOperation::List))
+        .await
+        .unwrap();
+    assert_eq!(
+        ws_envelope(stream.next().await),
+        ws_response(
+            "4",
+            JsonResult::Ok(WsReply::Subscriptions(vec![alice.context.clone()]))
+        )
+    );
+
+    stream
+        .send(ws_request("5", WsOperation::Ping))
+        .await
+        .unwrap();
+    assert_eq!(
+        ws_envelope(stream.next().await),
+        ws_response("5", JsonResult::Ok(WsReply::Pong))
+    );
+
+    // Changes are sent once, despite subscribing twice.
+    let _ = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Email]),
+    )
+    .await;
+
+    match ws_envelope(stream.next().await) {
+        WsEnvelope::Update { message, .. } => {
+            assert_eq!(
+                message.notifications,
+                vec![NotificationMessage::ManuallyVerified {
+                    context: alice.context.clone(),
+                    field: RawFieldName::Email,
+                }]
+            );
+        }
+        _ => panic!(),
+    }
+
+    sleep(Duration::from_secs(2)).await;
+    assert!(stream.next().now_or_never().is_none());
+
+    // Unsubscribe.
+    let unsubscribe = WsOperation::Unsubscribe {
+        context: alice.context.clone(),
+    };
+
+    stream
+        .send(ws_request("6", unsubscribe.clone()))
+        .await
+        .unwrap();
+    assert_eq!(
+        ws_envelope(stream.next().await),
+        ws_response(
+            "6",
+            JsonResult::Ok(WsReply::Unsubscribed(alice.context.clone()))
+        )
+    );
+
+    stream.send(ws_request("7", unsubscribe)).await.unwrap();
+    assert!(matches!(
+        ws_envelope(stream.next().await),
+        WsEnvelope::Response {
+            result: JsonResult::Err(_),
+            ..
+        }
+    ));
+
+    // No more updates.
+    let _ = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Matrix]),
+    )
+    .await;
+
+    sleep(Duration::from_secs(2)).await;
+    assert!(stream.next().now_or_never().is_none());
+
+    // Unsupported protocol version.
+    stream
+        .send(Message::Text(
+            r#"{"version":2,"id":"8","op":"ping"}"#.to_string().into(),
+        ))
+        .await
+        .unwrap();
+    assert!(matches!(
+        ws_envelope(stream.next().await),
+        WsEnvelope::Response { id: Some(id), result: JsonResult::Err(_), .. } if id == "8"
+    ));
+}
+
+#[actix::test]
+async fn versioned_history() {
+    let (db, connector, mut api, _) = new_env().await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let _ = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Email]),
+    )
+    .await;
+
+    // No subscription is required.
+    stream
+        .send(ws_request(
+            "1",
+            WsOperation::History {
+                context: alice.context.clone(),
+                query: EventHistoryQuery {
+                    limit: Some(1),
+                    ..Default::default()
+                },
+            },
+        ))
+        .await
+        .unwrap();
+
+    match ws_envelope(stream.next().await) {
+        WsEnvelope::Response {
+            id,
+            result: JsonResult::Ok(WsReply::History(history)),
+            ..
+        } => {
+            assert_eq!(id, Some("1".to_string()));
+            assert_eq!(history.events.len(), 1);
+            assert_eq!(
+                history.events[0].message,
+                NotificationMessage::ManuallyVerified {
+                    context: alice.context.clone(),
+                    field: RawFieldName::Email,
+                }
+            );
+        }
+        _ => panic!(),
+    }
+
+    // There is no judgement request from Bob.
+    stream
+        .send(ws_request(
+            "2",
+            WsOperation::History {
+                context: IdentityContext::bob(),
+                query: EventHistoryQuery::default(),
+            },
+        ))
+        .await
+        .unwrap();
+    assert!(matches!(
+        ws_envelope(stream.next().await),
+        WsEnvelope::Response { id: Some(id), result: JsonResult::Err(_), .. } if id == "2"
+    ));
+}
@@ -0,0 +1,55 @@
+use super::*;
+use crate::primitives::{ExternalMessage, ExternalMessageType, MessageId, Timestamp};
+
+#[actix::test]
+async fn metrics_endpoint() {
+    let (db, connector, api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let alice = db
+        .fetch_judgement_state(&IdentityContext::alice())
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Verify Twitter.
+    let msg = ExternalMessage {
+        origin: ExternalMessageType::Twitter("@alice".to_string()),
+        id: MessageId::from(0u32),
+        timestamp: Timestamp::now(),
+        values: alice
+            .get_field(&F::ALICE_TWITTER())
+            .expected_message()
+            .to_message_parts(),
+    };
+    db.verify_message(&msg).await.unwrap();
+
+    let mut res = api.get("/metrics").send().await.unwrap();
+    assert!(res.status().is_success());
+
+    let body = String::from_utf8(res.body().await.unwrap().to_vec()).unwrap();
+    assert!(body.contains("registrar_pending_requests{chain=\"polkadot\"} 1\n"));
+    assert!(body.contains("registrar_pending_requests{chain=\"kusama\"} 0\n"));
+    assert!(
+        body.contains("registrar_verifications_total{adapter=\"twitter\",outcome=\"verified\"}")
+    );
+}
+
+#[actix::test]
+async fn health_endpoints() {
+    let (_db, _connector, api, _) = new_env().await;
+
+    let mut res = api.get("/livez").send().await.unwrap();
+    assert!(res.status().is_success());
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["status"], "ok");
+
+    // The database is reachable.
+    let mut res = api.get("/readyz").send().await.unwrap();
+    assert!(res.status().is_success());
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["database"]["status"], "ok");
+    assert!(body["watchers"].is_object());
+    assert!(body["adapters"].is_object());
+}
@@ -2,7 +2,7 @@ use super::*;
 use crate::api::{JsonResult, ResponseAccountState};
 use crate::connector::DisplayNameEntry;
 use crate::display_name::DisplayNameVerifier;
-use crate::primitives::{IdentityContext, IdentityFieldValue};
+use crate::primitives::{ChainName, IdentityContext, IdentityFieldValue};
 use crate::DisplayNameConfig;
 use futures::StreamExt;
 
@@ -103,3 +103,75 @@ async fn invalid_display_name() {
     // Empty stream.
     assert!(stream.next().now_or_never().is_none());
 }
+
+#[actix::test]
+async fn confusable_display_name() {
+    let (db, connector, mut api, _) = new_env().await;
+    // Jaro similarities never exceed the limit, only the confusables check
+    // applies.
+    let verifier = DisplayNameVerifier::new(
+        db.clone(),
+        DisplayNameConfig {
+            enabled: true,
+            limit: 1.0,
+        },
+    );
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Pre-fill database with names which look like "Alice".
+    let confusables = vec![
+        // Cyrillic 'А'.
+        DisplayNameEntry::from("\u{0410}lice"),
+        // Zero width space.
+        DisplayNameEntry::from("Al\u{200B}ice"),
+        // Fullwidth letters.
+        DisplayNameEntry::from("\u{FF21}\u{FF4C}\u{FF49}\u{FF43}\u{FF45}"),
+        // Bidi override.
+        DisplayNameEntry::from("\u{202D}Alice\u{202C}"),
+        // Capital 'I' instead of 'l'.
+        DisplayNameEntry::from("AIice"),
+    ];
+
+    for name in &confusables {
+        db.insert_display_name(name).await.unwrap();
+    }
+
+    for name in ["Alicee", "Bob"] {
+        db.insert_display_name(&DisplayNameEntry::from(name))
+            .await
+            .unwrap();
+    }
+
+    // Names are compared after normalization.
+    let violations = verifier
+        .check_similarities("Ａlice", ChainName::Polkadot, None)
+        .await
+        .unwrap();
+    assert_eq!(violations, confusables);
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let mut alice = states[0].clone();
+    verifier.verify_display_name(&alice).await.unwrap();
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut stream, IdentityContext::alice()).await;
+
+    // Set expected result.
+    let field = alice.get_field_mut(&IdentityFieldValue::DisplayName("Alice".to_string()));
+    let (passed, violations) = field.expected_display_name_check_mut();
+    *passed = false;
+    *violations = confusables;
+
+    let expected = ResponseAccountState {
+        state: alice.into(),
+        notifications: vec![],
+    };
+
+    // Check expected state.
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Empty stream.
+    assert!(stream.next().now_or_never().is_none());
+}
@@ -32,6 +32,11 @@ async fn run_mocker() -> Result<()> {
             enabled: true,
             limit: 0.85,
         },
+        pgp: None,
+        allow_unscoped_second_challenge: false,
+        admin_api: None,
+        webhooks: None,
+        rate_limit: None,
     };
 
     info!("Starting mock adapter and session notifier instances");
@@ -67,7 +72,7 @@ async fn run_mocker() -> Result<()> {
 
         if reset == 0 {
             warn!("Resetting Identity");
-            db.delete_judgement(&alice.context).await.unwrap();
+            assert!(db.delete_judgement(&alice.context).await.unwrap());
 
             alice = JudgementState::alice();
             // Set display name to valid.
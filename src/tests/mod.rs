@@ -4,25 +4,34 @@ use crate::api::{JsonResult, ResponseAccountState};
 use crate::connector::{AccountType, JudgementRequest, WatcherMessage};
 use crate::database::Database;
 use crate::notifier::run_session_notifier;
-use crate::primitives::{IdentityContext, IdentityFieldValue};
+use crate::primitives::{ChainAddress, ChainName, IdentityContext, IdentityFieldValue};
+use crate::webhooks::WebhookDispatcher;
 use crate::{api::tests::run_test_server, connector::tests::ConnectorMocker};
+use crate::{FailedAttemptsConfig, WebhooksConfig};
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
 use actix_http::ws::Codec;
 use actix_http::ws::{Frame, ProtocolError};
 use actix_test::TestServer;
 use actix_web_actors::ws::Message;
+use ed25519_dalek::SigningKey;
 use futures::{FutureExt, SinkExt, StreamExt};
 use rand::{thread_rng, Rng};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashMap;
 use tokio::time::{sleep, Duration};
 
+mod api_account_key;
+mod api_admin;
+mod api_identity;
 mod api_judgement_state;
+mod api_monitoring;
 mod background_tasks;
 mod display_name_verification;
 mod explicit;
 mod live_mocker;
 mod process_admin_cmds;
+mod webhooks;
 
 // Convenience type
 pub type F = IdentityFieldValue;
@@ -71,17 +80,57 @@ pub fn bob_judgement_request() -> WatcherMessage {
     WatcherMessage::new_judgement_request(JudgementRequest::bob())
 }
 
+// An identity with an ed25519 account key.
+pub fn carol() -> (SigningKey, IdentityContext, WatcherMessage) {
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let address = ChainAddress::from_public_key(0, &key.verifying_key().to_bytes());
+
+    let request = WatcherMessage::new_judgement_request(JudgementRequest {
+        address: address.clone(),
+        accounts: HashMap::from([(AccountType::Email, "carol@email.com".to_string())]),
+    });
+
+    (
+        key,
+        IdentityContext::new(address, ChainName::Polkadot),
+        request,
+    )
+}
+
 // async fn new_env() -> (TestServer, ConnectorMocker, MessageInjector) {
 async fn new_env() -> (Database, ConnectorMocker, TestServer, MessageInjector) {
+    new_env_with_failed_attempts(None).await
+}
+
+async fn new_env_with_failed_attempts(
+    failed_attempts: Option<FailedAttemptsConfig>,
+) -> (Database, ConnectorMocker, TestServer, MessageInjector) {
+    setup_env(failed_attempts, None).await
+}
+
+async fn new_env_with_webhooks(
+    webhooks: WebhooksConfig,
+) -> (Database, ConnectorMocker, TestServer, MessageInjector) {
+    setup_env(None, Some(webhooks)).await
+}
+
+async fn setup_env(
+    failed_attempts: Option<FailedAttemptsConfig>,
+    webhooks: Option<WebhooksConfig>,
+) -> (Database, ConnectorMocker, TestServer, MessageInjector) {
     // Setup MongoDb database.
     let random: u32 = thread_rng().gen_range(u32::MIN..u32::MAX);
-    let db = Database::new(
+    let mut db = Database::new(
         "mongodb://localhost:27017/?replicaSet=rs0",
         &format!("registrar_test_{}", random),
     )
     .await
     .unwrap();
 
+    if let Some(config) = failed_attempts {
+        db = db.with_failed_attempts(config);
+    }
+
     db.connectivity_check()
         .await
         .expect("Failed to open database");
@@ -94,6 +143,10 @@ async fn new_env() -> (Database, ConnectorMocker, TestServer, MessageInjector) {
     let listener = AdapterListener::new(db.clone()).await;
     listener.start_message_adapter(injector.clone(), 1).await;
 
+    if let Some(config) = webhooks {
+        WebhookDispatcher::new(db.clone(), config).unwrap().run();
+    }
+
     let t_db = db.clone();
     actix::spawn(async move {
         run_session_notifier(t_db, actor).await;
@@ -1,8 +1,13 @@
 use super::*;
-use crate::adapters::admin::{process_admin, Command, RawFieldName, Response};
+use crate::adapters::admin::{
+    process_admin, process_admin_as, Command, IdentityStatus, RawFieldName, Response, Role,
+    TargetAddress,
+};
 use crate::api::{JsonResult, ResponseAccountState};
+use crate::database::{StatsInterval, StatsQuery};
 use crate::primitives::{
-    IdentityContext, IdentityFieldValue, JudgementStateBlanked, NotificationMessage,
+    ChainAddress, ChainName, ExternalMessage, ExternalMessageType, IdentityContext,
+    IdentityFieldValue, JudgementStateBlanked, MessageId, NotificationMessage, Timestamp,
 };
 use futures::{FutureExt, StreamExt};
 
@@ -16,8 +21,14 @@ async fn command_status() {
     let alice = states[0].clone();
 
     // Request status.
-    let res = process_admin(&db, Command::Status(alice.context.address.clone())).await;
-    assert_eq!(res, Response::Status(JudgementStateBlanked::from(alice)));
+    let res = process_admin(&db, Command::Status(alice.context.address.clone().into())).await;
+    assert_eq!(
+        res,
+        Response::Status(Box::new(IdentityStatus {
+            state: JudgementStateBlanked::from(alice),
+            notes: vec![],
+        }))
+    );
 }
 
 #[actix::test]
@@ -43,7 +54,7 @@ async fn command_verify_multiple_challenge_types() {
     let resp = process_admin(
         &db,
         Command::Verify(
-            alice.context.address.clone(),
+            alice.context.address.clone().into(),
             vec![RawFieldName::DisplayName, RawFieldName::Email],
         ),
     )
@@ -52,7 +63,7 @@ async fn command_verify_multiple_challenge_types() {
     assert_eq!(
         resp,
         Response::Verified(
-            alice.context.address.clone(),
+            alice.context.clone(),
             vec![RawFieldName::DisplayName, RawFieldName::Email]
         )
     );
@@ -100,13 +111,16 @@ async fn command_verify_multiple_challenge_types() {
     // Manually verify twitter field.
     let resp = process_admin(
         &db,
-        Command::Verify(alice.context.address.clone(), vec![RawFieldName::Twitter]),
+        Command::Verify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::Twitter],
+        ),
     )
     .await;
 
     assert_eq!(
         resp,
-        Response::Verified(alice.context.address.clone(), vec![RawFieldName::Twitter])
+        Response::Verified(alice.context.clone(), vec![RawFieldName::Twitter])
     );
 
     // Twitter and matrix are now verified.
@@ -141,7 +155,7 @@ async fn command_verify_unsupported_field() {
         .inject(WatcherMessage::new_judgement_request({
             let mut req = JudgementRequest::alice();
             req.accounts
-                .insert(AccountType::Web, "alice.com".to_string());
+                .insert(AccountType::LegalName, "Alice".to_string());
             req
         }))
         .await;
@@ -160,18 +174,21 @@ async fn command_verify_unsupported_field() {
     // Manually verify.
     let resp = process_admin(
         &db,
-        Command::Verify(alice.context.address.clone(), vec![RawFieldName::Web]),
+        Command::Verify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::LegalName],
+        ),
     )
     .await;
 
     assert_eq!(
         resp,
-        Response::Verified(alice.context.address.clone(), vec![RawFieldName::Web])
+        Response::Verified(alice.context.clone(), vec![RawFieldName::LegalName])
     );
 
-    // Web is now verified.
+    // Legal name is now verified.
     let is_verified = alice
-        .get_field_mut(&IdentityFieldValue::Web("alice.com".to_string()))
+        .get_field_mut(&IdentityFieldValue::LegalName("Alice".to_string()))
         .expected_unsupported_mut();
     *is_verified = Some(true);
 
@@ -180,7 +197,7 @@ async fn command_verify_unsupported_field() {
         state: alice.clone().into(),
         notifications: vec![NotificationMessage::ManuallyVerified {
             context: alice.context.clone(),
-            field: RawFieldName::Web,
+            field: RawFieldName::LegalName,
         }],
     };
 
@@ -213,14 +230,14 @@ async fn command_verify_all() {
     // Manually verify.
     let resp = process_admin(
         &db,
-        Command::Verify(alice.context.address.clone(), vec![RawFieldName::All]),
+        Command::Verify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::All],
+        ),
     )
     .await;
 
-    assert_eq!(
-        resp,
-        Response::FullyVerified(alice.context.address.clone(),)
-    );
+    assert_eq!(resp, Response::FullyVerified(alice.context.clone(),));
 
     // Expected event on stream.
     let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
@@ -305,12 +322,592 @@ async fn command_verify_missing_field() {
     // Manually verify a field that does not exist.
     let resp = process_admin(
         &db,
-        Command::Verify(alice.context.address.clone(), vec![RawFieldName::Email]),
+        Command::Verify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::Email],
+        ),
     )
     .await;
 
-    assert_eq!(resp, Response::IdentityNotFound);
+    assert_eq!(resp, Response::IdentityNotFound(alice.context.clone()));
 
     // Empty stream.
     assert!(stream.next().now_or_never().is_none());
 }
+
+#[actix::test]
+async fn command_unverify_and_reset() {
+    let (db, connector, _api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    // Manually verify.
+    let resp = process_admin(
+        &db,
+        Command::Verify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::Email, RawFieldName::Twitter],
+        ),
+    )
+    .await;
+
+    assert_eq!(
+        resp,
+        Response::Verified(
+            alice.context.clone(),
+            vec![RawFieldName::Email, RawFieldName::Twitter]
+        )
+    );
+
+    // Unverify email.
+    let resp = process_admin(
+        &db,
+        Command::Unverify(
+            alice.context.address.clone().into(),
+            vec![RawFieldName::Email],
+        ),
+    )
+    .await;
+
+    assert_eq!(
+        resp,
+        Response::Unverified(alice.context.clone(), vec![RawFieldName::Email])
+    );
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The email challenges were replaced, Twitter is still verified.
+    let email = current.get_field(&F::ALICE_EMAIL());
+    assert!(!email.challenge.is_verified());
+    assert_ne!(
+        email.expected_message(),
+        alice.get_field(&F::ALICE_EMAIL()).expected_message()
+    );
+    assert_ne!(
+        email.expected_second(),
+        alice.get_field(&F::ALICE_EMAIL()).expected_second()
+    );
+    assert!(current
+        .get_field(&F::ALICE_TWITTER())
+        .challenge
+        .is_verified());
+
+    let display_name = current
+        .get_field(&F::ALICE_DISPLAY_NAME())
+        .challenge
+        .clone();
+
+    // Reset the full identity.
+    let resp = process_admin(&db, Command::Reset(alice.context.address.clone().into())).await;
+    assert_eq!(resp, Response::Reset(alice.context.clone()));
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // The display name check is not re-run on reset, so it is kept.
+    assert!(current
+        .fields
+        .iter()
+        .filter(|f| f.value != F::ALICE_DISPLAY_NAME())
+        .all(|f| !f.challenge.is_verified()));
+    assert_eq!(
+        current.get_field(&F::ALICE_DISPLAY_NAME()).challenge,
+        display_name
+    );
+    assert_ne!(
+        current.get_field(&F::ALICE_TWITTER()).expected_message(),
+        alice.get_field(&F::ALICE_TWITTER()).expected_message()
+    );
+
+    // Unknown identity.
+    let resp = process_admin(&db, Command::Reset(IdentityContext::bob().address.into())).await;
+    assert_eq!(resp, Response::IdentityNotFound(IdentityContext::bob()));
+}
+
+#[actix::test]
+async fn command_pending_search_and_remove() {
+    let (db, connector, _api, _) = new_env().await;
+
+    // Insert judgement requests.
+    connector.inject(alice_judgement_request()).await;
+    connector.inject(bob_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+    let bob = states[1].clone();
+
+    // Both requests are pending.
+    let resp = process_admin(&db, Command::Pending(None)).await;
+    match resp {
+        Response::Pending(pending) => {
+            let contexts: Vec<IdentityContext> = pending.into_iter().map(|p| p.context).collect();
+            assert_eq!(contexts, vec![alice.context.clone(), bob.context.clone()]);
+        }
+        _ => panic!("unexpected response: {:?}", resp),
+    }
+
+    // Search is case insensitive.
+    let resp = process_admin(&db, Command::Search("ALICE@email.com".to_string())).await;
+    assert_eq!(resp, Response::Search(vec![alice.context.clone()]));
+
+    let resp = process_admin(&db, Command::Search("eve@email.com".to_string())).await;
+    assert_eq!(resp, Response::Search(vec![]));
+
+    // Remove Alice.
+    let resp = process_admin(&db, Command::Remove(alice.context.address.clone().into())).await;
+    assert_eq!(resp, Response::Removed(alice.context.clone()));
+
+    let resp = process_admin(&db, Command::Remove(alice.context.address.clone().into())).await;
+    assert_eq!(resp, Response::IdentityNotFound(alice.context.clone()));
+
+    assert!(db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix::test]
+async fn command_resend() {
+    let (db, connector, mut api, injector) = new_env().await;
+    let mut stream = api.ws_at("/api/account_status").await.unwrap();
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let mut alice = states[0].clone();
+
+    // Subscribe to endpoint.
+    let resp = subscribe_context(&mut stream, IdentityContext::alice()).await;
+    assert_eq!(
+        resp,
+        JsonResult::Ok(ResponseAccountState::with_no_notifications(alice.clone()))
+    );
+
+    // The email address is not verified yet.
+    let resp = process_admin(&db, Command::Resend(alice.context.address.clone().into())).await;
+    assert_eq!(resp, Response::NoPendingSecondChallenge);
+
+    // Verify the email address.
+    injector
+        .send(ExternalMessage {
+            origin: ExternalMessageType::Email("alice@email.com".to_string()),
+            id: MessageId::from(0u32),
+            timestamp: Timestamp::now(),
+            values: alice
+                .get_field(&F::ALICE_EMAIL())
+                .expected_message()
+                .to_message_parts(),
+        })
+        .await;
+
+    alice
+        .get_field_mut(&F::ALICE_EMAIL())
+        .expected_message_mut()
+        .set_verified();
+
+    // `FieldVerified` and `AwaitingSecondChallenge` notifications.
+    let _: JsonResult<ResponseAccountState> = stream.next().await.into();
+    let _: JsonResult<ResponseAccountState> = stream.next().await.into();
+
+    let resp = process_admin(&db, Command::Resend(alice.context.address.clone().into())).await;
+    assert_eq!(resp, Response::SecondChallengeResent(alice.context.clone()));
+
+    let expected = ResponseAccountState {
+        state: alice.clone().into(),
+        notifications: vec![NotificationMessage::AwaitingSecondChallenge {
+            context: alice.context.clone(),
+            field: F::ALICE_EMAIL(),
+        }],
+    };
+
+    let resp: JsonResult<ResponseAccountState> = stream.next().await.into();
+    assert_eq!(resp, JsonResult::Ok(expected));
+
+    // Empty stream.
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[actix::test]
+async fn command_explicit_chain() {
+    let (db, connector, _api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    // Explicit chain.
+    let resp = process_admin(&db, Command::Status(alice.context.clone().into())).await;
+    assert_eq!(
+        resp,
+        Response::Status(Box::new(IdentityStatus {
+            state: JudgementStateBlanked::from(alice.clone()),
+            notes: vec![],
+        }))
+    );
+
+    // The address is not valid on Kusama.
+    let target = TargetAddress {
+        address: alice.context.address.clone(),
+        chain: Some(ChainName::Kusama),
+    };
+    let resp = process_admin(&db, Command::Reset(target.clone())).await;
+    assert_eq!(resp, Response::InvalidAddress(target));
+
+    // Generic SS58 prefix, valid on both chains.
+    let generic =
+        ChainAddress::from("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string());
+    let resp = process_admin(&db, Command::Remove(generic.clone().into())).await;
+    assert_eq!(
+        resp,
+        Response::AmbiguousChain(generic, vec![ChainName::Polkadot, ChainName::Kusama])
+    );
+
+    // Nothing was changed.
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current, alice);
+}
+
+#[actix::test]
+async fn command_notes() {
+    let (db, connector, _api, _) = new_env().await;
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let resp = process_admin(&db, Command::Notes(alice.context.clone().into())).await;
+    assert_eq!(resp, Response::Notes(alice.context.clone(), vec![]));
+
+    // Add notes.
+    let resp = process_admin_as(
+        &db,
+        "@admin:matrix.org",
+        Role::Verifier,
+        Command::Note(
+            alice.context.clone().into(),
+            "LinkedIn chat confirmed legal name".to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(resp, Response::NoteAdded(alice.context.clone()));
+
+    let resp = process_admin_as(
+        &db,
+        "@other:matrix.org",
+        Role::Superadmin,
+        Command::Note(
+            alice.context.clone().into(),
+            "Domain matches verified email".to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(resp, Response::NoteAdded(alice.context.clone()));
+
+    // Viewers cannot add notes.
+    let resp = process_admin_as(
+        &db,
+        "@viewer:matrix.org",
+        Role::Viewer,
+        Command::Note(alice.context.clone().into(), "Nope".to_string()),
+    )
+    .await;
+    assert_eq!(resp, Response::PermissionDenied(Role::Viewer));
+
+    // Unknown identity.
+    let resp = process_admin(
+        &db,
+        Command::Note(IdentityContext::bob().into(), "Unknown".to_string()),
+    )
+    .await;
+    assert_eq!(resp, Response::IdentityNotFound(IdentityContext::bob()));
+
+    // Notes are listed in order.
+    let notes = match process_admin(&db, Command::Notes(alice.context.clone().into())).await {
+        Response::Notes(context, notes) => {
+            assert_eq!(context, alice.context);
+            notes
+        }
+        _ => panic!(),
+    };
+
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0].author, "@admin:matrix.org");
+    assert_eq!(notes[0].text, "LinkedIn chat confirmed legal name");
+    assert_eq!(notes[1].author, "@other:matrix.org");
+    assert_eq!(notes[1].text, "Domain matches verified email");
+
+    // The status includes the notes.
+    let resp = process_admin(&db, Command::Status(alice.context.clone().into())).await;
+    assert_eq!(
+        resp,
+        Response::Status(Box::new(IdentityStatus {
+            state: JudgementStateBlanked::from(alice),
+            notes,
+        }))
+    );
+}
+
+// Admins who can request and approve full verifications.
+fn with_approval(db: Database) -> Database {
+    db.with_approval_window(60)
+        .with_approval_admins(&HashMap::from([
+            (
+                "first".to_string(),
+                vec!["@first:matrix.org".to_string(), "token:first".to_string()],
+            ),
+            ("second".to_string(), vec!["@second:matrix.org".to_string()]),
+            ("third".to_string(), vec!["@third:matrix.org".to_string()]),
+        ]))
+}
+
+#[actix::test]
+async fn command_verify_all_requires_approval() {
+    let (db, connector, _api, _) = new_env().await;
+    let db = with_approval(db);
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let verify_all = Command::Verify(alice.context.clone().into(), vec![RawFieldName::All]);
+    let approve = Command::Approve(alice.context.clone().into());
+
+    // Nothing to approve yet.
+    let resp = process_admin_as(&db, "@second:matrix.org", Role::Superadmin, approve.clone()).await;
+    assert_eq!(resp, Response::NoPendingApproval(alice.context.clone()));
+
+    // Request the full verification.
+    let resp = process_admin_as(
+        &db,
+        "@first:matrix.org",
+        Role::Superadmin,
+        verify_all.clone(),
+    )
+    .await;
+    let approval = match resp {
+        Response::ApprovalRequested(approval) => approval,
+        _ => panic!(),
+    };
+    assert_eq!(approval.context, alice.context);
+    assert_eq!(approval.requested_by, "first");
+
+    // Only one pending approval per identity.
+    let resp = process_admin_as(&db, "@second:matrix.org", Role::Superadmin, verify_all).await;
+    assert_eq!(resp, Response::ApprovalAlreadyPending(approval.clone()));
+
+    let resp = process_admin(&db, Command::Approvals).await;
+    assert_eq!(resp, Response::Approvals(vec![approval]));
+
+    // Not verified yet.
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!current.is_fully_verified);
+
+    // The requesting admin cannot approve.
+    let resp = process_admin_as(&db, "@first:matrix.org", Role::Superadmin, approve.clone()).await;
+    assert_eq!(resp, Response::SelfApproval(alice.context.clone()));
+
+    // Not even with different credentials.
+    let resp = process_admin_as(&db, "token:first", Role::Superadmin, approve.clone()).await;
+    assert_eq!(resp, Response::SelfApproval(alice.context.clone()));
+
+    // Unlisted admins cannot approve.
+    let resp = process_admin_as(&db, "cli:second", Role::Superadmin, approve.clone()).await;
+    assert_eq!(
+        resp,
+        Response::UnknownApprovalAdmin("cli:second".to_string())
+    );
+
+    // Verifiers cannot approve.
+    let resp = process_admin_as(&db, "@verifier:matrix.org", Role::Verifier, approve.clone()).await;
+    assert_eq!(resp, Response::PermissionDenied(Role::Verifier));
+
+    // A second admin approves.
+    let resp = process_admin_as(&db, "@second:matrix.org", Role::Superadmin, approve.clone()).await;
+    assert_eq!(resp, Response::FullyVerified(alice.context.clone()));
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(current.is_fully_verified);
+    assert!(current.issue_judgement_at.is_some());
+
+    // The approval was consumed.
+    let resp = process_admin(&db, Command::Approvals).await;
+    assert_eq!(resp, Response::Approvals(vec![]));
+
+    let resp = process_admin_as(&db, "@third:matrix.org", Role::Superadmin, approve).await;
+    assert_eq!(resp, Response::NoPendingApproval(alice.context.clone()));
+}
+
+#[actix::test]
+async fn command_cancel_approval() {
+    let (db, connector, _api, _) = new_env().await;
+    let db = with_approval(db);
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let resp = process_admin_as(
+        &db,
+        "@first:matrix.org",
+        Role::Superadmin,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::All]),
+    )
+    .await;
+    assert!(matches!(resp, Response::ApprovalRequested(_)));
+
+    let resp = process_admin(&db, Command::Cancel(alice.context.clone().into())).await;
+    assert_eq!(resp, Response::ApprovalCancelled(alice.context.clone()));
+
+    let resp = process_admin(&db, Command::Cancel(alice.context.clone().into())).await;
+    assert_eq!(resp, Response::NoPendingApproval(alice.context.clone()));
+
+    let resp = process_admin_as(
+        &db,
+        "@second:matrix.org",
+        Role::Superadmin,
+        Command::Approve(alice.context.clone().into()),
+    )
+    .await;
+    assert_eq!(resp, Response::NoPendingApproval(alice.context.clone()));
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!current.is_fully_verified);
+}
+
+#[actix::test]
+async fn command_verify_remaining_fields() {
+    let (db, connector, _api, _) = new_env().await;
+    let db = with_approval(db);
+
+    // Insert judgement request.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let remaining = vec![
+        RawFieldName::DisplayName,
+        RawFieldName::Email,
+        RawFieldName::Twitter,
+        RawFieldName::Matrix,
+    ];
+
+    // Verifying the remaining fields completes the identity, just like `all`.
+    let resp = process_admin_as(
+        &db,
+        "@verifier:matrix.org",
+        Role::Verifier,
+        Command::Verify(alice.context.clone().into(), remaining.clone()),
+    )
+    .await;
+    assert_eq!(resp, Response::PermissionDenied(Role::Verifier));
+
+    let resp = process_admin_as(
+        &db,
+        "@first:matrix.org",
+        Role::Superadmin,
+        Command::Verify(alice.context.clone().into(), remaining),
+    )
+    .await;
+    assert!(matches!(resp, Response::ApprovalRequested(_)));
+
+    let current = db
+        .fetch_judgement_state(&alice.context)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!current.is_fully_verified);
+    assert!(!current.get_field(&F::ALICE_EMAIL()).challenge.is_verified());
+
+    // Individual fields can still be verified by verifiers.
+    let resp = process_admin_as(
+        &db,
+        "@verifier:matrix.org",
+        Role::Verifier,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::Email]),
+    )
+    .await;
+    assert_eq!(
+        resp,
+        Response::Verified(alice.context.clone(), vec![RawFieldName::Email])
+    );
+}
+
+#[actix::test]
+async fn command_stats() {
+    let (db, connector, _api, _) = new_env().await;
+
+    let resp = process_admin(&db, Command::Stats(StatsQuery::default())).await;
+    assert_eq!(resp, Response::Stats(vec![]));
+
+    // Insert judgement request and fully verify it.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let resp = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::All]),
+    )
+    .await;
+    assert_eq!(resp, Response::FullyVerified(alice.context.clone()));
+
+    let resp = process_admin(
+        &db,
+        Command::Stats(StatsQuery {
+            chain: Some(ChainName::Polkadot),
+            interval: Some(StatsInterval::Day),
+            ..Default::default()
+        }),
+    )
+    .await;
+
+    let stats = match resp {
+        Response::Stats(stats) => stats,
+        resp => panic!("unexpected response: {:?}", resp),
+    };
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].requests_received, 1);
+    assert_eq!(stats[0].manual_verifications, 1);
+    assert!(stats[0].median_time_to_verify.is_some());
+    assert!(stats[0].fields.is_empty());
+
+    let resp = process_admin(
+        &db,
+        Command::Stats(StatsQuery {
+            chain: Some(ChainName::Kusama),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert_eq!(resp, Response::Stats(vec![]));
+}
@@ -0,0 +1,166 @@
+use super::*;
+use crate::adapters::admin::{process_admin, Command, RawFieldName, Response};
+use crate::primitives::{ChainName, NotificationMessage};
+use crate::webhooks::{
+    sign, WebhookPayload, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use crate::WebhookConfig;
+use actix_test::start;
+use actix_web::{web, App, HttpRequest, HttpResponse};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const SECRET: &str = "webhook-secret";
+
+// Headers and body of a received webhook request.
+type Received = (HashMap<String, String>, web::Bytes);
+
+// Local receiver of webhook requests. Requests to `/broken` always fail.
+fn receiver(received: Arc<Mutex<Vec<Received>>>) -> TestServer {
+    start(move || {
+        let received = Arc::clone(&received);
+
+        App::new()
+            .route(
+                "/hook",
+                web::post().to(move |req: HttpRequest, body: web::Bytes| {
+                    let received = Arc::clone(&received);
+                    async move {
+                        let headers = req
+                            .headers()
+                            .iter()
+                            .map(|(name, value)| {
+                                (name.to_string(), value.to_str().unwrap().to_string())
+                            })
+                            .collect();
+
+                        received.lock().unwrap().push((headers, body));
+                        HttpResponse::Ok().finish()
+                    }
+                }),
+            )
+            .route(
+                "/broken",
+                web::post().to(|| async { HttpResponse::ServiceUnavailable().finish() }),
+            )
+    })
+}
+
+fn subscriber(name: &str, url: String, events: &[&str], chains: Vec<ChainName>) -> WebhookConfig {
+    WebhookConfig {
+        name: name.to_string(),
+        url,
+        secret: SECRET.to_string(),
+        events: events.iter().map(|event| event.to_string()).collect(),
+        chains,
+    }
+}
+
+#[actix::test]
+async fn deliver_events_to_webhooks() {
+    let received = Arc::new(Mutex::new(vec![]));
+    let server = receiver(Arc::clone(&received));
+
+    let (db, connector, _api, _) = new_env_with_webhooks(WebhooksConfig {
+        subscribers: vec![
+            subscriber(
+                "crm",
+                server.url("/hook"),
+                &["full_manual_verification"],
+                vec![ChainName::Polkadot],
+            ),
+            // Never receives any events.
+            subscriber(
+                "kusama",
+                server.url("/hook"),
+                &["full_manual_verification"],
+                vec![ChainName::Kusama],
+            ),
+            subscriber(
+                "broken",
+                server.url("/broken"),
+                &["full_manual_verification"],
+                vec![],
+            ),
+        ],
+        max_attempts: Some(2),
+        backoff: Some(0),
+        timeout: None,
+    })
+    .await;
+
+    // Insert judgement request and fully verify it.
+    connector.inject(alice_judgement_request()).await;
+    let states = connector.inserted_states().await;
+    let alice = states[0].clone();
+
+    let resp = process_admin(
+        &db,
+        Command::Verify(alice.context.clone().into(), vec![RawFieldName::All]),
+    )
+    .await;
+    assert_eq!(resp, Response::FullyVerified(alice.context.clone()));
+
+    // Wait for the deliveries.
+    let mut dead_letters = vec![];
+    for _ in 0..10 {
+        sleep(Duration::from_secs(1)).await;
+
+        dead_letters = db.fetch_webhook_dead_letters().await.unwrap();
+        if !received.lock().unwrap().is_empty() && !dead_letters.is_empty() {
+            break;
+        }
+    }
+
+    let expected_event = NotificationMessage::FullManualVerification {
+        context: alice.context.clone(),
+    };
+
+    // Only the matching subscriber received the event, exactly once.
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 1);
+
+    let (headers, body) = &received[0];
+    let header = |name: &str| headers[name].as_str();
+
+    let payload: WebhookPayload = serde_json::from_slice(body).unwrap();
+    assert_eq!(payload.event, expected_event);
+    assert_eq!(header(EVENT_HEADER), "full_manual_verification");
+    assert_eq!(header(DELIVERY_HEADER), payload.id);
+    let timestamp = header(TIMESTAMP_HEADER).parse().unwrap();
+    assert_eq!(
+        header(SIGNATURE_HEADER),
+        sign(SECRET, timestamp, body).unwrap()
+    );
+
+    // The failed delivery is recorded.
+    assert_eq!(dead_letters.len(), 1);
+
+    let dead_letter = &dead_letters[0];
+    assert_eq!(dead_letter.subscriber, "broken");
+    assert_eq!(dead_letter.url, server.url("/broken"));
+    assert_eq!(dead_letter.attempts, 2);
+    assert_eq!(dead_letter.payload.id, payload.id);
+    assert_eq!(dead_letter.payload.event, expected_event);
+    assert!(dead_letter.last_error.contains("503"));
+
+    // The payload carries the time of the event.
+    let history = db
+        .fetch_event_history(&alice.context, &Default::default())
+        .await
+        .unwrap();
+    let event = history
+        .events
+        .iter()
+        .find(|event| event.id == payload.id)
+        .unwrap();
+    assert_eq!(event.message, expected_event);
+    assert_eq!(payload.timestamp, event.timestamp);
+
+    // Handled events are tracked per subscriber, including failed deliveries
+    // and filtered events. Ids of the same length are ordered.
+    for name in ["crm", "kusama", "broken"] {
+        let cursor = db.fetch_webhook_cursor(name).await.unwrap().unwrap();
+        assert!(cursor >= payload.id);
+    }
+}
@@ -0,0 +1,509 @@
+use crate::database::Database;
+use crate::metrics::metrics;
+use crate::primitives::{ChainName, NotificationMessage, Timestamp};
+use crate::{Result, WebhookConfig, WebhooksConfig};
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Client;
+use sha2::Sha256;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+pub const SIGNATURE_HEADER: &str = "x-registrar-signature";
+pub const EVENT_HEADER: &str = "x-registrar-event";
+pub const DELIVERY_HEADER: &str = "x-registrar-delivery";
+pub const TIMESTAMP_HEADER: &str = "x-registrar-timestamp";
+
+const DEFAULT_MAX_ATTEMPTS: usize = 5;
+const DEFAULT_BACKOFF: u64 = 2;
+const DEFAULT_TIMEOUT: u64 = 10;
+// Minimum age of events before they are delivered, in seconds. See
+// `Database::fetch_events_for_delivery`.
+const SETTLE_DELAY: u64 = 2;
+// Maximum number of events fetched per iteration.
+const BATCH_SIZE: i64 = 100;
+// Upper limit of the delay between two attempts, in seconds.
+const MAX_BACKOFF: u64 = 3_600;
+
+/// Body of a webhook request.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebhookPayload {
+    // Id of the event in the event log. Identical for all attempts, so
+    // receivers can discard duplicates.
+    pub id: String,
+    // Time of the event, not of the delivery.
+    pub timestamp: Timestamp,
+    pub event: NotificationMessage,
+}
+
+/// Delivery which failed on all attempts.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebhookDeadLetter {
+    pub subscriber: String,
+    pub url: String,
+    pub payload: WebhookPayload,
+    pub attempts: usize,
+    pub last_error: String,
+    pub timestamp: Timestamp,
+}
+
+/// Delivers the events of the event log to the configured webhook
+/// subscribers.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    db: Database,
+    client: WebhookClient,
+    subscribers: Arc<Vec<WebhookConfig>>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: Database, config: WebhooksConfig) -> Result<Self> {
+        validate(&config)?;
+
+        Ok(WebhookDispatcher {
+            db,
+            client: WebhookClient {
+                client: Client::builder()
+                    .timeout(Duration::from_secs(
+                        config.timeout.unwrap_or(DEFAULT_TIMEOUT),
+                    ))
+                    .build()?,
+                max_attempts: config.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS).max(1),
+                backoff: config.backoff.unwrap_or(DEFAULT_BACKOFF),
+            },
+            subscribers: Arc::new(config.subscribers),
+        })
+    }
+    /// Starts a delivery loop for each subscriber. Each loop persists the id
+    /// of the last handled event, so events are delivered at least once, even
+    /// across restarts, and a slow subscriber does not hold up the others.
+    pub fn run(self) {
+        for subscriber in self.subscribers.iter().cloned() {
+            let dispatcher = self.clone();
+
+            actix::spawn(async move {
+                dispatcher.run_subscriber(subscriber).await;
+            });
+        }
+    }
+    async fn run_subscriber(&self, subscriber: WebhookConfig) {
+        let mut cursor = None;
+        loop {
+            match self.local(&subscriber, &mut cursor).await {
+                Ok(()) => {}
+                Err(err) => error!(
+                    "Error in delivery loop of webhook {}: {:?}",
+                    subscriber.name, err
+                ),
+            }
+
+            sleep(Duration::from_secs(1)).await;
+        }
+    }
+    async fn local(&self, subscriber: &WebhookConfig, cursor: &mut Option<String>) -> Result<()> {
+        // New subscribers start with the events inserted after their
+        // registration, instead of the full event log.
+        if cursor.is_none() {
+            *cursor = match self.db.fetch_webhook_cursor(&subscriber.name).await? {
+                Some(event_id) => Some(event_id),
+                None => {
+                    let latest = self.db.fetch_latest_event_id().await?;
+                    if let Some(event_id) = &latest {
+                        self.db
+                            .set_webhook_cursor(&subscriber.name, event_id)
+                            .await?;
+                    }
+
+                    latest
+                }
+            };
+        }
+
+        let events = self
+            .db
+            .fetch_events_for_delivery(cursor.as_deref(), SETTLE_DELAY, BATCH_SIZE)
+            .await?;
+
+        for event in events {
+            let kind = event_type(&event.message)?;
+            if is_subscribed(subscriber, &kind, event.message.context().chain) {
+                let payload = WebhookPayload {
+                    id: event.id.clone(),
+                    timestamp: event.timestamp,
+                    event: event.message,
+                };
+
+                self.deliver_or_record(subscriber, &kind, payload).await;
+            }
+
+            // The event is handled, either delivered or recorded as dead
+            // letter.
+            self.db
+                .set_webhook_cursor(&subscriber.name, &event.id)
+                .await?;
+            *cursor = Some(event.id);
+        }
+
+        Ok(())
+    }
+    async fn deliver_or_record(
+        &self,
+        subscriber: &WebhookConfig,
+        kind: &str,
+        payload: WebhookPayload,
+    ) {
+        let outcome = match self.client.deliver(subscriber, kind, &payload).await {
+            Ok(()) => "delivered",
+            Err(err) => {
+                error!(
+                    "Failed to deliver event {} to webhook {}: {:?}",
+                    payload.id, subscriber.name, err
+                );
+
+                let dead_letter = WebhookDeadLetter {
+                    subscriber: subscriber.name.clone(),
+                    url: subscriber.url.clone(),
+                    payload,
+                    attempts: self.client.max_attempts,
+                    last_error: err.to_string(),
+                    timestamp: Timestamp::now(),
+                };
+
+                if let Err(err) = self.db.insert_webhook_dead_letter(&dead_letter).await {
+                    error!("Failed to record webhook dead letter: {:?}", err);
+                }
+
+                "failed"
+            }
+        };
+
+        metrics()
+            .webhook_deliveries
+            .with_label_values(&[&subscriber.name, outcome])
+            .inc();
+    }
+}
+
+#[derive(Clone)]
+struct WebhookClient {
+    client: Client,
+    max_attempts: usize,
+    // Seconds until the first retry.
+    backoff: u64,
+}
+
+impl WebhookClient {
+    /// Sends the payload until the subscriber accepts it with a `2xx`
+    /// status. Returns the error of the last attempt if all attempts failed.
+    async fn deliver(
+        &self,
+        subscriber: &WebhookConfig,
+        kind: &str,
+        payload: &WebhookPayload,
+    ) -> Result<()> {
+        let body = serde_json::to_vec(payload)?;
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            // Each attempt is signed with its own timestamp, so receivers can
+            // reject replayed deliveries.
+            let timestamp = Timestamp::now().raw();
+            let signature = sign(&subscriber.secret, timestamp, &body)?;
+
+            let result = match self
+                .client
+                .post(&subscriber.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, kind)
+                .header(DELIVERY_HEADER, &payload.id)
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) => Err(anyhow!("Unexpected status code: {}", resp.status())),
+                Err(err) => Err(err.into()),
+            };
+
+            if attempt >= self.max_attempts {
+                return result;
+            }
+
+            let delay = backoff_delay(self.backoff, attempt);
+            warn!(
+                "Delivery of event {} to webhook {} failed (attempt {}), retrying in {} seconds: {:?}",
+                payload.id, subscriber.name, attempt, delay, result
+            );
+
+            sleep(Duration::from_secs(delay)).await;
+        }
+    }
+}
+
+/// Rejects unknown event types in the filters, since a misspelled type would
+/// silently filter out all events.
+fn validate(config: &WebhooksConfig) -> Result<()> {
+    for subscriber in &config.subscribers {
+        if let Some(event) = subscriber
+            .events
+            .iter()
+            .find(|event| !NotificationMessage::TYPES.contains(&event.as_str()))
+        {
+            return Err(anyhow!(
+                "Unknown event type '{}' in the filter of webhook {}",
+                event,
+                subscriber.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The `type` of the event as specified in the event filter, e.g.
+/// `identity_fully_verified`.
+fn event_type(event: &NotificationMessage) -> Result<String> {
+    serde_json::to_value(event)?
+        .get("type")
+        .and_then(|kind| kind.as_str())
+        .map(|kind| kind.to_string())
+        .ok_or_else(|| anyhow!("Event has no type"))
+}
+
+fn is_subscribed(subscriber: &WebhookConfig, kind: &str, chain: ChainName) -> bool {
+    (subscriber.events.is_empty() || subscriber.events.iter().any(|event| event == kind))
+        && (subscriber.chains.is_empty() || subscriber.chains.contains(&chain))
+}
+
+/// Value of the signature header: `sha256=<HEX>`, the HMAC-SHA256 of
+/// `<TIMESTAMP>.<BODY>` keyed with the secret of the subscriber, where the
+/// timestamp is the value of the timestamp header.
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> Result<String> {
+    let mut mac: Hmac<Sha256> = Hmac::new_from_slice(secret.as_bytes())?;
+    mac.update(format!("{}.", timestamp).as_bytes());
+    mac.update(body);
+
+    Ok(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Seconds to wait after the given (failed) attempt, doubled after each one.
+fn backoff_delay(backoff: u64, attempt: usize) -> u64 {
+    let exponent = attempt.saturating_sub(1).min(32) as u32;
+    backoff
+        .saturating_mul(2u64.saturating_pow(exponent))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::IdentityContext;
+    use actix_test::{start, TestServer};
+    use actix_web::{web, App, HttpRequest, HttpResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const SECRET: &str = "webhook-secret";
+
+    // Local webhook receiver, which rejects the first `failures` requests.
+    #[derive(Clone, Default)]
+    struct Receiver {
+        failures: Arc<AtomicUsize>,
+        received: Arc<Mutex<Vec<(String, String, WebhookPayload)>>>,
+    }
+
+    impl Receiver {
+        fn start(&self) -> TestServer {
+            let receiver = self.clone();
+
+            start(move || {
+                let receiver = receiver.clone();
+
+                App::new().route(
+                    "/hook",
+                    web::post().to(move |req: HttpRequest, body: web::Bytes| {
+                        let receiver = receiver.clone();
+                        async move {
+                            if receiver
+                                .failures
+                                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| {
+                                    failures.checked_sub(1)
+                                })
+                                .is_ok()
+                            {
+                                return HttpResponse::InternalServerError().finish();
+                            }
+
+                            let header = |name: &str| {
+                                req.headers()
+                                    .get(name)
+                                    .and_then(|value| value.to_str().ok())
+                                    .unwrap_or_default()
+                                    .to_string()
+                            };
+
+                            // Verify the signature of the timestamp and body.
+                            let timestamp = header(TIMESTAMP_HEADER).parse().unwrap();
+                            assert_eq!(
+                                header(SIGNATURE_HEADER),
+                                sign(SECRET, timestamp, &body).unwrap()
+                            );
+
+                            receiver.received.lock().unwrap().push((
+                                header(EVENT_HEADER),
+                                header(DELIVERY_HEADER),
+                                serde_json::from_slice(&body).unwrap(),
+                            ));
+
+                            HttpResponse::Ok().finish()
+                        }
+                    }),
+                )
+            })
+        }
+    }
+
+    fn subscriber(server: &TestServer) -> WebhookConfig {
+        WebhookConfig {
+            name: "test".to_string(),
+            url: server.url("/hook"),
+            secret: SECRET.to_string(),
+            events: vec![],
+            chains: vec![],
+        }
+    }
+
+    fn payload() -> WebhookPayload {
+        WebhookPayload {
+            id: "6423a1b6a2b1c8a1f4e3d2c1".to_string(),
+            timestamp: Timestamp::now(),
+            event: NotificationMessage::IdentityFullyVerified {
+                context: IdentityContext::alice(),
+            },
+        }
+    }
+
+    fn client(max_attempts: usize) -> WebhookClient {
+        WebhookClient {
+            client: Client::new(),
+            max_attempts,
+            backoff: 0,
+        }
+    }
+
+    #[test]
+    fn event_filter() {
+        let mut subscriber = WebhookConfig {
+            name: "test".to_string(),
+            url: "http://localhost/hook".to_string(),
+            secret: SECRET.to_string(),
+            events: vec![],
+            chains: vec![],
+        };
+
+        let kind = event_type(&payload().event).unwrap();
+        assert_eq!(kind, "identity_fully_verified");
+
+        assert!(is_subscribed(&subscriber, &kind, ChainName::Polkadot));
+
+        subscriber.events = vec!["judgement_provided".to_string()];
+        assert!(!is_subscribed(&subscriber, &kind, ChainName::Polkadot));
+
+        subscriber
+            .events
+            .push("identity_fully_verified".to_string());
+        assert!(is_subscribed(&subscriber, &kind, ChainName::Polkadot));
+
+        subscriber.chains = vec![ChainName::Kusama];
+        assert!(!is_subscribed(&subscriber, &kind, ChainName::Polkadot));
+        assert!(is_subscribed(&subscriber, &kind, ChainName::Kusama));
+    }
+
+    #[test]
+    fn validate_event_types() {
+        // All types are known variants.
+        for kind in NotificationMessage::TYPES {
+            let err = serde_json::from_value::<NotificationMessage>(
+                serde_json::json!({ "type": kind, "value": {} }),
+            )
+            .unwrap_err();
+            assert!(!err.to_string().contains("unknown variant"), "{}", kind);
+        }
+
+        let mut config = WebhooksConfig {
+            subscribers: vec![WebhookConfig {
+                name: "test".to_string(),
+                url: "http://localhost/hook".to_string(),
+                secret: SECRET.to_string(),
+                events: vec!["judgement_provided".to_string()],
+                chains: vec![],
+            }],
+            max_attempts: None,
+            backoff: None,
+            timeout: None,
+        };
+        validate(&config).unwrap();
+
+        config.subscribers[0]
+            .events
+            .push("judgment_provided".to_string());
+        assert!(validate(&config).is_err());
+    }
+
+    #[test]
+    fn signature_and_backoff() {
+        // Key and data of RFC 4231, test case 2, prefixed with the timestamp.
+        assert_eq!(
+            sign("Jefe", 1_700_000_000, b"what do ya want for nothing?").unwrap(),
+            "sha256=1cdd0650c8be1cb0974b1788d458b1e781206cfef59b85faafc582d2e182c57e"
+        );
+
+        assert_eq!(backoff_delay(2, 1), 2);
+        assert_eq!(backoff_delay(2, 2), 4);
+        assert_eq!(backoff_delay(2, 4), 16);
+        assert_eq!(backoff_delay(2, 100), MAX_BACKOFF);
+        assert_eq!(backoff_delay(0, 3), 0);
+    }
+
+    #[actix::test]
+    async fn deliver_with_retries() {
+        let receiver = Receiver::default();
+        let server = receiver.start();
+        let subscriber = subscriber(&server);
+        let payload = payload();
+
+        // Succeeds on the third attempt.
+        receiver.failures.store(2, Ordering::SeqCst);
+        client(3)
+            .deliver(&subscriber, "identity_fully_verified", &payload)
+            .await
+            .unwrap();
+
+        let received = receiver.received.lock().unwrap().clone();
+        assert_eq!(
+            received,
+            vec![(
+                "identity_fully_verified".to_string(),
+                payload.id.clone(),
+                payload.clone()
+            )]
+        );
+
+        // Fails on all attempts.
+        receiver.failures.store(2, Ordering::SeqCst);
+        let res = client(2)
+            .deliver(&subscriber, "identity_fully_verified", &payload)
+            .await;
+        assert!(res.is_err());
+        assert_eq!(receiver.received.lock().unwrap().len(), 1);
+    }
+}
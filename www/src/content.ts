@@ -1,4 +1,4 @@
-import { CheckDisplayNameResult, DisplayNameChallenge, GenericMessage, State, Violation } from './json';
+import { CheckDisplayNameResult, Context, DisplayNameChallenge, FieldValue, GenericMessage, State, Violation } from './json';
 import { NotificationHandler } from './notifications.js';
 
 const BadgeVerified = `
@@ -30,7 +30,12 @@ export class ContentManager {
     div_verification_overview: HTMLElement;
     div_email_second_challenge: HTMLElement;
     div_unsupported_overview: HTMLElement;
+    div_account_key: HTMLElement;
     notifications: NotificationHandler;
+    // Requests a new challenge for the field, set by the `ActionListener`.
+    onRegenerate: ((context: Context, field: FieldValue, challenge: string) => void) | null;
+    // Proves the ownership of the account key, set by the `ActionListener`.
+    onProveAccountKey: ((context: Context) => Promise<void>) | null;
 
     constructor(handler: NotificationHandler) {
         // Register relevant elements.
@@ -62,7 +67,13 @@ export class ContentManager {
             document
                 .getElementById("div-unsupported-overview")!;
 
+        this.div_account_key =
+            document
+                .getElementById("div-account-key")!;
+
         this.notifications = handler;
+        this.onRegenerate = null;
+        this.onProveAccountKey = null;
     }
 
     setButtonLoadingSpinner() {
@@ -89,6 +100,7 @@ export class ContentManager {
     }
     processVerificationOverviewTable(state: State) {
         let table = "";
+        let challenges: [FieldValue, string][] = [];
 
         let counter = 1;
         for (let field of state.fields) {
@@ -98,7 +110,7 @@ export class ContentManager {
                     if (field.challenge.content.second && !field.challenge.content.second!.is_verified) {
                         validity = BadgeVerifiedHalf;
 
-                        this.setEmailSecondChallengeContent(field.value.value);
+                        this.setEmailSecondChallengeContent(state.context, field.value.value);
                     } else {
                         validity = BadgeVerified;
 
@@ -110,6 +122,14 @@ export class ContentManager {
                     validity = BadgeUnverified;
                 }
 
+                // Pending challenges can be replaced, e.g. once expired.
+                let action = "";
+                let second = field.challenge.content.second;
+                if (!field.challenge.content.expected.is_verified || (second && !second.is_verified)) {
+                    action = `<button type="button" class="btn btn-sm btn-outline-light btn-regenerate"
+                        data-field="${counter - 1}">New challenge</button>`;
+                }
+
                 // Specify the destination address.
                 let to = "N/A";
                 if (field.value.type == "email") {
@@ -128,9 +148,11 @@ export class ContentManager {
                             <td>${field.value.value}</td>
                             <td>${to}</td>
                             <td>${validity}</td>
+                            <td>${action}</td>
                         </tr>
                     `;
 
+                challenges.push([field.value, field.challenge.content.expected.value]);
                 counter += 1;
             } else if (field.challenge.type == "display_name_check") {
                 let validity;
@@ -147,6 +169,20 @@ export class ContentManager {
 
         // Apply table to the page.
         this.setVerificationOverviewContent(table);
+
+        Array.from(document
+            .getElementsByClassName("btn-regenerate"))
+            .forEach(element => {
+                let button = element as HTMLButtonElement;
+                let [field, challenge] = challenges[Number(button.dataset.field)];
+
+                button.addEventListener("click", (_: Event) => {
+                    button.disabled = true;
+                    if (this.onRegenerate) {
+                        this.onRegenerate(state.context, field, challenge);
+                    }
+                });
+            });
     }
     processUnsupportedOverview(state: State) {
         let unsupported = "";
@@ -213,6 +249,8 @@ export class ContentManager {
                 <p>Send each provided challenge <strong>from</strong> your account <strong>to</strong> the corresponding W3F account.
                     You can just copy and paste the challenge directly.</p>
                 <p><em>Note:</em> Twitter verification can take about 5 minutes.</p>
+                <p>If a challenge expired, request a new one with <em>"New challenge"</em>. The request must be
+                    signed with the account key in your wallet extension.</p>
                 <table id="verification-overview" class="table table-striped table-dark">
                     <thead>
                         <tr>
@@ -222,6 +260,7 @@ export class ContentManager {
                             <th scope="col">From</th>
                             <th scope="col">To</th>
                             <th scope="col">Status</th>
+                            <th scope="col"></th>
                         </tr>
                     </thead>
                     <tbody>
@@ -234,7 +273,7 @@ export class ContentManager {
     wipeVerificationOverviewContent() {
         this.div_verification_overview.innerHTML = "";
     }
-    setEmailSecondChallengeContent(address: string) {
+    setEmailSecondChallengeContent(context: Context, address: string) {
         this.div_email_second_challenge.innerHTML = `
             <div class="col-10">
                 <h2>⚠️️ Additional Challenge</h2>
@@ -271,6 +310,7 @@ export class ContentManager {
                     `;
 
                 let body = JSON.stringify({
+                    context: context,
                     entry: {
                         type: "email",
                         value: address,
@@ -320,6 +360,51 @@ export class ContentManager {
     wipeUnsupportedContent() {
         this.div_unsupported_overview.innerHTML = "";
     }
+    setAccountKeyContent(state: State) {
+        if (state.account_key && state.account_key.nonce.is_verified) {
+            this.div_account_key.innerHTML = `
+                <div class="col-10">
+                    <h2>Account ownership</h2>
+                    <p>${BadgeVerified} The ownership of the account key was proven.</p>
+                </div>
+            `;
+
+            return;
+        }
+
+        this.div_account_key.innerHTML = `
+            <div class="col-10">
+                <h2>Account ownership</h2>
+                <p>Optionally, prove that you own the account by signing a message with the account key in your
+                    wallet extension. Some registrars require this proof before issuing a judgement.</p>
+                <button id="execute-account-key" class="btn btn-primary" type="button">Sign with wallet</button>
+            </div>
+        `;
+
+        let button = document
+            .getElementById("execute-account-key")! as HTMLButtonElement;
+
+        button
+            .addEventListener("click", async (_: Event) => {
+                button.disabled = true;
+                button
+                    .innerHTML = `
+                        <span class="spinner-border spinner-border-sm" role="status" aria-hidden="true"></span>
+                        <span class="visually-hidden"></span>
+                    `;
+
+                // The result is submitted over the websocket stream.
+                if (this.onProveAccountKey) {
+                    await this.onProveAccountKey(state.context);
+                }
+
+                button.disabled = false;
+                button.innerHTML = "Sign with wallet";
+            });
+    }
+    wipeAccountKeyContent() {
+        this.div_account_key.innerHTML = "";
+    }
 }
 
 export function capitalizeFirstLetter(word: string) {
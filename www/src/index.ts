@@ -1,6 +1,7 @@
-import { StateNotification, GenericMessage, Notification, CheckDisplayNameResult, Violation } from "./json";
+import { StateNotification, GenericMessage, Notification, CheckDisplayNameResult, Violation, Context, FieldValue, AccountKeyChallenge } from "./json";
 import { ContentManager, capitalizeFirstLetter, BadgeValid } from './content';
 import { NotificationHandler } from "./notifications";
+import { signMessage } from "./wallet";
 
 interface Config {
     http_url: string;
@@ -17,6 +18,7 @@ class ActionListerner {
     btn_execute_action: HTMLButtonElement;
     manager: ContentManager;
     notifications: NotificationHandler;
+    socket: WebSocket | null;
 
     constructor() {
         // Register relevant elements.
@@ -39,6 +41,14 @@ class ActionListerner {
         const handler = new NotificationHandler;
         this.manager = new ContentManager(handler);
         this.notifications = handler;
+        this.socket = null;
+
+        this.manager.onRegenerate = (context: Context, field: FieldValue, challenge: string) => {
+            this.regenerateChallenge(context, field, challenge);
+        };
+        this.manager.onProveAccountKey = (context: Context) => {
+            return this.proveAccountKey(context);
+        };
 
         // Handler for choosing network, e.g. "Kusama" or "Polkadot".
         document
@@ -130,6 +140,7 @@ class ActionListerner {
 
         if (action == "Check Judgement") {
             const socket = new WebSocket(config.ws_url);
+            this.socket = socket;
 
             window.setInterval(() => {
                 socket.send("heartbeat");
@@ -169,6 +180,49 @@ class ActionListerner {
             })();
         }
     }
+    // Requests a new challenge for the field. The request is signed with the
+    // account key, the new challenge arrives as a regular state notification.
+    async regenerateChallenge(context: Context, field: FieldValue, challenge: string) {
+        const message = `polkadot-registrar:regenerate:${context.chain}:${context.address}:${challenge}`;
+
+        try {
+            const signature = await signMessage(context.address, message);
+            this.socket!.send(JSON.stringify({
+                context: context,
+                field: field,
+                signature: signature,
+            }));
+        } catch (error) {
+            this.notifications.displayError(`Failed to request a new challenge: ${(error as Error).message}`);
+        }
+    }
+    // Signs the account key challenge with the wallet extension and submits
+    // the signature. Success is reported as a state notification.
+    async proveAccountKey(context: Context) {
+        try {
+            let challenge = await postApi("/api/account_key/challenge", { context: context });
+            if (challenge.type == "err") {
+                throw new Error(challenge.message);
+            }
+
+            let content: AccountKeyChallenge = challenge.message;
+            if (content.is_verified) {
+                return;
+            }
+
+            const signature = await signMessage(context.address, content.message);
+            let result = await postApi("/api/account_key/verify", {
+                context: context,
+                signature: signature,
+            });
+
+            if (result.type == "err") {
+                throw new Error(result.message);
+            }
+        } catch (error) {
+            this.notifications.displayError(`Failed to prove the account ownership: ${(error as Error).message}`);
+        }
+    }
     // Handles the display name result received from the server.
     handleDisplayNameCheck(data: GenericMessage, display_name: string) {
         this.manager.wipeIntroduction();
@@ -197,6 +251,7 @@ class ActionListerner {
         this.manager.wipeVerificationOverviewContent();
         this.manager.wipeEmailSecondChallengeContent();
         this.manager.wipeUnsupportedContent();
+        this.manager.wipeAccountKeyContent();
     }
     // Handles the judgement state received from the server.
     handleJudgementState(msg: MessageEvent) {
@@ -208,6 +263,7 @@ class ActionListerner {
             this.manager.setLiveUpdateInfo();
             this.manager.processVerificationOverviewTable(message.state);
             this.manager.processUnsupportedOverview(message.state);
+            this.manager.setAccountKeyContent(message.state);
 
             this.notifications.processNotifications(message.notifications);
 
@@ -228,4 +284,18 @@ class ActionListerner {
     }
 }
 
+// Posts to an API endpoint of the same backend as `http_url`.
+async function postApi(path: string, body: any): Promise<GenericMessage> {
+    let response = await fetch(new URL(path, config.http_url).toString(),
+        {
+            method: "POST",
+            headers: {
+                "Content-Type": "application/json",
+            },
+            body: JSON.stringify(body),
+        });
+
+    return JSON.parse(await response.text());
+}
+
 new ActionListerner();
@@ -13,6 +13,20 @@ export interface State {
     is_fully_verified: boolean;
     completion_timestamp?: any;
     fields: Field[];
+    account_key?: AccountKeyProof | null;
+}
+
+export interface AccountKeyProof {
+    nonce: {
+        value: string;
+        is_verified: boolean;
+    };
+    key_type?: string | null;
+}
+
+export interface AccountKeyChallenge {
+    message: string;
+    is_verified: boolean;
 }
 
 export interface Context {
@@ -127,6 +127,27 @@ function notificationTypeResolver(notification: Notification): [string, string]
                 "bg-info text-dark"
             ]
         }
+        case "field_locked": {
+            let data = notification.value as NotificationFieldContext;
+            return [
+                `${capitalizeFirstLetter(data.field.type)} account "${data.field.value}" is temporarily locked after too many failed attempts. A new challenge has been issued and can be used once the lock expires.`,
+                "bg-danger text-light"
+            ]
+        }
+        case "challenge_expired": {
+            let data = notification.value as NotificationFieldContext;
+            return [
+                `The challenge of ${capitalizeFirstLetter(data.field.type)} account "${data.field.value}" has expired. Please request a new challenge with the "New challenge" button.`,
+                "bg-danger text-light"
+            ]
+        }
+        case "challenge_regenerated": {
+            let data = notification.value as NotificationFieldContext;
+            return [
+                `A new challenge was generated for ${capitalizeFirstLetter(data.field.type)} account "${data.field.value}".`,
+                "bg-info text-dark"
+            ]
+        }
         case "identity_fully_verified": {
             return [
                 `<strong>Verification process completed!</strong> Judgement will be issued in a couple of minutes.`,
@@ -152,6 +173,25 @@ function notificationTypeResolver(notification: Notification): [string, string]
                 "bg-info text-light"
             ]
         }
+        case "manually_unverified": {
+            let data = notification.value as ManuallyVerified;
+            return [
+                `Manually unverified ${capitalizeFirstLetter(data.field)}. A new challenge was generated.`,
+                "bg-info text-light"
+            ]
+        }
+        case "account_key_verified": {
+            return [
+                `The ownership of the account key was proven.`,
+                "bg-success text-light"
+            ]
+        }
+        case "identity_reset": {
+            return [
+                `The identity was reset. New challenges were generated for all fields.`,
+                "bg-info text-light"
+            ]
+        }
         default: {
             throw new Error("unrecognized notification");
         }
@@ -0,0 +1,43 @@
+// Minimal access to the API injected by Polkadot wallet extensions (e.g.
+// polkadot{.js}), so no additional dependencies are required.
+interface InjectedSigner {
+    signRaw(payload: { address: string, data: string, type: string }): Promise<{ signature: string }>;
+}
+
+interface InjectedExtension {
+    signer: InjectedSigner;
+}
+
+interface InjectedProvider {
+    enable(origin: string): Promise<InjectedExtension>;
+}
+
+const APP_NAME = "W3F Registrar";
+
+function toHex(message: string): string {
+    let hex = "0x";
+    new TextEncoder().encode(message).forEach(byte => {
+        hex += ("0" + byte.toString(16)).slice(-2);
+    });
+
+    return hex;
+}
+
+// Signs the message with the account key, using the first available wallet
+// extension. Returns the hex encoded signature.
+export async function signMessage(address: string, message: string): Promise<string> {
+    const injected: { [name: string]: InjectedProvider } = (window as any).injectedWeb3 || {};
+    const names = Object.keys(injected);
+    if (names.length == 0) {
+        throw new Error("No wallet extension found. Please install a Polkadot wallet extension.");
+    }
+
+    const extension = await injected[names[0]].enable(APP_NAME);
+    const result = await extension.signer.signRaw({
+        address: address,
+        data: toHex(message),
+        type: "bytes",
+    });
+
+    return result.signature;
+}